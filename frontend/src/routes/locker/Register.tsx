@@ -15,7 +15,7 @@ const { Content } = Layout;
 interface Locker {
     lockerId: string;
     floor: string;
-    status: 'vacant' | 'occupied' | 'out-of-work';
+    status: 'vacant' | 'occupied' | 'out-of-work' | 'broken' | 'maintenance';
 }
 
 const Register: React.FC = () => {
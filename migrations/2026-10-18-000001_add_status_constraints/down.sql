@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE registration
+    DROP CONSTRAINT registration_status_acceptance_check,
+    DROP CONSTRAINT registration_status_authentication_check,
+    DROP CONSTRAINT registration_status_form_confirmation_check,
+    DROP CONSTRAINT registration_status_registration_complete_check;
+
+ALTER TABLE locker DROP CONSTRAINT locker_status_check;
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+UPDATE locker SET status = 'out-of-work' WHERE status NOT IN ('vacant', 'occupied', 'out-of-work');
+
+ALTER TABLE locker
+    ADD CONSTRAINT locker_status_check
+    CHECK (status IN ('vacant', 'occupied', 'out-of-work'));
+
+ALTER TABLE registration
+    ADD CONSTRAINT registration_status_acceptance_check
+    CHECK (status_acceptance IN ('pending', 'accepted')),
+    ADD CONSTRAINT registration_status_authentication_check
+    CHECK (status_authentication IN ('not_authenticated', 'authenticated')),
+    ADD CONSTRAINT registration_status_form_confirmation_check
+    CHECK (status_form_confirmation IN ('not_confirmed', 'confirmed')),
+    ADD CONSTRAINT registration_status_registration_complete_check
+    CHECK (status_registration_complete IN ('incomplete', 'completed'));
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX assignment_record_active_pair_year_idx;
+DROP INDEX assignment_record_active_locker_year_idx;
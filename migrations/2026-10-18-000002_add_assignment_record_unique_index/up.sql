@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE UNIQUE INDEX assignment_record_active_locker_year_idx
+    ON assignment_record (locker_id, year)
+    WHERE deleted_at IS NULL;
+
+CREATE UNIQUE INDEX assignment_record_active_pair_year_idx
+    ON assignment_record (pair_id, year)
+    WHERE deleted_at IS NULL;
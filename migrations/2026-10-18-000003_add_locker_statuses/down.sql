@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+UPDATE locker SET status = 'out-of-work' WHERE status IN ('broken', 'maintenance');
+
+ALTER TABLE locker DROP CONSTRAINT locker_status_check;
+
+ALTER TABLE locker
+    ADD CONSTRAINT locker_status_check
+    CHECK (status IN ('vacant', 'occupied', 'out-of-work'));
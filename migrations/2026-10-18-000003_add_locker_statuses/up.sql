@@ -0,0 +1,6 @@
+-- Your SQL goes here
+ALTER TABLE locker DROP CONSTRAINT locker_status_check;
+
+ALTER TABLE locker
+    ADD CONSTRAINT locker_status_check
+    CHECK (status IN ('vacant', 'occupied', 'out-of-work', 'broken', 'maintenance'));
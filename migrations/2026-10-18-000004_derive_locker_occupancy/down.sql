@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+UPDATE locker SET status = 'occupied'
+WHERE status = 'vacant'
+    AND locker_id IN (
+        SELECT locker_id FROM assignment_record
+        WHERE deleted_at IS NULL
+            AND year = EXTRACT(YEAR FROM CURRENT_DATE)
+    );
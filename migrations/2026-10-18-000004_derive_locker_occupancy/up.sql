@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- 使用中かどうかは年度ごとの有効なassignment_recordから判定するため、statusには物理的な状態のみを保存する
+UPDATE locker SET status = 'vacant' WHERE status = 'occupied';
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- tokenの有効期限はphaseが更新された時刻から判定する
+ALTER TABLE auth ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+-- ハッシュ値から平文のtokenは復元できないため、認証中のレコードを削除する
+DELETE FROM locker_auth_info;
+DELETE FROM circle_auth_info;
+DELETE FROM auth;
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- tokenは平文で保存せず、SHA-256のハッシュ値(16進数)のみを保存する
+-- 発行済みの平文のtokenは形式が異なり使用済みの空文字とも区別できないため、認証中のレコードを削除する
+DELETE FROM locker_auth_info;
+DELETE FROM circle_auth_info;
+DELETE FROM auth;
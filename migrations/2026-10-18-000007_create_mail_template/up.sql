@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- 管理画面から編集されたメールテンプレート、存在する場合はテンプレートファイルより優先される
+CREATE TABLE mail_template(
+    name TEXT NOT NULL,
+    locale TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    body_text TEXT NOT NULL,
+    body_html TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(name, locale)
+);
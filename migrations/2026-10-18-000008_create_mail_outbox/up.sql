@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- 送信するメールを保存し、バックグラウンドのワーカーが送信する
+CREATE TABLE mail_outbox(
+    mail_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    to_address TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    body_text TEXT NOT NULL,
+    body_html TEXT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    sent_at TIMESTAMPTZ,
+    CONSTRAINT mail_outbox_status_check CHECK (status IN ('pending', 'sent', 'failed'))
+);
+
+CREATE INDEX mail_outbox_pending_index ON mail_outbox (next_attempt_at) WHERE status = 'pending';
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX mail_outbox_student_id_index;
+
+ALTER TABLE mail_outbox
+    DROP COLUMN organization_id,
+    DROP COLUMN auth_id,
+    DROP COLUMN student_id,
+    DROP COLUMN template;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- 送信したメールを履歴として検索できるように、テンプレート名と関連する学籍番号、認証、団体を保存する
+ALTER TABLE mail_outbox
+    ADD COLUMN template TEXT,
+    ADD COLUMN student_id TEXT,
+    ADD COLUMN auth_id UUID,
+    ADD COLUMN organization_id TEXT;
+
+CREATE INDEX mail_outbox_student_id_index ON mail_outbox (student_id);
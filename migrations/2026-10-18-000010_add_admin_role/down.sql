@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE admin
+    DROP CONSTRAINT admin_role_check,
+    DROP COLUMN role;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- 管理者ごとに使用できる管理機能を分ける、既存の管理者は全ての機能を使用できるsuperadminとする
+ALTER TABLE admin
+    ADD COLUMN role TEXT NOT NULL DEFAULT 'superadmin',
+    ADD CONSTRAINT admin_role_check CHECK (role IN ('superadmin', 'locker_staff', 'circle_staff'));
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- 管理者を削除せずにログインできないようにする
+ALTER TABLE admin ADD COLUMN disabled BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- 管理者のログインごとにセッションを保存し、jwtのjtiと照合する
+CREATE TABLE admin_session(
+    session_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    username TEXT NOT NULL REFERENCES admin(username) ON DELETE CASCADE,
+    ip_address TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX admin_session_username_index ON admin_session (username);
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- 管理者のログインの試行を保存し、ユーザーネーム、IPアドレスごとの連続した失敗回数からロックアウトを判定する
+CREATE TABLE login_attempt(
+    attempt_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    username TEXT NOT NULL,
+    ip_address TEXT,
+    result TEXT NOT NULL,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT login_attempt_result_check CHECK (result IN ('success', 'invalid_credentials', 'locked', 'disabled'))
+);
+
+CREATE INDEX login_attempt_username_index ON login_attempt (username, attempted_at);
+CREATE INDEX login_attempt_ip_address_index ON login_attempt (ip_address, attempted_at);
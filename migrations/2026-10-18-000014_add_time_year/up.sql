@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- 受付期間の対象年度、設定されていない場合は今年度を対象とする
+ALTER TABLE time ADD COLUMN year INTEGER;
@@ -1,3 +1,4 @@
 pub mod controller;
+pub mod guard;
 pub mod repository;
 pub mod httpmodels;
\ No newline at end of file
@@ -4,8 +4,9 @@ pub mod locker;
 use crate::adapters::httpmodels::{HealthCheckRequest, DownloadRequest, DownloadResponse};
 use crate::adapters::controller::{locker::*, circle::*};
 use crate::adapters::httpmodels::*;
-use crate::domain::{student::{UserInfo, RepresentativeInfo}, student_pair::PairInfo, assignment::AssignmentInfo, circle::{OrganizationInfo, Organization, OrganizationUpdateInfo}};
-use crate::infrastructure::router::App;
+use crate::adapters::guard::{GuardMessage, AdminUser, SuperAdmin};
+use crate::domain::{admin::{AdminRole, LoginResult}, mail::MailStatus, student::{UserInfo, RepresentativeInfo}, student_pair::PairInfo, assignment::{AssignmentInfo, RolloverAssignment}, locker::{LockerInfo, LockerChange}, circle::{OrganizationInfo, Organization, OrganizationUpdateInfo, AcceptanceStatus, AuthenticationStatus, FormConfirmationStatus, RegistrationCompleteStatus}};
+use crate::infrastructure::{router::App, models::{MailOutbox, AdminSession, LoginAttempt}};
 use crate::usecase::{
     student::StudentUsecase,
     student_pair::StudentPairUsecase,
@@ -15,16 +16,21 @@ use crate::usecase::{
     organization::OrganizationUsecase,
     registration::RegistrationUsecase,
     time::TimeUsecase,
+    auth::AuthUsecase,
+    mail_template::MailTemplateUsecase,
+    mail_outbox::MailOutboxUsecase,
+    admin::AdminUsecase,
+    admin_session::AdminSessionUsecase,
+    login_attempt::LoginAttemptUsecase,
 };
-use crate::utils::{jwt::decode_jwt, verify_password::verify_password_hash};
-use rocket::{get, post, serde::json::Json, State, http::{Status, CookieJar}};
+use crate::utils::{verify_password::verify_password_hash, password_policy::validate_password, archive::{to_csv, to_zip}, student_identity::StudentIdFlow};
+use rocket::{catch, get, post, put, patch, delete, serde::json::Json, State, http::Status, Request};
 use utoipa::OpenApi;
 use regex::Regex;
 use dotenv::dotenv;
-use std::{env, io::{Cursor, Write}};
-use chrono::Utc;
-use csv::Writer;
-use zip::{write::{FileOptions, ExtendedFileOptions}, CompressionMethod, ZipWriter};
+use std::{env, net::IpAddr};
+use chrono::{SecondsFormat, Utc};
+use uuid::Uuid;
 
 #[derive(OpenApi)]
 #[openapi(
@@ -32,21 +38,53 @@ use zip::{write::{FileOptions, ExtendedFileOptions}, CompressionMethod, ZipWrite
         get_healthcheck,
         post_healthcheck,
         download,
+        auth_sweep_status,
+        mail_template_list,
+        mail_template_get,
+        mail_template_put,
+        mail_template_delete,
+        mail_outbox_list,
+        mail_outbox_retry,
+        mail_outbox_retry_failed,
+        mail_log_search,
+        admin_list,
+        admin_create,
+        admin_update,
+        admin_password_reset,
+        admin_password_change,
+        admin_session_list,
+        admin_session_revoke,
+        admin_session_revoke_all,
+        login_attempt_list,
         token_generator,
         main_auth,
         co_auth,
         auth_check,
+        locker_resend,
         locker_register,
+        cancel_token_generator,
+        cancel,
         login,
         logout,
         user_search,
         availability,
+        period_setting_get,
         reset,
+        rollover,
+        period_setting_post,
+        locker_list,
+        locker_create,
+        locker_update,
+        locker_delete,
+        locker_import,
+        assignment_create,
+        assignment_update,
         update_entry,
         update_token_generator,
         register_token_generator,
         circle_main_auth,
         circle_co_auth,
+        circle_resend,
         circle_status,
         access_setting_post,
         access_setting_get,
@@ -61,12 +99,49 @@ use zip::{write::{FileOptions, ExtendedFileOptions}, CompressionMethod, ZipWrite
         AuthCheckResponse,
         AssignmentInfo,
         LockerResisterRequest,
+        LockerCancelTokenGenRequest,
         LoginFormRequest,
         LockerStatusResponse,
         UserSearchResponse,
+        UserAffiliation,
+        LockerPeriodSetting,
         LockerResetRequest,
+        LockerRolloverRequest,
+        RolloverAssignment,
+        LockerRolloverResponse,
+        LockerDetail,
+        LockerListResponse,
+        LockerCreateRequest,
+        LockerUpdateRequest,
+        LockerInfo,
+        LockerChange,
+        LockerImportResponse,
+        LockerAssignmentRequest,
+        LockerReassignmentRequest,
         DownloadRequest,
         DownloadResponse,
+        AuthSweepStatusResponse,
+        MailTemplateSummary,
+        MailTemplateListResponse,
+        MailTemplateResponse,
+        MailTemplateRequest,
+        MailOutboxItem,
+        MailOutboxListResponse,
+        MailOutboxRetryResponse,
+        AdminRole,
+        AdminSummary,
+        AdminListResponse,
+        AdminCreateRequest,
+        AdminUpdateRequest,
+        AdminPasswordResetRequest,
+        AdminPasswordChangeRequest,
+        AdminSessionItem,
+        AdminSessionListResponse,
+        AdminSessionRevokeResponse,
+        LoginAttemptItem,
+        LoginAttemptListResponse,
+        LockerResendRequest,
+        CircleResendRequest,
         RepresentativeInfo,
         Organization,
         OrganizationInfo,
@@ -76,6 +151,10 @@ use zip::{write::{FileOptions, ExtendedFileOptions}, CompressionMethod, ZipWrite
         CircleTokenGenRequest,
         CircleUpdateTokenGenRequest,
         CircleAccessSetting,
+        AcceptanceStatus,
+        AuthenticationStatus,
+        FormConfirmationStatus,
+        RegistrationCompleteStatus,
     ))
 )]
 pub struct ApiDoc;
@@ -94,12 +173,534 @@ pub fn post_healthcheck(data: Json<HealthCheckRequest>) -> String {
     format!("Accepted post request! {:?}", data.text)
 }
 
+// 有効期限切れtokenのエラーレスポンス
+#[catch(410)]
+pub fn token_expired() -> &'static str {
+    "token has expired"
+}
+
+// 管理者の認証に失敗したリクエストのエラーレスポンス
+#[catch(401)]
+pub fn unauthorized(req: &Request) -> &'static str {
+    req.local_cache(|| GuardMessage("request is unauthorized")).0
+}
+
+// 管理者の権限が不足しているリクエストのエラーレスポンス
+#[catch(403)]
+pub fn forbidden(req: &Request) -> &'static str {
+    req.local_cache(|| GuardMessage("request is forbidden")).0
+}
+
+// 認証レコード定期削除の実行状況取得API
+#[utoipa::path(context_path = "/api/admin")]
+#[get("/auth-sweep")]
+pub async fn auth_sweep_status(_admin: SuperAdmin, app: &State<App>) -> Result<Json<AuthSweepStatusResponse>, Status> {
+    let response = match app.auth.last_sweep() {
+        Some(report) => AuthSweepStatusResponse {
+            last_run: Some(report.ran_at.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            deleted_auth: report.auth,
+            deleted_locker_auth_info: report.locker_auth_info,
+            deleted_circle_auth_info: report.circle_auth_info,
+        },
+        None => AuthSweepStatusResponse {
+            last_run: None,
+            deleted_auth: 0,
+            deleted_locker_auth_info: 0,
+            deleted_circle_auth_info: 0,
+        },
+    };
+
+    Ok(Json(response))
+}
+
+// メールテンプレート一覧取得API
+#[utoipa::path(context_path = "/api/admin")]
+#[get("/mail-templates")]
+pub async fn mail_template_list(_admin: SuperAdmin, app: &State<App>) -> Result<Json<MailTemplateListResponse>, Status> {
+    let overrides = app.mail_template.get_all().await?;
+
+    let data = app.mail_template.templates.names().into_iter()
+        .map(|name| {
+            let overridden = overrides.iter().find(|template| template.name == name);
+            MailTemplateSummary {
+                overridden: overridden.is_some(),
+                updated_at: overridden.map(|template| template.updated_at.and_local_timezone(Utc).unwrap().to_rfc3339_opts(SecondsFormat::Millis, true)),
+                name,
+            }
+        })
+        .collect();
+
+    Ok(Json(MailTemplateListResponse {
+        locale: app.mail_template.templates.locale.clone(),
+        data,
+    }))
+}
+
+// メールテンプレート取得API
+#[utoipa::path(context_path = "/api/admin")]
+#[get("/mail-templates/<name>")]
+pub async fn mail_template_get(name: String, _admin: SuperAdmin, app: &State<App>) -> Result<Json<MailTemplateResponse>, Status> {
+    // テンプレートファイルの内容、存在しないテンプレート名はNotFound
+    let source = app.mail_template.templates.source(&name).ok_or(Status::NotFound)?;
+
+    let response = match app.mail_template.get_by_name(&name).await? {
+        Some(template) => MailTemplateResponse {
+            name: template.name,
+            locale: template.locale,
+            subject: template.subject,
+            body_text: template.body_text,
+            body_html: template.body_html,
+            overridden: true,
+            updated_at: Some(template.updated_at.and_local_timezone(Utc).unwrap().to_rfc3339_opts(SecondsFormat::Millis, true)),
+        },
+        None => MailTemplateResponse {
+            name,
+            locale: source.locale,
+            subject: source.subject.trim().to_string(),
+            body_text: source.body_text,
+            body_html: source.body_html,
+            overridden: false,
+            updated_at: None,
+        },
+    };
+
+    Ok(Json(response))
+}
+
+/// ### メールテンプレート編集API
+///
+/// 編集したテンプレートはテンプレートファイルより優先して使用される
+#[utoipa::path(context_path = "/api/admin")]
+#[put("/mail-templates/<name>", data = "<request>")]
+pub async fn mail_template_put(name: String, request: Json<MailTemplateRequest>, _admin: SuperAdmin, app: &State<App>) -> (Status, &'static str) {
+    // データのバリデーション
+    if request.subject.trim().is_empty() || request.body_text.trim().is_empty() {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    match app.mail_template.register(&name, &request.subject, &request.body_text, request.body_html.as_deref()).await {
+        Ok(_) => {},
+        Err(status) if status == Status::NotFound => return (status, "mail template not found"),
+        Err(status) if status == Status::BadRequest => return (status, "mail template syntax is not valid"),
+        Err(status) => return (status, "failed to update mail template"),
+    }
+
+    (Status::Ok, "success update mail template")
+}
+
+/// ### メールテンプレート初期化API
+///
+/// 編集したテンプレートを削除し、テンプレートファイルの内容に戻す
+#[utoipa::path(context_path = "/api/admin")]
+#[delete("/mail-templates/<name>")]
+pub async fn mail_template_delete(name: String, _admin: SuperAdmin, app: &State<App>) -> (Status, &'static str) {
+    match app.mail_template.delete(&name).await {
+        Ok(_) => {},
+        Err(status) if status == Status::NotFound => return (status, "mail template is not overridden"),
+        Err(status) => return (status, "failed to delete mail template"),
+    }
+
+    (Status::Ok, "success reset mail template")
+}
+
+/// ### 送信待ちメール一覧取得API
+///
+/// statusを省略した場合は送信待ちと送信失敗のメールを返す
+#[utoipa::path(context_path = "/api/admin")]
+#[get("/mail-outbox?<status>")]
+pub async fn mail_outbox_list(status: Option<String>, _admin: SuperAdmin, app: &State<App>) -> Result<Json<MailOutboxListResponse>, Status> {
+    // データのバリデーション
+    let statuses = match status {
+        None => vec![MailStatus::Pending, MailStatus::Failed],
+        Some(status) => match status.parse::<MailStatus>() {
+            Ok(status) => vec![status],
+            Err(_) => return Err(Status::BadRequest),
+        },
+    };
+
+    let mails = app.mail_outbox.get_by_status(statuses).await?;
+
+    let data = mails.into_iter().map(to_mail_outbox_item).collect();
+
+    Ok(Json(MailOutboxListResponse { data }))
+}
+
+/// ### メール送信履歴検索API
+///
+/// 学籍番号、メールアドレス(前方一致)で送信したメールを検索する、新しい順に最大200件を返す
+#[utoipa::path(context_path = "/api/admin")]
+#[get("/mail-log?<student_id>&<email>")]
+pub async fn mail_log_search(student_id: Option<String>, email: Option<String>, _admin: SuperAdmin, app: &State<App>) -> Result<Json<MailOutboxListResponse>, Status> {
+    // データのバリデーション
+
+    // 学籍番号
+    if let Some(student_id) = &student_id {
+        if !(app.student_identity.is_valid(StudentIdFlow::Locker, student_id) || app.student_identity.is_valid(StudentIdFlow::Circle, student_id)) {
+            return Err(Status::BadRequest);
+        }
+    }
+
+    // メールアドレス
+    if let Some(email) = &email {
+        let re = Regex::new(r"^[a-zA-Z0-9_.+@-]+$").unwrap();
+        if !(re.is_match(email.as_str())) {
+            return Err(Status::BadRequest);
+        }
+    }
+
+    let mails = app.mail_outbox.search(student_id, email).await?;
+
+    let data = mails.into_iter().map(to_mail_outbox_item).collect();
+
+    Ok(Json(MailOutboxListResponse { data }))
+}
+
+// 送信待ちメールをレスポンスの形式に変換する
+fn to_mail_outbox_item(mail: MailOutbox) -> MailOutboxItem {
+    MailOutboxItem {
+        mail_id: mail.mail_id.to_string(),
+        to_address: mail.to_address,
+        subject: mail.subject,
+        template: mail.template,
+        student_id: mail.student_id,
+        auth_id: mail.auth_id.map(|auth_id| auth_id.to_string()),
+        organization_id: mail.organization_id,
+        status: mail.status,
+        attempts: mail.attempts,
+        last_error: mail.last_error,
+        next_attempt_at: mail.next_attempt_at.and_local_timezone(Utc).unwrap().to_rfc3339_opts(SecondsFormat::Millis, true),
+        created_at: mail.created_at.and_local_timezone(Utc).unwrap().to_rfc3339_opts(SecondsFormat::Millis, true),
+        sent_at: mail.sent_at.map(|sent_at| sent_at.and_local_timezone(Utc).unwrap().to_rfc3339_opts(SecondsFormat::Millis, true)),
+    }
+}
+
+/// ### メール再送API
+///
+/// 送信済みでないメールを送信待ちに戻し、再送回数をリセットする
+#[utoipa::path(context_path = "/api/admin")]
+#[post("/mail-outbox/<mail_id>/retry")]
+pub async fn mail_outbox_retry(mail_id: String, _admin: SuperAdmin, app: &State<App>) -> (Status, &'static str) {
+    // データのバリデーション
+    let mail_id = match Uuid::parse_str(&mail_id) {
+        Ok(mail_id) => mail_id,
+        Err(_) => return (Status::BadRequest, "request parameter is not valid"),
+    };
+
+    match app.mail_outbox.retry(&mail_id).await {
+        Ok(_) => {},
+        Err(status) if status == Status::NotFound => return (status, "mail not found or already sent"),
+        Err(status) => return (status, "failed to retry mail"),
+    }
+
+    (Status::Ok, "success retry mail")
+}
+
+/// ### 送信失敗メール一括再送API
+#[utoipa::path(context_path = "/api/admin")]
+#[post("/mail-outbox/retry-failed")]
+pub async fn mail_outbox_retry_failed(_admin: SuperAdmin, app: &State<App>) -> Result<Json<MailOutboxRetryResponse>, Status> {
+    let retried = app.mail_outbox.retry_failed().await?;
+
+    Ok(Json(MailOutboxRetryResponse { retried }))
+}
+
+/// ### 管理者一覧取得API
+#[utoipa::path(context_path = "/api/admin")]
+#[get("/admins")]
+pub async fn admin_list(_admin: SuperAdmin, app: &State<App>) -> Result<Json<AdminListResponse>, Status> {
+    let admins = app.admin.get_all().await?;
+
+    let data = admins.into_iter()
+        .map(|admin| AdminSummary {
+            username: admin.username,
+            role: admin.role,
+            disabled: admin.disabled,
+        })
+        .collect();
+
+    Ok(Json(AdminListResponse { data }))
+}
+
+/// ### 管理者登録API
+///
+/// passwordはパスワードポリシーを満たす必要がある
+#[utoipa::path(context_path = "/api/admin")]
+#[post("/admins", data = "<request>")]
+pub async fn admin_create(request: Json<AdminCreateRequest>, _admin: SuperAdmin, app: &State<App>) -> (Status, String) {
+    // データのバリデーション
+
+    // username
+    let re = Regex::new(r"^[A-Za-z\d_-]{1,64}$").unwrap();
+    if !(re.is_match(request.username.as_str())) {
+        return (Status::BadRequest, String::from("request data is not valid"));
+    }
+
+    // role
+    let role = match request.role.parse::<AdminRole>() {
+        Ok(role) => role,
+        Err(_) => return (Status::BadRequest, String::from("request data is not valid")),
+    };
+
+    // password
+    if let Err(e) = validate_password(&request.username, &request.password) {
+        return (Status::BadRequest, e.to_string());
+    }
+
+    match app.admin.register(&request.username, &request.password, &role).await {
+        Ok(_) => {},
+        Err(status) if status == Status::Conflict => return (status, String::from("admin already exists")),
+        Err(status) => return (status, String::from("failed to insert admin")),
+    }
+
+    (Status::Created, String::from("success create admin"))
+}
+
+/// ### 管理者情報更新API
+///
+/// 権限、無効化を更新する。自身の権限の変更と無効化、有効なsuperadminがいなくなる変更はできない
+#[utoipa::path(context_path = "/api/admin")]
+#[patch("/admins/<username>", data = "<request>")]
+pub async fn admin_update(username: String, request: Json<AdminUpdateRequest>, admin: SuperAdmin, app: &State<App>) -> (Status, &'static str) {
+    // データのバリデーション
+    if request.role.is_none() && request.disabled.is_none() {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    // role
+    let role = match &request.role {
+        None => None,
+        Some(role) => match role.parse::<AdminRole>() {
+            Ok(role) => Some(role),
+            Err(_) => return (Status::BadRequest, "request data is not valid"),
+        },
+    };
+
+    // 自身を操作できなくなることを防ぐ
+    if username == admin.0.username {
+        return (Status::BadRequest, "cannot update own account");
+    }
+
+    match app.admin.update(&username, &role, &request.disabled).await {
+        Ok(_) => {},
+        Err(status) if status == Status::NotFound => return (status, "admin not found"),
+        Err(status) if status == Status::Conflict => return (status, "cannot remove the last superadmin"),
+        Err(status) => return (status, "failed to update admin"),
+    }
+
+    // 無効化した管理者のセッションを失効させる
+    if request.disabled == Some(true) {
+        if let Err(status) = app.admin_session.revoke_all(&username, None).await {
+            return (status, "failed to revoke admin sessions");
+        }
+    }
+
+    (Status::Ok, "success update admin")
+}
+
+/// ### 管理者パスワード再設定API
+///
+/// 管理者のパスワードを再設定し、再設定した管理者のセッションを全て失効させる。passwordはパスワードポリシーを満たす必要がある
+#[utoipa::path(context_path = "/api/admin")]
+#[post("/admins/<username>/password", data = "<request>")]
+pub async fn admin_password_reset(username: String, request: Json<AdminPasswordResetRequest>, _admin: SuperAdmin, app: &State<App>) -> (Status, String) {
+    // データのバリデーション
+    if let Err(e) = validate_password(&username, &request.password) {
+        return (Status::BadRequest, e.to_string());
+    }
+
+    match app.admin.update_password(&username, &request.password).await {
+        Ok(_) => {},
+        Err(status) if status == Status::NotFound => return (status, String::from("admin not found")),
+        Err(status) => return (status, String::from("failed to update password")),
+    }
+
+    if let Err(status) = app.admin_session.revoke_all(&username, None).await {
+        return (status, String::from("failed to revoke admin sessions"));
+    }
+
+    (Status::Ok, String::from("success reset password"))
+}
+
+/// ### 管理者パスワード変更API
+///
+/// ログインしている管理者自身のパスワードを変更し、現在のセッション以外のセッションを全て失効させる
+#[utoipa::path(context_path = "/api/admin")]
+#[put("/password", data = "<request>")]
+pub async fn admin_password_change(request: Json<AdminPasswordChangeRequest>, admin: AdminUser, app: &State<App>) -> (Status, String) {
+    let username = admin.username;
+
+    // データのバリデーション
+    if let Err(e) = validate_password(&username, &request.new_password) {
+        return (Status::BadRequest, e.to_string());
+    }
+    if request.new_password == request.current_password {
+        return (Status::BadRequest, String::from("new password must be different from current password"));
+    }
+
+    // 現在のパスワードの検証
+    let credential = match app.admin.get_by_name(&username).await {
+        Ok(credential) => credential,
+        Err(status) => return (status, String::from("failed to get admin")),
+    };
+    if verify_password_hash(request.current_password.clone(), credential.password).is_err() {
+        return (Status::BadRequest, String::from("invalid password"));
+    }
+
+    if let Err(status) = app.admin.update_password(&username, &request.new_password).await {
+        return (status, String::from("failed to update password"));
+    }
+
+    if let Err(status) = app.admin_session.revoke_all(&username, Some(&admin.session_id)).await {
+        return (status, String::from("failed to revoke admin sessions"));
+    }
+
+    (Status::Ok, String::from("success change password"))
+}
+
+/// ### 管理者セッション一覧取得API
+///
+/// 有効なセッションを最終アクセス時刻の新しい順に返す。superadmin以外は自身のセッションのみ取得できる
+///
+/// superadminがusernameを指定しない場合は全管理者のセッションを返す
+#[utoipa::path(context_path = "/api/admin")]
+#[get("/sessions?<username>")]
+pub async fn admin_session_list(username: Option<String>, admin: AdminUser, app: &State<App>) -> Result<Json<AdminSessionListResponse>, Status> {
+    let username = match admin.role {
+        AdminRole::Superadmin => username,
+        _ => match username {
+            Some(username) if username != admin.username => return Err(Status::Forbidden),
+            _ => Some(admin.username.clone()),
+        },
+    };
+
+    let sessions = app.admin_session.get_active(username).await?;
+
+    let data = sessions.into_iter()
+        .map(|session| to_admin_session_item(session, &admin.session_id))
+        .collect();
+
+    Ok(Json(AdminSessionListResponse { data }))
+}
+
+// セッションをレスポンスの形式に変換する
+fn to_admin_session_item(session: AdminSession, current: &Uuid) -> AdminSessionItem {
+    AdminSessionItem {
+        session_id: session.session_id.to_string(),
+        username: session.username,
+        ip_address: session.ip_address,
+        created_at: session.created_at.and_local_timezone(Utc).unwrap().to_rfc3339_opts(SecondsFormat::Millis, true),
+        last_seen_at: session.last_seen_at.and_local_timezone(Utc).unwrap().to_rfc3339_opts(SecondsFormat::Millis, true),
+        expires_at: session.expires_at.and_local_timezone(Utc).unwrap().to_rfc3339_opts(SecondsFormat::Millis, true),
+        current: session.session_id == *current,
+    }
+}
+
+/// ### 管理者セッション失効API
+///
+/// セッションを失効させる。superadmin以外は自身のセッションのみ失効させることができる
+#[utoipa::path(context_path = "/api/admin")]
+#[delete("/sessions/<session_id>")]
+pub async fn admin_session_revoke(session_id: String, admin: AdminUser, app: &State<App>) -> (Status, &'static str) {
+    // データのバリデーション
+    let session_id = match Uuid::parse_str(&session_id) {
+        Ok(session_id) => session_id,
+        Err(_) => return (Status::BadRequest, "request parameter is not valid"),
+    };
+
+    let session = match app.admin_session.get_by_id(&session_id).await {
+        Ok(session) => session,
+        Err(status) if status == Status::NotFound => return (status, "session not found"),
+        Err(status) => return (status, "failed to get admin session"),
+    };
+    if admin.role != AdminRole::Superadmin && session.username != admin.username {
+        return (Status::Forbidden, "request is forbidden");
+    }
+
+    match app.admin_session.revoke(&session_id).await {
+        Ok(_) => {},
+        Err(status) if status == Status::NotFound => return (status, "session not found or already revoked"),
+        Err(status) => return (status, "failed to revoke session"),
+    }
+
+    (Status::Ok, "success revoke session")
+}
+
+/// ### 管理者セッション一括失効API
+///
+/// 管理者の全てのセッションを失効させる。usernameを指定しない場合は自身のセッションを失効させる
+///
+/// superadmin以外は自身のセッションのみ失効させることができる
+#[utoipa::path(context_path = "/api/admin")]
+#[delete("/sessions?<username>")]
+pub async fn admin_session_revoke_all(username: Option<String>, admin: AdminUser, app: &State<App>) -> Result<Json<AdminSessionRevokeResponse>, Status> {
+    let username = username.unwrap_or_else(|| admin.username.clone());
+    if admin.role != AdminRole::Superadmin && username != admin.username {
+        return Err(Status::Forbidden);
+    }
+
+    let revoked = app.admin_session.revoke_all(&username, None).await?;
+
+    Ok(Json(AdminSessionRevokeResponse { revoked }))
+}
+
+/// ### ログイン履歴取得API
+///
+/// 管理者のログインの試行をusername、IPアドレス、結果で検索する、新しい順に最大200件を返す
+///
+/// resultを指定しない場合は失敗したログインのみ返す
+#[utoipa::path(context_path = "/api/admin")]
+#[get("/login-attempts?<username>&<ip_address>&<result>")]
+pub async fn login_attempt_list(username: Option<String>, ip_address: Option<String>, result: Option<String>, _admin: SuperAdmin, app: &State<App>) -> Result<Json<LoginAttemptListResponse>, Status> {
+    // データのバリデーション
+
+    // username
+    if let Some(username) = &username {
+        let re = Regex::new(r"^[A-Za-z\d_-]+$").unwrap();
+        if !(re.is_match(username.as_str())) {
+            return Err(Status::BadRequest);
+        }
+    }
+
+    // IPアドレス
+    if let Some(ip_address) = &ip_address {
+        if ip_address.parse::<IpAddr>().is_err() {
+            return Err(Status::BadRequest);
+        }
+    }
+
+    // result
+    let results = match result {
+        None => vec![LoginResult::InvalidCredentials, LoginResult::Locked, LoginResult::Disabled],
+        Some(result) => match result.parse::<LoginResult>() {
+            Ok(result) => vec![result],
+            Err(_) => return Err(Status::BadRequest),
+        },
+    };
+
+    let attempts = app.login_attempt.search(username, ip_address, results).await?;
+
+    let data = attempts.into_iter().map(to_login_attempt_item).collect();
+
+    Ok(Json(LoginAttemptListResponse { data }))
+}
+
+// ログインの試行をレスポンスの形式に変換する
+fn to_login_attempt_item(attempt: LoginAttempt) -> LoginAttemptItem {
+    LoginAttemptItem {
+        attempt_id: attempt.attempt_id.to_string(),
+        username: attempt.username,
+        ip_address: attempt.ip_address,
+        result: attempt.result,
+        attempted_at: attempt.attempted_at.and_local_timezone(Utc).unwrap().to_rfc3339_opts(SecondsFormat::Millis, true),
+    }
+}
+
 // zipダウンロードAPI
 #[utoipa::path(context_path = "/api/admin")]
 #[post("/download", data = "<request>")]
 pub async fn download(
     request: Json<DownloadRequest>, 
-    jar: &CookieJar<'_>, 
+    _admin: SuperAdmin, 
     app: &State<App>
 ) -> Result<Json<DownloadResponse>, Status> {
     // バリデーション: パスワードが英数字のみかチェック
@@ -108,134 +709,44 @@ pub async fn download(
         return Err(Status::BadRequest);
     }
 
-    // CookieからJWTの取得
-    let jwt = match jar.get("token").map(|c| c.value()) {
-        None => return Err(Status::Unauthorized),
-        Some(t) => t.to_string(),
-    };
-
-    // JWTの検証
-    match decode_jwt(&jwt) {
-        None => Err(Status::Unauthorized),
-        Some(_) => {
-            // パスワードの検証
-            dotenv().ok();
-            let password_hash = env::var("DOWNLOAD_PASSWORD_HASH")
-                .expect("download password hash must be set");
-            match verify_password_hash(request.password.clone(), password_hash) {
-                Ok(_) => {},
-                Err(_) => return Err(Status::BadRequest),
-            }
+    // パスワードの検証
+    dotenv().ok();
+    let password_hash = env::var("DOWNLOAD_PASSWORD_HASH")
+        .expect("download password hash must be set");
+    match verify_password_hash(request.password.clone(), password_hash) {
+        Ok(_) => {},
+        Err(_) => return Err(Status::BadRequest),
+    }
 
-            // 各テーブルからデータ取得（失敗時は InternalServerError を返す）
-            let students = app.student.get_all().await.map_err(|_| Status::InternalServerError)?;
-            let student_pairs = app.student_pair.get_all().await.map_err(|_| Status::InternalServerError)?;
-            let assignment_records = app.assignment_record.get_all().await.map_err(|_| Status::InternalServerError)?;
-            let lockers = app.locker.get_all().await.map_err(|_| Status::InternalServerError)?;
-            let representatives = app.representatives.get_all().await.map_err(|_| Status::InternalServerError)?;
-            let organizations = app.organization.get_all().await.map_err(|_| Status::InternalServerError)?;
-            let registrations = app.registration.get_all().await.map_err(|_| Status::InternalServerError)?;
-            let times = app.time.get_all().await.map_err(|_| Status::InternalServerError)?;
-
-            // CSVファイルの内容をメモリ上で作成するための Vec
-            let mut files: Vec<(&str, Vec<u8>)> = Vec::new();
-
-            {
-                let mut wtr = Writer::from_writer(vec![]);
-                for student in &students {
-                    wtr.serialize(student).map_err(|_| Status::InternalServerError)?;
-                }
-                wtr.flush().map_err(|_| Status::InternalServerError)?;
-                let data = wtr.into_inner().map_err(|_| Status::InternalServerError)?;
-                files.push(("students.csv", data));
-            }
-            {
-                let mut wtr = Writer::from_writer(vec![]);
-                for pair in &student_pairs {
-                    wtr.serialize(pair).map_err(|_| Status::InternalServerError)?;
-                }
-                wtr.flush().map_err(|_| Status::InternalServerError)?;
-                let data = wtr.into_inner().map_err(|_| Status::InternalServerError)?;
-                files.push(("student_pairs.csv", data));
-            }
-            {
-                let mut wtr = Writer::from_writer(vec![]);
-                for record in &assignment_records {
-                    wtr.serialize(record).map_err(|_| Status::InternalServerError)?;
-                }
-                wtr.flush().map_err(|_| Status::InternalServerError)?;
-                let data = wtr.into_inner().map_err(|_| Status::InternalServerError)?;
-                files.push(("assignment_records.csv", data));
-            }
-            {
-                let mut wtr = Writer::from_writer(vec![]);
-                for locker in &lockers {
-                    wtr.serialize(locker).map_err(|_| Status::InternalServerError)?;
-                }
-                wtr.flush().map_err(|_| Status::InternalServerError)?;
-                let data = wtr.into_inner().map_err(|_| Status::InternalServerError)?;
-                files.push(("lockers.csv", data));
-            }
-            {
-                let mut wtr = Writer::from_writer(vec![]);
-                for rep in &representatives {
-                    wtr.serialize(rep).map_err(|_| Status::InternalServerError)?;
-                }
-                wtr.flush().map_err(|_| Status::InternalServerError)?;
-                let data = wtr.into_inner().map_err(|_| Status::InternalServerError)?;
-                files.push(("representatives.csv", data));
-            }
-            {
-                let mut wtr = Writer::from_writer(vec![]);
-                for org in &organizations {
-                    wtr.serialize(org).map_err(|_| Status::InternalServerError)?;
-                }
-                wtr.flush().map_err(|_| Status::InternalServerError)?;
-                let data = wtr.into_inner().map_err(|_| Status::InternalServerError)?;
-                files.push(("organizations.csv", data));
-            }
-            {
-                let mut wtr = Writer::from_writer(vec![]);
-                for reg in &registrations {
-                    wtr.serialize(reg).map_err(|_| Status::InternalServerError)?;
-                }
-                wtr.flush().map_err(|_| Status::InternalServerError)?;
-                let data = wtr.into_inner().map_err(|_| Status::InternalServerError)?;
-                files.push(("registrations.csv", data));
-            }
-            {
-                let mut wtr = Writer::from_writer(vec![]);
-                for time in &times {
-                    wtr.serialize(time).map_err(|_| Status::InternalServerError)?;
-                }
-                wtr.flush().map_err(|_| Status::InternalServerError)?;
-                let data = wtr.into_inner().map_err(|_| Status::InternalServerError)?;
-                files.push(("times.csv", data));
-            }
+    // 各テーブルからデータ取得（失敗時は InternalServerError を返す）
+    let students = app.student.get_all().await.map_err(|_| Status::InternalServerError)?;
+    let student_pairs = app.student_pair.get_all().await.map_err(|_| Status::InternalServerError)?;
+    let assignment_records = app.assignment_record.get_all().await.map_err(|_| Status::InternalServerError)?;
+    let lockers = app.locker.get_all().await.map_err(|_| Status::InternalServerError)?;
+    let representatives = app.representatives.get_all().await.map_err(|_| Status::InternalServerError)?;
+    let organizations = app.organization.get_all().await.map_err(|_| Status::InternalServerError)?;
+    let registrations = app.registration.get_all().await.map_err(|_| Status::InternalServerError)?;
+    let times = app.time.get_all().await.map_err(|_| Status::InternalServerError)?;
 
-            // ZIPアーカイブをメモリ上で作成
-            let mut zip_buffer = Cursor::new(Vec::new());
-            let options: FileOptions<ExtendedFileOptions> = FileOptions::default().compression_method(CompressionMethod::Deflated);
-            {
-                let mut zip_writer = ZipWriter::new(&mut zip_buffer);
-                for (filename, data) in files {
-                    zip_writer.start_file(filename, options.clone())
-                        .map_err(|_| Status::InternalServerError)?;
-                    zip_writer.write_all(&data)
-                        .map_err(|_| Status::InternalServerError)?;
-                }
-                zip_writer.finish().map_err(|_| Status::InternalServerError)?;
-            }
-            let zip_bytes = zip_buffer.into_inner();
+    // CSVファイルの内容をメモリ上で作成し、ZIPアーカイブにまとめる
+    let files: Vec<(&str, Vec<u8>)> = vec![
+        ("students.csv", to_csv(&students)?),
+        ("student_pairs.csv", to_csv(&student_pairs)?),
+        ("assignment_records.csv", to_csv(&assignment_records)?),
+        ("lockers.csv", to_csv(&lockers)?),
+        ("representatives.csv", to_csv(&representatives)?),
+        ("organizations.csv", to_csv(&organizations)?),
+        ("registrations.csv", to_csv(&registrations)?),
+        ("times.csv", to_csv(&times)?),
+    ];
+    let zip_bytes = to_zip(files)?;
 
-            // 現在時刻を "yymmddhhmmss" 形式で取得
-            let timestamp = Utc::now().format("%y%m%d%H%M%S").to_string();
+    // 現在時刻を "yymmddhhmmss" 形式で取得
+    let timestamp = Utc::now().format("%y%m%d%H%M%S").to_string();
 
-            // ZipResponse 構造体を返す
-            Ok(Json(DownloadResponse {
-                zip_data: zip_bytes,
-                filename: format!("Database_{}.zip", timestamp),
-            }))
-        }
-    }
+    // ZipResponse 構造体を返す
+    Ok(Json(DownloadResponse {
+        zip_data: zip_bytes,
+        filename: format!("Database_{}.zip", timestamp),
+    }))
 }
\ No newline at end of file
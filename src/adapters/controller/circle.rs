@@ -1,5 +1,5 @@
-use crate::adapters::httpmodels::*;
-use crate::domain::{circle::{OrganizationInfo, Organization}, student::RepresentativeInfo};
+use crate::adapters::{httpmodels::*, guard::{CircleAccessWindow, CircleStaff}};
+use crate::domain::{circle::{OrganizationInfo, Organization, AcceptanceStatus, AuthenticationStatus, FormConfirmationStatus, RegistrationCompleteStatus}, student::RepresentativeInfo, mail::MailAudit};
 use crate::infrastructure::router::App;
 use crate::usecase::time::TimeUsecase;
 use crate::usecase::{
@@ -7,22 +7,25 @@ use crate::usecase::{
                     representatives::RepresentativesUsecase,
                     organization::OrganizationUsecase,
                     registration::RegistrationUsecase,
+                    mail_template::MailTemplateUsecase,
+                    mail_outbox::MailOutboxUsecase,
                     };
-use crate::utils::jwt::decode_jwt;
+use crate::utils::{verify_recaptcha::verify_recaptcha, student_identity::StudentIdFlow};
 
 use std::env;
 use chrono::{DateTime, Utc};
 use dotenv::dotenv;
-use rocket::{get, http::{Status, CookieJar}, post, serde::json::Json, State};
+use rocket::{get, http::Status, post, serde::json::Json, State};
 use regex::Regex;
+use serde_json::json;
 
 // 団体登録受付API
 #[utoipa::path(context_path = "/api/circle")]
 #[post("/update/entry", data = "<request>")]
-pub async fn update_entry(request: Json<CircleUpdateRequest>, app: &State<App>) -> (Status, &'static str) {
+pub async fn update_entry(request: Json<CircleUpdateRequest>, _window: CircleAccessWindow, app: &State<App>) -> (Status, &'static str) {
     // 環境変数からURLを取得
     dotenv().ok();
-    let app_url = env::var("GFORM_UPDATE_URL").expect("GFORM_UPDATE_URL must be set");
+    let form_url = env::var("GFORM_UPDATE_URL").expect("GFORM_UPDATE_URL must be set");
 
     // データのバリデーション
 
@@ -33,8 +36,7 @@ pub async fn update_entry(request: Json<CircleUpdateRequest>, app: &State<App>)
     }
 
     // 旧代表者学籍番号
-    let re = Regex::new(r"^[1-46-9][1-9AB]\d{5}$").unwrap();
-    if !(re.is_match(request.student_id.as_str())) {
+    if !(app.student_identity.is_valid(StudentIdFlow::Circle, &request.student_id)) {
         return (Status::BadRequest, "request data is not valid");
     }
 
@@ -56,22 +58,26 @@ pub async fn update_entry(request: Json<CircleUpdateRequest>, app: &State<App>)
     // 団体が存在しているかの確認
 
     // メール内容の作成
-    let signature = env::var("EMAIL_SIGNATURE").expect("EMAIL_SIGNATURE must be set.");
-
     let user_address = request.email.to_string();
-    let content = format!("{} 代表 {}{} 様\n\n以下のURLから団体情報更新用GoogleFormにアクセスして更新内容を入力してください。\n\n{}\n\n{}", request.organization_name, request.family_name, request.given_name, app_url, signature);
-    let subject = "【団体登録システム】団体情報更新 更新用URLのお知らせ";
+    let mail = match app.mail_template.render("circle_update_entry", json!({
+        "organization_name": request.organization_name,
+        "family_name": request.family_name,
+        "given_name": request.given_name,
+        "form_url": form_url,
+    })).await {
+        Ok(mail) => mail,
+        Err(status) => return (status, "failed to render authentication email"),
+    };
+    let audit = MailAudit{
+        template: String::from("circle_update_entry"),
+        student_id: Some(request.student_id.clone()),
+        organization_id: Some(request.organization_id.clone()),
+        ..Default::default()
+    };
 
     // 認証メールを送信
-    if app.option.local_mail_enable {
-        if app.auth.mail_sender_local(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
-        }
-    }
-    else {
-        if app.auth.mail_sender(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
-        }
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return (Status::InternalServerError, "Failed to send authentication email");
     }
 
     // レスポンスを返す
@@ -81,7 +87,7 @@ pub async fn update_entry(request: Json<CircleUpdateRequest>, app: &State<App>)
 // 団体情報更新認証API
 #[utoipa::path(context_path = "/api/circle")]
 #[post("/update/token-gen", data= "<request>")]
-pub async fn update_token_generator(request: Json<CircleUpdateTokenGenRequest>, app: &State<App>) -> (Status, &'static str) {
+pub async fn update_token_generator(request: Json<CircleUpdateTokenGenRequest>, _window: CircleAccessWindow, app: &State<App>) -> (Status, &'static str) {
 
     // リクエストからデータを取得
     let data = &request.data;
@@ -95,11 +101,10 @@ pub async fn update_token_generator(request: Json<CircleUpdateTokenGenRequest>,
     }
 
     // 学籍番号
-    let re = Regex::new(r"^[1-46-9][1-9AB]\d{5}$").unwrap();
-    if !(re.is_match(data.main_user.student_id.as_str())) {
+    if !(app.student_identity.is_valid(StudentIdFlow::Circle, &data.main_user.student_id)) {
         return (Status::BadRequest, "request data is not valid");
     }
-    if !(re.is_match(data.co_user.student_id.as_str())) {
+    if !(app.student_identity.is_valid(StudentIdFlow::Circle, &data.co_user.student_id)) {
         return (Status::BadRequest, "request data is not valid");
     }
 
@@ -154,32 +159,34 @@ pub async fn update_token_generator(request: Json<CircleUpdateTokenGenRequest>,
     };
 
     // 団体情報をDBに登録し、auth_tokenを取得
-    let token = match app.auth.circle_register(&auth_info, &String::from("main_auth"), false).await {
-        Ok(auth) => auth.main_auth_token,
+    let auth = match app.auth.circle_register(&auth_info, &String::from("main_auth"), false).await {
+        Ok(auth) => auth,
         Err(_) => {return (Status::InternalServerError, "failed to issue auth token")}
     };
 
     // メール内容の作成
     let main_user = &data.main_user;
 
-    dotenv().ok();
-    let app_url = env::var("APP_URL").expect("APP_URL must be set.");
-    let signature = env::var("EMAIL_SIGNATURE").expect("EMAIL_SIGNATURE must be set.");
-
     let user_address = main_user.email.to_string();
-    let content = format!("{}{} 様\n\n申請を受け付けました。\n以下のURLにアクセスして代表者の認証を完了してください。\n{}/circle/update/auth?method=1&token={}&id={}\n\n{}", main_user.family_name, main_user.given_name, app_url, token, data.organization_id, signature);
-    let subject = "【団体登録システム】 認証手続きを行ってください。";
+    let mail = match app.mail_template.render("circle_main_auth", json!({
+        "family_name": main_user.family_name,
+        "given_name": main_user.given_name,
+        "token": auth.main_auth_token,
+        "organization_id": data.organization_id,
+    })).await {
+        Ok(mail) => mail,
+        Err(status) => return (status, "failed to render authentication email"),
+    };
+    let audit = MailAudit{
+        template: String::from("circle_main_auth"),
+        student_id: Some(main_user.student_id.clone()),
+        auth_id: Some(auth.auth_id),
+        organization_id: Some(data.organization_id.clone()),
+    };
 
     // メールの送信
-    if app.option.local_mail_enable {
-        if app.auth.mail_sender_local(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
-        }
-    }
-    else {
-        if app.auth.mail_sender(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
-        }
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return (Status::InternalServerError, "Failed to send authentication email");
     }
 
     (Status::Created, "Authentication email sent successfully")
@@ -188,7 +195,7 @@ pub async fn update_token_generator(request: Json<CircleUpdateTokenGenRequest>,
 // 団体登録認証API
 #[utoipa::path(context_path = "/api/circle")]
 #[post("/register/token-gen", data="<request>")]
-pub async fn register_token_generator(request: Json<CircleTokenGenRequest>, app: &State<App>) -> (Status, &'static str) {
+pub async fn register_token_generator(request: Json<CircleTokenGenRequest>, _window: CircleAccessWindow, app: &State<App>) -> (Status, &'static str) {
 
     // リクエストからデータを取得
     let data = &request.data;
@@ -211,11 +218,10 @@ pub async fn register_token_generator(request: Json<CircleTokenGenRequest>, app:
     }
 
     // 学籍番号
-    let re = Regex::new(r"^[1-46-9][1-9AB]\d{5}$").unwrap();
-    if !(re.is_match(data.main_user.student_id.as_str())) {
+    if !(app.student_identity.is_valid(StudentIdFlow::Circle, &data.main_user.student_id)) {
         return (Status::BadRequest, "request data is not valid");
     }
-    if !(re.is_match(data.co_user.student_id.as_str())) {
+    if !(app.student_identity.is_valid(StudentIdFlow::Circle, &data.co_user.student_id)) {
         return (Status::BadRequest, "request data is not valid");
     }
 
@@ -247,32 +253,34 @@ pub async fn register_token_generator(request: Json<CircleTokenGenRequest>, app:
     }
 
     // 団体情報をDBに登録し、auth_tokenを取得
-    let token = match app.auth.circle_register(data, &String::from("main_auth"), false).await {
-        Ok(auth) => auth.main_auth_token,
+    let auth = match app.auth.circle_register(data, &String::from("main_auth"), false).await {
+        Ok(auth) => auth,
         Err(_) => {return (Status::InternalServerError, "failed to issue auth token")}
     };
 
     // メール内容の作成
     let main_user = &data.main_user;
 
-    dotenv().ok();
-    let app_url = env::var("APP_URL").expect("APP_URL must be set.");
-    let signature = env::var("EMAIL_SIGNATURE").expect("EMAIL_SIGNATURE must be set.");
-
     let user_address = main_user.email.to_string();
-    let content = format!("{}{} 様\n\n申請を受け付けました。\n以下のURLにアクセスして代表者の認証を完了してください。\n{}/circle/register/auth?method=1&token={}\n\n{}", main_user.family_name, main_user.given_name, app_url, token, signature);
-    let subject = "【団体登録システム】 認証手続きを行ってください";
+    let mail = match app.mail_template.render("circle_main_auth", json!({
+        "family_name": main_user.family_name,
+        "given_name": main_user.given_name,
+        "token": auth.main_auth_token,
+        "organization_id": null,
+    })).await {
+        Ok(mail) => mail,
+        Err(status) => return (status, "failed to render authentication email"),
+    };
+    let audit = MailAudit{
+        template: String::from("circle_main_auth"),
+        student_id: Some(main_user.student_id.clone()),
+        auth_id: Some(auth.auth_id),
+        ..Default::default()
+    };
 
     // メールの送信
-    if app.option.local_mail_enable {
-        if app.auth.mail_sender_local(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
-        }
-    }
-    else {
-        if app.auth.mail_sender(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
-        }
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return (Status::InternalServerError, "Failed to send authentication email");
     }
 
     (Status::Created, "Authentication email sent successfully")
@@ -281,7 +289,7 @@ pub async fn register_token_generator(request: Json<CircleTokenGenRequest>, app:
 // 団体代表者認証API
 #[utoipa::path(context_path = "/api/circle")]
 #[post("/main-auth?<token>&<id>")]
-pub async fn circle_main_auth(token: String, id: Option<String>, app:&State<App>) -> (Status, &'static str) {
+pub async fn circle_main_auth(token: String, id: Option<String>, _window: CircleAccessWindow, app:&State<App>) -> (Status, &'static str) {
 
     // データのバリデーション
 
@@ -294,23 +302,21 @@ pub async fn circle_main_auth(token: String, id: Option<String>, app:&State<App>
     }
 
     // token
-    let re = Regex::new(r"^[a-zA-Z0-9]{16}$").unwrap();
+    let re = Regex::new(r"^[a-zA-Z0-9]{32}$").unwrap();
     if !(re.is_match(token.as_str())) {
         return (Status::BadRequest, "request parameter is not valid");
     }
 
     // tokenが一致するレコードを取得
-    let auth = match app.auth.token_check(token, true).await{
+    let auth = match app.auth.token_check(token, "main_auth", true).await{
         Ok(auth) => auth,
+        // 有効期限切れの場合は410
+        Err(status) if status == Status::Gone => return (status, "token has expired"),
+        Err(status) if status == Status::BadRequest => return (status, "authentication phase does not match"),
         // 存在しなかったら終了
         Err(status) => return (status, "invalid token"),
     };
 
-    // authのphaseを確認
-    if auth.phase != *"main_auth" {
-        return (Status::BadRequest, "authentication phase does not match");
-    }
-
     // auth_infoからレコードを取得
     let auth_info = match app.auth.get_circle_auth_info(&auth.auth_id).await {
         Ok(info) => info,
@@ -338,27 +344,32 @@ pub async fn circle_main_auth(token: String, id: Option<String>, app:&State<App>
         phone_number: auth_info.co_phone,
     };
 
-    dotenv().ok();
-    let app_url = env::var("APP_URL").expect("APP_URL must be set.");
-    let signature = env::var("EMAIL_SIGNATURE").expect("EMAIL_SIGNATURE must be set.");
+    // co_user用のtokenを発行
+    let co_token = match app.auth.reissue_token(&auth.auth_id, false).await {
+        Ok(token) => token,
+        Err(status) => return (status, "failed to issue auth token"),
+    };
 
     let user_address = co_user.email.to_string();
-    let content = match id {
-        Some(id) => format!("{}{} 様\n\n代表者の認証が完了しました。\n以下のURLにアクセスして認証を完了してください。\n{}/circle/update/auth?method=0&token={}&id={}\n\n{}", co_user.family_name, co_user.given_name, app_url, auth.co_auth_token, id, signature),
-        None => format!("{}{} 様\n\n代表者の認証が完了しました。\n以下のURLにアクセスして認証を完了してください。\n{}/circle/register/auth?method=0&token={}\n\n{}", co_user.family_name, co_user.given_name, app_url, auth.co_auth_token, signature),
+    let mail = match app.mail_template.render("circle_co_auth", json!({
+        "family_name": co_user.family_name,
+        "given_name": co_user.given_name,
+        "token": co_token,
+        "organization_id": id,
+    })).await {
+        Ok(mail) => mail,
+        Err(status) => return (status, "failed to render authentication email"),
+    };
+    let audit = MailAudit{
+        template: String::from("circle_co_auth"),
+        student_id: Some(co_user.student_id.clone()),
+        auth_id: Some(auth.auth_id),
+        organization_id: id,
     };
-    let subject = "【団体登録システム】 認証手続きを行ってください";
 
     // メールの送信
-    if app.option.local_mail_enable {
-        if app.auth.mail_sender_local(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
-        }
-    }
-    else {
-        if app.auth.mail_sender(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
-        }
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return (Status::InternalServerError, "Failed to send authentication email");
     }
 
     // phaseの更新
@@ -372,7 +383,7 @@ pub async fn circle_main_auth(token: String, id: Option<String>, app:&State<App>
 // 団体副代表者認証API
 #[utoipa::path(context_path = "/api/circle")]
 #[post("/co-auth?<token>&<id>")]
-pub async fn circle_co_auth(token: String, id: Option<String>, app:&State<App>) -> (Status, &'static str) {
+pub async fn circle_co_auth(token: String, id: Option<String>, _window: CircleAccessWindow, app:&State<App>) -> (Status, &'static str) {
 
     // データのバリデーション
 
@@ -385,24 +396,22 @@ pub async fn circle_co_auth(token: String, id: Option<String>, app:&State<App>)
     };
 
     // token
-    let re = Regex::new(r"^[a-zA-Z0-9]{16}$").unwrap();
+    let re = Regex::new(r"^[a-zA-Z0-9]{32}$").unwrap();
     if !(re.is_match(token.as_str())) {
         return (Status::BadRequest, "request parameter is not valid");
     }
 
 
     // tokenが一致するレコードを取得
-    let auth = match app.auth.token_check(token, false).await{
+    let auth = match app.auth.token_check(token, "co_auth", false).await{
         Ok(auth) => auth,
+        // 有効期限切れの場合は410
+        Err(status) if status == Status::Gone => return (status, "token has expired"),
+        Err(status) if status == Status::BadRequest => return (status, "authentication phase does not match"),
         // 存在しなかったら終了
         Err(status) => return (status, "invalid token"),
     };
 
-    // authのphaseを確認
-    if auth.phase != *"co_auth" {
-        return (Status::BadRequest, "authentication phase does not match");
-    }
-
     // auth_infoからレコードを取得
     let auth_info = match app.auth.get_circle_auth_info(&auth.auth_id).await {
         Ok(info) => info,
@@ -434,10 +443,15 @@ pub async fn circle_co_auth(token: String, id: Option<String>, app:&State<App>)
         organization_email: auth_info.organization_email,
     };
 
-    // 副代表者を登録
-    if app.representatives.register(&co_user).await.is_err() {
-        return (Status::InternalServerError, "failed to insert Representative")
-    }
+    // registrationの情報を格納
+    let organization_info = &OrganizationInfo{
+        organization: organization.clone(),
+        main_user: main_user.clone(),
+        co_user: co_user.clone(),
+        b_doc: auth_info.b_doc,
+        c_doc: auth_info.c_doc,
+        d_doc: auth_info.d_doc,
+    };
 
     match id.clone() {
         // 団体情報更新
@@ -451,85 +465,153 @@ pub async fn circle_co_auth(token: String, id: Option<String>, app:&State<App>)
                 None => {return (Status::InternalServerError, "can't get valid organization_id")}
             };
 
-            // 団体メールアドレスの更新
-            if !organization.organization_email.is_empty() && app.organization.update_email(&organization_id, &organization.organization_email).await.is_err() {
+            // 副代表者の登録、団体メールアドレス、代表者、副代表者の更新
+            if app.registration.update_with_organization(&organization_id, organization_info).await.is_err() {
                 return (Status::InternalServerError, "failed to update organization")
             }
-
-            // 代表者、副代表者の更新
-            if (!main_user.student_id.is_empty() && !co_user.student_id.is_empty()) && app.registration.update_student(&organization_id, &main_user.student_id, &co_user.student_id).await.is_err() {
-                return (Status::InternalServerError, "failed to update registration")
-            }
         },
         // 団体新規登録
         None => {
-
-            // registrationの情報を格納
-            let organization_info = &OrganizationInfo{
-                organization: organization.clone(),
-                main_user: main_user.clone(),
-                co_user: co_user.clone(),
-                b_doc: auth_info.b_doc,
-                c_doc: auth_info.c_doc,
-                d_doc: auth_info.d_doc,
-            };
-
-            let organization_id = match app.organization.register(&organization).await {
-                Ok(org) => {
-                    org.organization_id
-                },
-                Err(_) => {
-                    return (Status::InternalServerError, "failed to insert Organization")
-                }
-            };
-
-            // register DBに登録
-            if app.registration.register(organization_info, &organization_id).await.is_err() {
+            // 副代表者、organization、registrationをまとめて登録
+            if app.registration.register_with_organization(organization_info).await.is_err() {
                 return (Status::InternalServerError, "failed to insert Registration")
             }
         }
     }
 
-    dotenv().ok();
-    let signature = env::var("EMAIL_SIGNATURE").expect("EMAIL_SIGNATURE must be set.");
-
     let user_address = main_user.email.to_string();
-    let content = match id {
-        Some(_) => format!("{}{} 様\n\nメール認証が完了し、団体情報が更新されました。\n\
-                            【更新情報】\n団体メールアドレス：{}\n\
-                            代表者\n　氏名：{} {}\n　学籍番号：{}\n　メールアドレス：{}\n　電話番号：{}\n\
-                            副代表者\n　氏名：{} {}\n　学籍番号：{}\n　メールアドレス：{}\n　電話番号：{}\n\n{}
-                            ", main_user.family_name.clone(), main_user.given_name.clone(),
-                            organization.organization_email,
-                            main_user.family_name, main_user.given_name, main_user.student_id, main_user.email, main_user.phone_number,
-                            co_user.family_name, co_user.given_name, co_user.student_id, co_user.email, co_user.phone_number, signature),
-        None => format!("{}{} 様\n\nメール認証が完了し、団体情報が登録されました。\n\
-                            【登録情報】\n団体名：{}\n\
-                            団体名ふりがな：{}\n\
-                            団体メールアドレス：{}\n\
-                            代表者\n　氏名：{} {}\n　学籍番号：{}\n　メールアドレス：{}\n　電話番号：{}\n\
-                            副代表者\n　氏名：{} {}\n　学籍番号：{}\n　メールアドレス：{}\n　電話番号：{}\n\n{}
-                            ", main_user.family_name.clone(), main_user.given_name.clone(),
-                            organization.organization_name, organization.organization_ruby, organization.organization_email,
-                            main_user.family_name, main_user.given_name, main_user.student_id, main_user.email, main_user.phone_number,
-                            co_user.family_name, co_user.given_name, co_user.student_id, co_user.email, co_user.phone_number, signature),
-    };
-    let subject = "【団体登録システム】 メール認証完了のお知らせ";
+    let template = match id {
+        Some(_) => "circle_update_complete",
+        None => "circle_register_complete",
+    };
+    let mail = match app.mail_template.render(template, json!({
+        "family_name": main_user.family_name,
+        "given_name": main_user.given_name,
+        "organization_name": organization.organization_name,
+        "organization_ruby": organization.organization_ruby,
+        "organization_email": organization.organization_email,
+        "main": {
+            "student_id": main_user.student_id,
+            "family_name": main_user.family_name,
+            "given_name": main_user.given_name,
+            "email": main_user.email,
+            "phone_number": main_user.phone_number,
+        },
+        "co": {
+            "student_id": co_user.student_id,
+            "family_name": co_user.family_name,
+            "given_name": co_user.given_name,
+            "email": co_user.email,
+            "phone_number": co_user.phone_number,
+        },
+    })).await {
+        Ok(mail) => mail,
+        Err(status) => return (status, "failed to render notification email"),
+    };
+    let audit = MailAudit{
+        template: String::from(template),
+        student_id: Some(main_user.student_id.clone()),
+        auth_id: Some(auth.auth_id),
+        organization_id: id,
+    };
 
     // 登録完了メールの送信
-    if app.option.local_mail_enable {
-        if app.auth.mail_sender_local(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
-        }
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return (Status::InternalServerError, "Failed to send authentication email");
     }
-    else {
-        if app.auth.mail_sender(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
+
+    (Status::Created, "Organization Infomation updated successfully")
+
+}
+
+// 認証メール再送信API
+#[utoipa::path(context_path = "/api/circle")]
+#[post("/resend", data = "<request>")]
+pub async fn circle_resend(request: Json<CircleResendRequest>, _window: CircleAccessWindow, app: &State<App>) -> (Status, &'static str) {
+
+    // データのバリデーション
+
+    // 学籍番号
+    if !(app.student_identity.is_valid(StudentIdFlow::Circle, &request.student_id)) {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    // 団体ID
+    if let Some(id) = request.organization_id.clone() {
+        let re = Regex::new(r"^C\d{5}$").unwrap();
+        if !(re.is_match(id.as_str())) {
+            return (Status::BadRequest, "request data is not valid");
         }
     }
 
-    (Status::Created, "Organization Infomation updated successfully")
+    dotenv().ok();
+
+    // recaptchaの検証
+    if !verify_recaptcha(&request.recaptcha_token).await.unwrap_or(false) {
+        return (Status::Unauthorized, "recaptcha verification failed");
+    }
+
+    // 認証中のレコードを取得
+    let (auth, auth_info) = match app.auth.circle_resend(&request.student_id).await {
+        Ok(pending) => pending,
+        Err(status) if status == Status::NotFound => return (status, "no pending authentication"),
+        Err(status) if status == Status::TooManyRequests => return (status, "resend is rate limited"),
+        Err(status) => return (status, "failed to get pending authentication"),
+    };
+
+    // 情報更新の場合は団体ふりがなが空で登録されているため、リンクに団体IDを付与する
+    let organization_id = if auth_info.organization_ruby.is_empty() {
+        match request.organization_id.clone() {
+            Some(id) => Some(id),
+            None => return (Status::BadRequest, "organization id is required"),
+        }
+    } else {
+        None
+    };
+
+    // 現在のphaseのメール内容を作成
+    let (user_address, student_id, template, context) = match auth.phase.as_str() {
+        "main_auth" => (
+            auth_info.main_email.clone(),
+            auth_info.main_student_id.clone(),
+            "circle_main_auth",
+            json!({
+                "family_name": auth_info.main_family_name,
+                "given_name": auth_info.main_given_name,
+                "token": auth.main_auth_token,
+                "organization_id": organization_id,
+            }),
+        ),
+        "co_auth" => (
+            auth_info.co_email.clone(),
+            auth_info.co_student_id.clone(),
+            "circle_co_auth",
+            json!({
+                "family_name": auth_info.co_family_name,
+                "given_name": auth_info.co_given_name,
+                "token": auth.co_auth_token,
+                "organization_id": organization_id,
+            }),
+        ),
+        _ => return (Status::BadRequest, "authentication phase does not match"),
+    };
+    let mail = match app.mail_template.render(template, context).await {
+        Ok(mail) => mail,
+        Err(status) => return (status, "failed to render authentication email"),
+    };
+    let audit = MailAudit{
+        template: String::from(template),
+        student_id: Some(student_id),
+        auth_id: Some(auth.auth_id),
+        organization_id,
+    };
 
+    // メールの送信
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return (Status::InternalServerError, "Failed to send authentication email");
+    }
+
+    (Status::Created, "Authentication email resent successfully")
 }
 
 // 団体情報取得API
@@ -563,28 +645,17 @@ pub async fn circle_status(app: &State<App>) -> Result<Json<OrganizationStatusRe
 // 団体アクセス制限API POST
 #[utoipa::path(context_path = "/api/admin/circle")]
 #[post("/access/setting", data="<request>")]
-pub async fn access_setting_post(request: Json<CircleAccessSetting>, jar: &CookieJar<'_>, app: &State<App>) -> (Status, &'static str) {
-    // Cookieからjwtの取得
-    let jwt = match jar.get("token").map(|c| c.value()) {
-        None => return (Status::Unauthorized, "request is unautorized"),
-        Some(t) => String::from(t),
-    };
-
-    match decode_jwt(&jwt) {
-        None => (Status::Unauthorized, "request token is not valid."),
-        Some(_) => {
-            // 時間情報を整形
-            let start_time = DateTime::parse_from_rfc3339(&request.start).unwrap().naive_utc();
-            let end_time = DateTime::parse_from_rfc3339(&request.end).unwrap().naive_utc();
-
-            // アクセス制限情報をDBに保存
-            if app.time.register(&String::from("access_restrictions"), &start_time, &end_time).await.is_err() {
-                return (Status::InternalServerError, "failed to insert time")
-            }
+pub async fn access_setting_post(request: Json<CircleAccessSetting>, _admin: CircleStaff, app: &State<App>) -> (Status, &'static str) {
+    // 時間情報を整形
+    let start_time = DateTime::parse_from_rfc3339(&request.start).unwrap().naive_utc();
+    let end_time = DateTime::parse_from_rfc3339(&request.end).unwrap().naive_utc();
 
-            (Status::Created, "Access Restrictions registered successfully")
-        }
+    // アクセス制限情報をDBに保存
+    if app.time.register(&String::from("access_restrictions"), &start_time, &end_time, &None).await.is_err() {
+        return (Status::InternalServerError, "failed to insert time")
     }
+
+    (Status::Created, "Access Restrictions registered successfully")
 }
 
 // 団体アクセス制限API GET
@@ -614,106 +685,87 @@ pub async fn access_setting_get(app: &State<App>) -> Result<Json<CircleAccessSet
 // 管理者用団体情報取得API
 #[utoipa::path(context_path = "/api/admin/circle")]
 #[get("/list")]
-pub async fn circle_list(jar: &CookieJar<'_>, app: &State<App>) -> Result<Json<OrganizationListResponse>, Status> {
-    // Cookieからjwtの取得
-    let jwt = match jar.get("token").map(|c| c.value()) {
-        None => return Err(Status::Unauthorized),
-        Some(t) => String::from(t),
-    };
-
-    match decode_jwt(&jwt) {
-        None => Err(Status::Unauthorized),
-        Some(_) => {
-            let result = app.registration.get_all().await.unwrap();
-
-            let mut response: Vec<OrganizationList> = Vec::new();
-            for element in result {
-                let organization_info = app.organization.get_by_id(&element.organization_id).await.unwrap();
-                let main_info = app.representatives.get_by_id(&element.main_student_id).await.unwrap();
-                let co_info = app.representatives.get_by_id(&element.co_student_id).await.unwrap();
-
-                let data = OrganizationList{
-                    organization_id: format!("C{0: >05}", element.organization_id),
-                    organization_name: organization_info.organization_name,
-                    organization_email: organization_info.organization_email,
-                    main_id: main_info.student_id,
-                    main_family_name: main_info.family_name,
-                    main_given_name: main_info.given_name,
-                    main_email: main_info.email,
-                    main_phone: main_info.phone,
-                    co_id: co_info.student_id,
-                    co_family_name: co_info.family_name,
-                    co_given_name: co_info.given_name,
-                    co_email: co_info.email,
-                    co_phone: co_info.phone,
-                    b_url: element.b_doc,
-                    c_url: element.c_doc,
-                    d_url: element.d_doc,
-                    status_acceptance: element.status_acceptance,
-                    status_authentication: element.status_authentication,
-                    status_form_confirmation: element.status_form_confirmation,
-                    status_registration_complete: element.status_registration_complete,
-                };
-                response.push(data);
-            }
+pub async fn circle_list(_admin: CircleStaff, app: &State<App>) -> Result<Json<OrganizationListResponse>, Status> {
+    let result = app.registration.get_all().await.unwrap();
 
-            response.sort_by(|lt, rt| lt.organization_id.partial_cmp(&rt.organization_id).unwrap());
+    let mut response: Vec<OrganizationList> = Vec::new();
+    for element in result {
+        let organization_info = app.organization.get_by_id(&element.organization_id).await.unwrap();
+        let main_info = app.representatives.get_by_id(&element.main_student_id).await.unwrap();
+        let co_info = app.representatives.get_by_id(&element.co_student_id).await.unwrap();
 
-            Ok(Json(OrganizationListResponse {
-                data: response,
-            }))
-        }
+        let data = OrganizationList{
+            organization_id: format!("C{0: >05}", element.organization_id),
+            organization_name: organization_info.organization_name,
+            organization_email: organization_info.organization_email,
+            main_id: main_info.student_id,
+            main_family_name: main_info.family_name,
+            main_given_name: main_info.given_name,
+            main_email: main_info.email,
+            main_phone: main_info.phone,
+            co_id: co_info.student_id,
+            co_family_name: co_info.family_name,
+            co_given_name: co_info.given_name,
+            co_email: co_info.email,
+            co_phone: co_info.phone,
+            b_url: element.b_doc,
+            c_url: element.c_doc,
+            d_url: element.d_doc,
+            status_acceptance: element.status_acceptance,
+            status_authentication: element.status_authentication,
+            status_form_confirmation: element.status_form_confirmation,
+            status_registration_complete: element.status_registration_complete,
+        };
+        response.push(data);
     }
+
+    response.sort_by(|lt, rt| lt.organization_id.partial_cmp(&rt.organization_id).unwrap());
+
+    Ok(Json(OrganizationListResponse {
+        data: response,
+    }))
 }
 
 // 団体ステータス更新API
 #[utoipa::path(context_path = "/api/admin/circle")]
 #[post("/status/update", data="<request>")]
-pub async fn circle_status_update(request: Json<OrganizationStatusUpdateRequest>, jar: &CookieJar<'_>, app: &State<App>) -> (Status, &'static str) {
-    // Cookieからjwtの取得
-    let jwt = match jar.get("token").map(|c| c.value()) {
-        None => return (Status::Unauthorized, "request is unauthorized"),
-        Some(t) => String::from(t),
-    };
-
-    match decode_jwt(&jwt) {
-        None => (Status::Unauthorized, "request token is not valid"),
-        Some(_) => {
-
-            // データのバリデーション
-
-            // organization_idの整形
-            let re = Regex::new(r"[1-9]+").unwrap();
-            let organization_id = match re.find(request.organization_id.as_str()) {
-                Some(m) => m.as_str().parse::<i32>().unwrap(),
-                None => {return (Status::InternalServerError, "can't get valid organization_id")}
-            };
+pub async fn circle_status_update(request: Json<OrganizationStatusUpdateRequest>, _admin: CircleStaff, app: &State<App>) -> (Status, &'static str) {
+    // データのバリデーション
 
-            // 受理ステータス
-            if request.status_acceptance.as_str() != "pending" && request.status_acceptance.as_str() != "accepted" {
-                return (Status::BadRequest, "request data is not valid");
-            }
+    // organization_idの整形
+    let re = Regex::new(r"[1-9]+").unwrap();
+    let organization_id = match re.find(request.organization_id.as_str()) {
+        Some(m) => m.as_str().parse::<i32>().unwrap(),
+        None => {return (Status::InternalServerError, "can't get valid organization_id")}
+    };
 
-            // 認証ステータス
-            if request.status_authentication.as_str() != "not_authenticated" && request.status_authentication.as_str() != "authenticated" {
-                return (Status::BadRequest, "request data is not valid");
-            }
+    // 受理ステータス
+    let status_acceptance = match request.status_acceptance.parse::<AcceptanceStatus>() {
+        Ok(status) => status,
+        Err(_) => return (Status::BadRequest, "request data is not valid"),
+    };
 
-            // 書類受理ステータス
-            if request.status_form_confirmation.as_str() != "not_confirmed" && request.status_form_confirmation.as_str() != "confirmed" {
-                return (Status::BadRequest, "request data is not valid");
-            }
+    // 認証ステータス
+    let status_authentication = match request.status_authentication.parse::<AuthenticationStatus>() {
+        Ok(status) => status,
+        Err(_) => return (Status::BadRequest, "request data is not valid"),
+    };
 
-            // 登録完了ステータス
-            if request.status_registration_complete.as_str() != "incomplete" && request.status_registration_complete.as_str() != "completed" {
-                return (Status::BadRequest, "request data is not valid");
-            }
+    // 書類受理ステータス
+    let status_form_confirmation = match request.status_form_confirmation.parse::<FormConfirmationStatus>() {
+        Ok(status) => status,
+        Err(_) => return (Status::BadRequest, "request data is not valid"),
+    };
 
-            if app.registration.update_status(&organization_id, &request.status_acceptance, &request.status_authentication, &request.status_form_confirmation, &request.status_registration_complete).await.is_err() {
-                return (Status::InternalServerError, "failed to update status")
-            }
+    // 登録完了ステータス
+    let status_registration_complete = match request.status_registration_complete.parse::<RegistrationCompleteStatus>() {
+        Ok(status) => status,
+        Err(_) => return (Status::BadRequest, "request data is not valid"),
+    };
 
-            (Status::Ok, "organization status updated successfully")
-        }
+    if app.registration.update_status(&organization_id, &status_acceptance, &status_authentication, &status_form_confirmation, &status_registration_complete).await.is_err() {
+        return (Status::InternalServerError, "failed to update status")
     }
+
+    (Status::Ok, "organization status updated successfully")
 }
\ No newline at end of file
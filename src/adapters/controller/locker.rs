@@ -1,5 +1,5 @@
-use crate::adapters::httpmodels::*;
-use crate::domain::{student::UserInfo, student_pair::PairInfo};
+use crate::adapters::{httpmodels::*, guard::{LockerStaff, SuperAdmin, admin_token_cookie}};
+use crate::domain::{admin::LoginResult, student::UserInfo, student_pair::PairInfo, locker, mail::MailAudit};
 use crate::infrastructure::{router::App, models::{AssignmentRecord, StudentPair}};
 use crate::usecase::{
                     student::StudentUsecase,
@@ -7,32 +7,44 @@ use crate::usecase::{
                     assignment_record::AssignmentRecordUsecase,
                     auth::AuthUsecase,
                     locker::LockerUsecase,
-                    admin::AdminUsecase};
-use crate::utils::{jwt::{encode_jwt, decode_jwt}, verify_password::verify_password_hash, verify_recaptcha::verify_recaptcha};
-
-use std::{env, collections::HashSet};
+                    time::TimeUsecase,
+                    admin::AdminUsecase,
+                    admin_session::AdminSessionUsecase,
+                    login_attempt::LoginAttemptUsecase,
+                    mail_template::MailTemplateUsecase,
+                    mail_outbox::MailOutboxUsecase};
+use crate::utils::{jwt::{encode_jwt, decode_jwt}, verify_password::{verify_password_hash, dummy_password_hash}, password_policy::PASSWORD_MAX_LENGTH, verify_recaptcha::verify_recaptcha, student_identity::StudentIdFlow};
+
+use std::{env, collections::HashSet, net::IpAddr};
 use uuid::Uuid;
 use dotenv::dotenv;
-use rocket::{get, http::{Status, RawStr, Cookie, CookieJar, SameSite}, post, serde::json::Json, State};
-use rocket::time::Duration as RocketDuration;
-use chrono::Duration as ChronoDuration;
+use rocket::{get, http::{Status, RawStr, CookieJar}, post, patch, delete, serde::json::Json, data::{Data, ToByteUnit}, State};
+use chrono::{DateTime, Datelike, Local, SecondsFormat, Utc, Duration as ChronoDuration};
 use regex::Regex;
+use csv::{ReaderBuilder, Trim};
+use serde_json::json;
 
 // token生成、メール送信API
 #[utoipa::path(context_path = "/api/locker")]
 #[post("/token-gen", data = "<request>")]
 pub async fn token_generator(request: Json<LockerTokenGenRequest>, app: &State<App>) -> Status {
 
+    // 受付期間の確認、期間が設定されていない場合は常に受け付ける
+    match app.time.is_open("locker_registration").await {
+        Ok(Some(false)) => return Status::Forbidden,
+        Ok(_) => {},
+        Err(status) => return status,
+    }
+
     let data = &request.data;
 
     // データのバリデーション
 
     // 学籍番号についてのバリデーション
-    let re = Regex::new(r"^(15\d{5}|[48][1-6]\d{5})$").unwrap();
-    if !(re.is_match(data.main_user.student_id.clone().as_str())) {
+    if !(app.student_identity.is_valid(StudentIdFlow::Locker, &data.main_user.student_id)) {
         return Status::BadRequest;
     }
-    if !(re.is_match(data.co_user.student_id.clone().as_str())) {
+    if !(app.student_identity.is_valid(StudentIdFlow::Locker, &data.co_user.student_id)) {
         return Status::BadRequest;
     }
     if !app.option.same_student_enable && data.main_user.student_id.clone() == data.co_user.student_id.clone() {
@@ -63,30 +75,32 @@ pub async fn token_generator(request: Json<LockerTokenGenRequest>, app: &State<A
 
 
     // tokenの生成
-    let token = match app.auth.locker_register(&data.main_user.clone(), &data.co_user.clone(), &String::from("main_auth"), false).await{
-        Ok(auth) => auth.main_auth_token,
+    let auth = match app.auth.locker_register(&data.main_user.clone(), &data.co_user.clone(), &String::from("main_auth"), false).await{
+        Ok(auth) => auth,
         Err(_) => return Status::InternalServerError,
     };
 
     // メール内容の作成
     let main_user = &data.main_user;
 
-    let app_url = env::var("APP_URL").expect("APP_URL must be set.");
-    let signature = env::var("EMAIL_SIGNATURE").expect("EMAIL_SIGNATURE must be set.");
-
-    let user_address = format!("{}@ed.tus.ac.jp", main_user.student_id);
-    let content = format!("{}{} 様\n\n申請を受け付けました。\n以下のURLにアクセスして申請者のメール認証を完了してください。\n\n{}/locker/user-register?method=1&token={}\n\n{}", main_user.family_name, main_user.given_name, app_url, token, signature);
-    let subject = "【ロッカー登録システム】 メール認証を行ってください";
+    let user_address = app.student_identity.email(&main_user.student_id);
+    let mail = match app.mail_template.render("locker_main_auth", json!({
+        "family_name": main_user.family_name,
+        "given_name": main_user.given_name,
+        "token": auth.main_auth_token,
+    })).await {
+        Ok(mail) => mail,
+        Err(status) => return status,
+    };
+    let audit = MailAudit{
+        template: String::from("locker_main_auth"),
+        student_id: Some(main_user.student_id.clone()),
+        auth_id: Some(auth.auth_id),
+        ..Default::default()
+    };
 
-    if app.option.local_mail_enable {
-        if app.auth.mail_sender_local(user_address, content, subject).await.is_err(){
-            return Status::InternalServerError;
-        }
-    }
-    else {
-        if app.auth.mail_sender(user_address, content, subject).await.is_err(){
-            return Status::InternalServerError;
-        }
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return Status::InternalServerError;
     }
 
     Status::Created
@@ -100,23 +114,18 @@ pub async fn main_auth(token: String, app: &State<App>) -> Status {
 
     // token
 
-    let re = Regex::new(r"^[a-zA-Z0-9]{16}$").unwrap();
+    let re = Regex::new(r"^[a-zA-Z0-9]{32}$").unwrap();
     if !(re.is_match(token.as_str())) {
         return Status::BadRequest;
     }
 
     // tokenが一致するレコードを取得
-    let auth = match app.auth.token_check(token, true).await{
+    let auth = match app.auth.token_check(token, "main_auth", true).await{
         Ok(auth) => auth,
         // 存在しなかったら終了
         Err(status) => return status,
     };
 
-    // authのphaseを確認
-    if auth.phase != *"main_auth" {
-        return Status::BadRequest
-    }
-
     let auth_info = match app.auth.get_locker_auth_info(&auth.auth_id).await {
         Ok(info) => info,
         Err(status) => return status,
@@ -141,25 +150,32 @@ pub async fn main_auth(token: String, app: &State<App>) -> Status {
         given_name: auth_info.co_given_name.clone(),
     };
 
-    // メール内容の作成
-    dotenv().ok();
-    let app_url = env::var("APP_URL").expect("APP_URL must be set.");
-    let signature = env::var("EMAIL_SIGNATURE").expect("EMAIL_SIGNATURE must be set.");
+    // couser用のtokenを発行
+    let co_token = match app.auth.reissue_token(&auth.auth_id, false).await {
+        Ok(token) => token,
+        Err(status) => return status,
+    };
 
-    let user_address = format!("{}@ed.tus.ac.jp", co_user.student_id);
-    let content = format!("{}{} 様\n\n申請者のメール認証が完了しました。\n以下のURLにアクセスして共同利用者のメール認証を完了してください。\n\n{}/locker/user-register?method=0&token={}\n\n{}", co_user.family_name, co_user.given_name, app_url, auth.co_auth_token, signature);
-    let subject = "【ロッカーシステム】 メール認証を行ってください";
+    // メール内容の作成
+    let user_address = app.student_identity.email(&co_user.student_id);
+    let mail = match app.mail_template.render("locker_co_auth", json!({
+        "family_name": co_user.family_name,
+        "given_name": co_user.given_name,
+        "token": co_token,
+    })).await {
+        Ok(mail) => mail,
+        Err(status) => return status,
+    };
+    let audit = MailAudit{
+        template: String::from("locker_co_auth"),
+        student_id: Some(co_user.student_id.clone()),
+        auth_id: Some(auth.auth_id),
+        ..Default::default()
+    };
 
     // メールの送信
-    if app.option.local_mail_enable {
-        if app.auth.mail_sender_local(user_address, content, subject).await.is_err(){
-            return Status::InternalServerError;
-        }
-    }
-    else {
-        if app.auth.mail_sender(user_address, content, subject).await.is_err(){
-            return Status::InternalServerError;
-        }
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return Status::InternalServerError;
     }
 
     // phaseの更新
@@ -178,22 +194,17 @@ pub async fn co_auth(token: String, app: &State<App>) -> Status {
 
     // token
 
-    let re = Regex::new(r"^[a-zA-Z0-9]{16}$").unwrap();
+    let re = Regex::new(r"^[a-zA-Z0-9]{32}$").unwrap();
     if !(re.is_match(token.as_str())) {
         return Status::BadRequest;
     }
 
     // tokenが一致するレコードを取得
-    let auth = match app.auth.token_check(token, false).await{
+    let auth = match app.auth.token_check(token, "co_auth", false).await{
         Ok(auth) => auth,
         Err(status) => return status,
     };
 
-    // authのphaseを確認
-    if auth.phase != *"co_auth" {
-        return Status::BadRequest
-    }
-
     let auth_info = match app.auth.get_locker_auth_info(&auth.auth_id).await {
         Ok(info) => info,
         Err(status) => return status,
@@ -206,11 +217,6 @@ pub async fn co_auth(token: String, app: &State<App>) -> Status {
         given_name: auth_info.co_given_name.clone(),
     };
 
-    // couserの情報をstudentテーブルに保存
-    if app.student.register(&co_user.clone()).await.is_err(){
-        return Status::InternalServerError;
-    }
-
     // mainuserの情報を格納
     let main_user = &UserInfo{
         student_id: auth_info.main_student_id.clone(),
@@ -224,61 +230,60 @@ pub async fn co_auth(token: String, app: &State<App>) -> Status {
         co_user: co_user.clone(),
     };
 
+    // 登録を受け付けている年度
+    let year = match app.time.year("locker_registration").await {
+        Ok(year) => year,
+        Err(status) => return status,
+    };
+
     // studentpairに同じ学籍番号がないか確認
-    match app.student_pair.get_by_id(&main_user.student_id).await {
+    match app.student_pair.get_by_id_and_year(&main_user.student_id, &year).await {
         Ok(None) => {},
         Ok(_) => return Status::InternalServerError,
         Err(status) => return status,
     }
 
-    match app.student_pair.get_by_id(&co_user.student_id).await {
+    match app.student_pair.get_by_id_and_year(&co_user.student_id, &year).await {
         Ok(None) => {},
         Ok(_) => return Status::InternalServerError,
         Err(status) => return status,
     }
 
-    // studentpairの情報をstudent_pairテーブルに保存
-    if app.student_pair.register(student_pair).await.is_err(){
-        return Status::InternalServerError;
-    }
-
-    // 認証完了用のレコードを保存
-    let token = match app.auth.locker_register(&main_user.clone(), &co_user.clone(), &String::from("auth_check"), true).await{
-        Ok(auth) => auth.main_auth_token,
-        Err(_) => return Status::InternalServerError,
+    // couser、studentpairの登録と認証完了用レコードの発行、認証レコードの削除
+    let check_auth = match app.auth.locker_co_auth(&auth.auth_id, student_pair, &year).await {
+        Ok(auth) => auth,
+        Err(status) => return status,
     };
 
     // メールの作成
-    dotenv().ok();
-    let app_url = env::var("APP_URL").expect("APP_URL must be set.");
-    let signature = env::var("EMAIL_SIGNATURE").expect("EMAIL_SIGNATURE must be set.");
-
-    let user_address = format!("{}@ed.tus.ac.jp", main_user.student_id);
-    let content = format!("{}{} 様\n\nメール認証が完了しました。\n\n\
-                                    以下のURLにアクセスして使用するロッカー番号を選択してください。\n\
-                                    {}/locker/user-register/?method=2&token={}\n\n\
-                                    認証された情報は以下の通りです。\n【認証情報】\n\
-                                    申請者\n　学籍番号：{}\n　名前：{} {}\n\
-                                    共同利用者\n　学籍番号：{}\n　名前：{} {}\n\n{}",
-                                    main_user.family_name.clone(), main_user.given_name.clone(), app_url, token,
-                                    main_user.student_id, main_user.family_name, main_user.given_name,
-                                    co_user.student_id, co_user.family_name, co_user.given_name, signature);
-    let subject = "【ロッカー登録システム】 メール認証が完了しました";
+    let user_address = app.student_identity.email(&main_user.student_id);
+    let mail = match app.mail_template.render("locker_auth_check", json!({
+        "family_name": main_user.family_name,
+        "given_name": main_user.given_name,
+        "token": check_auth.main_auth_token,
+        "main": {
+            "student_id": main_user.student_id,
+            "family_name": main_user.family_name,
+            "given_name": main_user.given_name,
+        },
+        "co": {
+            "student_id": co_user.student_id,
+            "family_name": co_user.family_name,
+            "given_name": co_user.given_name,
+        },
+    })).await {
+        Ok(mail) => mail,
+        Err(status) => return status,
+    };
+    let audit = MailAudit{
+        template: String::from("locker_auth_check"),
+        student_id: Some(main_user.student_id.clone()),
+        auth_id: Some(check_auth.auth_id),
+        ..Default::default()
+    };
 
     // メールの送信
-    if app.option.local_mail_enable {
-        if app.auth.mail_sender_local(user_address, content, subject).await.is_err(){
-            return Status::InternalServerError;
-        }
-    }
-    else {
-        if app.auth.mail_sender(user_address, content, subject).await.is_err(){
-            return Status::InternalServerError;
-        }
-    }
-
-    // レコードを削除
-    if app.auth.delete(&auth.auth_id).await.is_err() {
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
         return Status::InternalServerError;
     }
 
@@ -293,22 +298,17 @@ pub async fn auth_check(token: String, app: &State<App>) -> Result<Json<AuthChec
 
     // token
 
-    let re = Regex::new(r"^[a-zA-Z0-9]{16}$").unwrap();
+    let re = Regex::new(r"^[a-zA-Z0-9]{32}$").unwrap();
     if !(re.is_match(token.as_str())) {
         return Err(Status::BadRequest);
     }
 
-    // tokenを取得
-    let auth = match app.auth.token_check(token, true).await{
+    // tokenを取得、確認画面は再読み込みされるため使用済みにしない
+    let auth = match app.auth.token_verify(token, "auth_check", true).await{
         Ok(auth) => auth,
         Err(status) => return Err(status),
     };
 
-    // authのphaseを確認
-    if auth.phase != *"auth_check" {
-        return Err(Status::BadRequest)
-    }
-
     let auth_info = match app.auth.get_locker_auth_info(&auth.auth_id).await {
         Ok(info) => info,
         Err(status) => return Err(status),
@@ -340,10 +340,107 @@ pub async fn auth_check(token: String, app: &State<App>) -> Result<Json<AuthChec
     }))
 }
 
+/// ### 認証メール再送信API
+///
+/// 学籍番号を含む認証中のレコードを取得し、現在のphaseの認証メールを再送信する、受付期間外は再送信しない
+#[utoipa::path(context_path = "/api/locker")]
+#[post("/resend", data = "<request>")]
+pub async fn locker_resend(request: Json<LockerResendRequest>, app: &State<App>) -> (Status, &'static str) {
+
+    // 受付期間の確認、期間が設定されていない場合は常に受け付ける
+    match app.time.is_open("locker_registration").await {
+        Ok(Some(false)) => return (Status::Forbidden, "locker registration is closed"),
+        Ok(_) => {},
+        Err(status) => return (status, "failed to get registration period"),
+    }
+
+    // 学籍番号についてのバリデーション
+    if !(app.student_identity.is_valid(StudentIdFlow::Locker, &request.student_id)) {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    dotenv().ok();
+
+    // recaptchaの検証
+    if !verify_recaptcha(&request.recaptcha_token).await.unwrap_or(false) {
+        return (Status::Unauthorized, "recaptcha verification failed");
+    }
+
+    // 認証中のレコードを取得
+    let (auth, auth_info) = match app.auth.locker_resend(&request.student_id).await {
+        Ok(pending) => pending,
+        Err(status) if status == Status::NotFound => return (status, "no pending authentication"),
+        Err(status) if status == Status::TooManyRequests => return (status, "resend is rate limited"),
+        Err(status) => return (status, "failed to get pending authentication"),
+    };
+
+    // 現在のphaseのメール内容を作成
+    let (student_id, template, context) = match auth.phase.as_str() {
+        "main_auth" => (
+            auth_info.main_student_id.clone(),
+            "locker_main_auth",
+            json!({
+                "family_name": auth_info.main_family_name,
+                "given_name": auth_info.main_given_name,
+                "token": auth.main_auth_token,
+            }),
+        ),
+        "co_auth" => (
+            auth_info.co_student_id.clone(),
+            "locker_co_auth",
+            json!({
+                "family_name": auth_info.co_family_name,
+                "given_name": auth_info.co_given_name,
+                "token": auth.co_auth_token,
+            }),
+        ),
+        "auth_check" => (
+            auth_info.main_student_id.clone(),
+            "locker_auth_check",
+            json!({
+                "family_name": auth_info.main_family_name,
+                "given_name": auth_info.main_given_name,
+                "token": auth.main_auth_token,
+                "main": {
+                    "student_id": auth_info.main_student_id,
+                    "family_name": auth_info.main_family_name,
+                    "given_name": auth_info.main_given_name,
+                },
+                "co": {
+                    "student_id": auth_info.co_student_id,
+                    "family_name": auth_info.co_family_name,
+                    "given_name": auth_info.co_given_name,
+                },
+            }),
+        ),
+        _ => return (Status::BadRequest, "authentication phase does not match"),
+    };
+    let user_address = app.student_identity.email(&student_id);
+    let mail = match app.mail_template.render(template, context).await {
+        Ok(mail) => mail,
+        Err(status) => return (status, "failed to render authentication email"),
+    };
+    let audit = MailAudit{
+        template: String::from(template),
+        student_id: Some(student_id),
+        auth_id: Some(auth.auth_id),
+        ..Default::default()
+    };
+
+    // メールの送信
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return (Status::InternalServerError, "failed to send authentication email");
+    }
+
+    (Status::Created, "authentication email resent")
+}
+
 /// ### ロッカー空き状態確認API
+///
+/// yearを省略した場合は今年度の空き状態を返す
 #[utoipa::path(context_path = "/api/locker")]
-#[get("/availability?<floor>")]
-pub async fn availability(floor: Option<i8>, app: &State<App>) -> Result<Json<LockerStatusResponse>, Status> {
+#[get("/availability?<floor>&<year>")]
+pub async fn availability(floor: Option<i8>, year: Option<i32>, app: &State<App>) -> Result<Json<LockerStatusResponse>, Status> {
     // データのバリデーション
 
     // floor
@@ -354,8 +451,14 @@ pub async fn availability(floor: Option<i8>, app: &State<App>) -> Result<Json<Lo
         }
     }
 
+    // year
+    let year = year.unwrap_or_else(|| Local::now().year());
+    if year < 2024 {
+        return Err(Status::BadRequest);
+    }
+
     // 指定階数のlockerレコードの取得
-    let result = app.locker.get_by_floor(&floor).await.unwrap();
+    let result = app.locker.get_by_floor(&floor, &year).await?;
 
     let mut response: Vec<LockerStatus> = Vec::new();
     for element in result {
@@ -374,18 +477,52 @@ pub async fn availability(floor: Option<i8>, app: &State<App>) -> Result<Json<Lo
     }))
 }
 
+/// ### ロッカー登録受付期間取得API
+///
+/// 期間が設定されていない場合はstart、endともに空文字を返す
+#[utoipa::path(context_path = "/api/locker")]
+#[get("/period/setting")]
+pub async fn period_setting_get(app: &State<App>) -> Result<Json<LockerPeriodSetting>, Status> {
+
+    // nameがlocker_registrationのレコードをtimeから取得しレスポンスを作成
+    let response = match app.time.get_by_name("locker_registration").await {
+        Ok(time) => {
+            LockerPeriodSetting {
+                start: time.start_time.and_local_timezone(Utc).unwrap().to_rfc3339_opts(SecondsFormat::Millis, true),
+                end: time.end_time.and_local_timezone(Utc).unwrap().to_rfc3339_opts(SecondsFormat::Millis, true),
+                year: time.year,
+            }
+        },
+        Err(_) => {
+            LockerPeriodSetting {
+                start: String::from(""),
+                end: String::from(""),
+                year: None,
+            }
+        }
+    };
+
+    Ok(Json(response))
+}
+
 /// ### ロッカー登録API
 #[utoipa::path(context_path = "/api/locker")]
 #[post("/locker-register", data = "<request>")]
 pub async fn locker_register(request: Json<LockerResisterRequest>, app: &State<App>) -> (Status, &'static str) {
 
+    // 受付期間の確認、期間が設定されていない場合は常に受け付ける
+    match app.time.is_open("locker_registration").await {
+        Ok(Some(false)) => return (Status::Forbidden, "locker registration is closed"),
+        Ok(_) => {},
+        Err(status) => return (status, "failed to get registration period"),
+    }
+
     let assignment = &request.data;
 
     //データのバリデーション
 
     // 代表者学籍番号
-    let re = Regex::new(r"^(15\d{5}|[48][1-6]\d{5})$").unwrap();
-    if !(re.is_match(assignment.student_id.as_str())) {
+    if !(app.student_identity.is_valid(StudentIdFlow::Locker, &assignment.student_id)) {
         return (Status::BadRequest, "request data is not valid");
     }
 
@@ -401,88 +538,245 @@ pub async fn locker_register(request: Json<LockerResisterRequest>, app: &State<A
         Err(_) => {return (Status::BadRequest, "request auth_id is not valid");}
     };
 
+    // 登録を受け付けている年度
+    let year = match app.time.year("locker_registration").await {
+        Ok(year) => year,
+        Err(status) => return (status, "failed to get registration period"),
+    };
+
     // pair_idの検索
-    let user_pair = match app.student_pair.get_by_main_id(&assignment.student_id).await {
+    let user_pair = match app.student_pair.get_by_main_id_and_year(&assignment.student_id, &year).await {
         Ok(student_pair) => student_pair,
         Err(_) => return (Status::InternalServerError, "failed to get student_pair id"),
     };
 
     // 既に登録されていないかの確認
-    match app.assignment_record.get_by_pair_id(&user_pair.pair_id).await {
+    match app.assignment_record.get_by_pair_id_and_year(&user_pair.pair_id, &year).await {
         Ok(Some(_)) => {return (Status::InternalServerError, "same pair already exists")},
         Ok(None) => {},
         Err(_) => {return (Status::InternalServerError, "failed to get assignment_record")},
     }
 
-    // 対象ロッカーの空き確認
-    let locker = match app.locker.get_by_id(&assignment.locker_id).await {
-        Ok(locker) => locker,
-        Err(_) => return (Status::InternalServerError, "failed to get locker"),
+    // 対象ロッカーの空き確認、割り当て情報の登録、ロッカーのステータス更新
+    match app.assignment_record.register(&user_pair, assignment, &year).await {
+        Ok(_) => {},
+        Err(status) if status == Status::Conflict => return (Status::BadRequest, "This locker is not vacant"),
+        Err(status) if status == Status::UnprocessableEntity => return (Status::Conflict, "this pair already has a locker"),
+        Err(_) => return (Status::InternalServerError, "failed to insert request"),
+    }
+
+    // レコードを削除
+    if app.auth.delete(&auth_id).await.is_err() {
+        return (Status::InternalServerError, "failed to delete auth table");
+    }
+
+    // メールの作成
+    let user_address = app.student_identity.email(&user_pair.student_id1);
+    let mail = match app.mail_template.render("locker_register_complete", json!({
+        "locker_id": assignment.locker_id,
+    })).await {
+        Ok(mail) => mail,
+        Err(status) => return (status, "failed to render notification email"),
+    };
+    let audit = MailAudit{
+        template: String::from("locker_register_complete"),
+        student_id: Some(user_pair.student_id1.clone()),
+        auth_id: Some(auth_id),
+        ..Default::default()
+    };
+
+    // メールの送信
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return (Status::InternalServerError, "Failed to send authentication email");
+    }
+
+    println!(r"( 'ω')/ウオオオオオアアアーーーーッ！！！");
+
+    (Status::Created, "success create assignment")
+}
+
+/// ### ロッカー登録取り消しのtoken生成、メール送信API
+///
+/// 前回の送信から再送信の間隔が経過していない場合は429を返す
+#[utoipa::path(context_path = "/api/locker")]
+#[post("/cancel/token-gen", data = "<request>")]
+pub async fn cancel_token_generator(request: Json<LockerCancelTokenGenRequest>, app: &State<App>) -> (Status, &'static str) {
+
+    // データのバリデーション
+
+    // 学籍番号
+    if !(app.student_identity.is_valid(StudentIdFlow::Locker, &request.student_id)) {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    // recaptchaの検証
+    if !verify_recaptcha(&request.recaptcha_token).await.unwrap_or(false) {
+        return (Status::Unauthorized, "recaptcha verification failed");
+    }
+
+    // 登録を受け付けている年度
+    let year = match app.time.year("locker_registration").await {
+        Ok(year) => year,
+        Err(status) => return (status, "failed to get registration period"),
+    };
+
+    // 申請者が属するstudent_pairの取得
+    let user_pair = match app.student_pair.get_by_id_and_year(&request.student_id, &year).await {
+        Ok(Some(student_pair)) => student_pair,
+        Ok(None) => return (Status::NotFound, "no locker is assigned"),
+        Err(status) => return (status, "failed to get student_pair"),
     };
 
-    if locker.status != "vacant" {
-        return (Status::BadRequest, "This locker is not vacant");
+    // 割り当て済みのロッカーがあるか確認
+    let record = match app.assignment_record.get_by_pair_id_and_year(&user_pair.pair_id, &year).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return (Status::NotFound, "no locker is assigned"),
+        Err(status) => return (status, "failed to get assignment_record"),
+    };
+
+    // 申請者と共同利用者の情報を取得
+    let partner_id = if user_pair.student_id1 == request.student_id {
+        user_pair.student_id2.clone()
+    } else {
+        user_pair.student_id1.clone()
+    };
+    let user = match app.student.get_by_id(&request.student_id).await {
+        Ok(student) => UserInfo{
+            student_id: student.student_id,
+            family_name: student.family_name,
+            given_name: student.given_name,
+        },
+        Err(status) => return (status, "failed to get student"),
+    };
+    let partner = match app.student.get_by_id(&partner_id).await {
+        Ok(student) => UserInfo{
+            student_id: student.student_id,
+            family_name: student.family_name,
+            given_name: student.given_name,
+        },
+        Err(status) => return (status, "failed to get student"),
+    };
+
+    // tokenの生成、取り消し中の場合は再発行する
+    let auth = match app.auth.locker_cancel_register(&user, &partner).await {
+        Ok(auth) => auth,
+        Err(status) if status == Status::TooManyRequests => return (status, "cancellation email was sent recently"),
+        Err(_) => return (Status::InternalServerError, "failed to generate token"),
+    };
+
+    // メール内容の作成
+    let user_address = app.student_identity.email(&user.student_id);
+    let mail = match app.mail_template.render("locker_cancel_confirm", json!({
+        "family_name": user.family_name,
+        "given_name": user.given_name,
+        "token": auth.main_auth_token,
+        "locker_id": record.locker_id,
+    })).await {
+        Ok(mail) => mail,
+        Err(status) => return (status, "failed to render authentication email"),
+    };
+    let audit = MailAudit{
+        template: String::from("locker_cancel_confirm"),
+        student_id: Some(user.student_id.clone()),
+        auth_id: Some(auth.auth_id),
+        ..Default::default()
+    };
+
+    // メールの送信
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return (Status::InternalServerError, "Failed to send authentication email");
     }
 
-    // 割り当て情報の登録
-    if app.assignment_record.register(&user_pair, assignment).await.is_err() {
-        return (Status::InternalServerError, "failed to insert request");
+    (Status::Created, "success send cancellation email")
+}
+
+/// ### ロッカー登録取り消しAPI
+#[utoipa::path(context_path = "/api/locker")]
+#[get("/cancel?<token>")]
+pub async fn cancel(token: String, app: &State<App>) -> (Status, &'static str) {
+
+    // データのバリデーション
+
+    // token
+    let re = Regex::new(r"^[a-zA-Z0-9]{32}$").unwrap();
+    if !(re.is_match(token.as_str())) {
+        return (Status::BadRequest, "request parameter is not valid");
     }
 
-    // ロッカーのステータス更新
-    let status = String::from("occupied");
-    if app.locker.update_status(&assignment.locker_id, &status).await.is_err() {
-        return (Status::InternalServerError, "failed to update locker status");
+    // tokenが一致するレコードを取得
+    let auth = match app.auth.token_check(token, "cancel", true).await {
+        Ok(auth) => auth,
+        Err(status) if status == Status::BadRequest => return (status, "authentication phase does not match"),
+        Err(status) => return (status, "invalid token"),
+    };
+
+    let auth_info = match app.auth.get_locker_auth_info(&auth.auth_id).await {
+        Ok(info) => info,
+        Err(status) => return (status, "failed to get locker auth info"),
+    };
+
+    // 登録を受け付けている年度
+    let year = match app.time.year("locker_registration").await {
+        Ok(year) => year,
+        Err(status) => return (status, "failed to get registration period"),
+    };
+
+    // 割り当て情報の取得
+    let user_pair = match app.student_pair.get_by_id_and_year(&auth_info.main_student_id, &year).await {
+        Ok(Some(student_pair)) => student_pair,
+        Ok(None) => return (Status::NotFound, "no locker is assigned"),
+        Err(status) => return (status, "failed to get student_pair"),
+    };
+
+    let record = match app.assignment_record.get_by_pair_id_and_year(&user_pair.pair_id, &year).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return (Status::NotFound, "no locker is assigned"),
+        Err(status) => return (status, "failed to get assignment_record"),
+    };
+
+    // 割り当ての論理削除、ロッカーのステータスを空きに戻す
+    match app.assignment_record.cancel(&record).await {
+        Ok(_) => {},
+        Err(status) if status == Status::NotFound => return (Status::NotFound, "no locker is assigned"),
+        Err(_) => return (Status::InternalServerError, "failed to cancel assignment"),
     }
 
     // レコードを削除
-    if app.auth.delete(&auth_id).await.is_err() {
+    if app.auth.delete(&auth.auth_id).await.is_err() {
         return (Status::InternalServerError, "failed to delete auth table");
     }
 
-    dotenv().ok();
-    let signature = env::var("EMAIL_SIGNATURE").expect("EMAIL_SIGNATURE must be set");
-
-    let user_address = format!("{}@ed.tus.ac.jp", user_pair.student_id1.clone());
-    let content = format!(
-        "ロッカーの登録が完了しました。\n\n\
-        ロッカー番号: {}\n\n\
-        【内容物の回収・保管・廃棄について】\n\
-        ・内容物回収期間：3月中旬\n\
-        ・保管期間：次年度の4~6月\n\
-        ・廃棄日：次年度の6月下旬\n\n\
-        ※ 期限までに回収しなかった場合、内容物は廃棄され、返還できません。\n\
-        ※ 廃棄に伴う責任は負いかねますので、必ず期間内に回収をお願いします。\n\n\
-        【ロッカー使用時の注意事項】\n\
-        ・ロッカー使用時には必ず鍵を使用してください。\n\
-        ・鍵の購入はこちら： https://www.univcoop.jp/rikadai/time/index.html#s02 \n\n\
-        ご不明点がございましたら、お問い合わせください。\n\n\
-        よろしくお願いいたします。\n\n{}",
-        assignment.locker_id, signature
-    );
-    let subject = "【ロッカー登録システム】 ロッカー登録完了のお知らせ";
+    // メール内容の作成
+    let user_address = app.student_identity.email(&auth_info.main_student_id);
+    let mail = match app.mail_template.render("locker_cancel_complete", json!({
+        "family_name": auth_info.main_family_name,
+        "given_name": auth_info.main_given_name,
+        "locker_id": record.locker_id,
+    })).await {
+        Ok(mail) => mail,
+        Err(status) => return (status, "failed to render notification email"),
+    };
+    let audit = MailAudit{
+        template: String::from("locker_cancel_complete"),
+        student_id: Some(auth_info.main_student_id.clone()),
+        auth_id: Some(auth.auth_id),
+        ..Default::default()
+    };
 
     // メールの送信
-    if app.option.local_mail_enable {
-        if app.auth.mail_sender_local(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
-        }
+    if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+        return (Status::InternalServerError, "Failed to send authentication email");
     }
-    else {
-        if app.auth.mail_sender(user_address, content, subject).await.is_err(){
-            return (Status::InternalServerError, "Failed to send authentication email");
-        }
-    }
-
-    println!(r"( 'ω')/ウオオオオオアアアーーーーッ！！！");
 
-    (Status::Created, "success create assignment")
+    (Status::Ok, "success cancel assignment")
 }
 
 /// ### 管理者パスワード照合API
+///
+/// usernameまたはpasswordが異なる場合は400を返す。連続して失敗したusername、IPアドレスは一定時間ロックアウトし、429を返す
 #[utoipa::path(context_path = "/api")]
 #[post("/login", data = "<request>")]
-pub async fn login(request: Json<LoginFormRequest>, jar: &CookieJar<'_>, app: &State<App>) -> Status {
+pub async fn login(request: Json<LoginFormRequest>, client_ip: Option<IpAddr>, jar: &CookieJar<'_>, app: &State<App>) -> Status {
     // バリデーション
 
     // username
@@ -491,63 +785,87 @@ pub async fn login(request: Json<LoginFormRequest>, jar: &CookieJar<'_>, app: &S
         return Status::BadRequest;
     }
 
-    // password
-    let re = Regex::new(r"^[A-Za-z\d]+$").unwrap();
-    if !(re.is_match(request.password.as_str())) {
+    // password、ポリシー導入前に設定されたパスワードでもログインできるように長さのみ確認する
+    if request.password.is_empty() || request.password.chars().count() > PASSWORD_MAX_LENGTH {
         return Status::BadRequest;
     }
 
+    let ip_address = client_ip.map(|ip| ip.to_string());
+
+    // 連続して失敗したユーザーネーム、IPアドレスはロックアウトする
+    // 同時に送られた試行も失敗回数に数えるため、照合の前にパスワードの誤りとして記録し、結果が確定したら更新する
+    let attempt = match app.login_attempt.start(&request.username, ip_address.clone()).await {
+        Ok(attempt) => attempt,
+        Err(status) => return status,
+    };
+
     // usernameが一致するレコードをadminテーブルから取得
     let credential = match app.admin.get_by_name(&request.username).await {
-        Ok(admin) => admin,
-        Err(_) => return Status::InternalServerError,
+        Ok(admin) => Some(admin),
+        Err(status) if status == Status::NotFound => None,
+        Err(status) => return status,
     };
 
-    // passwordの検証
-    match verify_password_hash(request.password.clone(), credential.password) {
-        Ok(_) => {},
-        Err(_) => {
-            return Status::BadRequest},
+    // passwordの検証、usernameが存在しない場合もパスワードを照合し、passwordが異なる場合と区別できないようにする
+    let password_hash = match &credential {
+        Some(credential) => credential.password.clone(),
+        None => dummy_password_hash().to_string(),
+    };
+    let verified = verify_password_hash(request.password.clone(), password_hash).is_ok();
+    let credential = match credential {
+        Some(credential) if verified => credential,
+        _ => return Status::BadRequest,
+    };
+
+    // 無効化された管理者はログインできない
+    if credential.disabled {
+        if let Err(status) = app.login_attempt.finish(&attempt, LoginResult::Disabled).await {
+            return status;
+        }
+        return Status::Forbidden;
+    }
+
+    if let Err(status) = app.login_attempt.finish(&attempt, LoginResult::Success).await {
+        return status;
     }
 
+    // セッションの作成
+    let session = match app.admin_session.start(&request.username, ip_address).await {
+        Ok(session) => session,
+        Err(status) => return status,
+    };
+
     // 環境変数TOKEN_KEYを取得
     dotenv().ok();
     let key = env::var("TOKEN_KEY").expect("token key must be set.");
-    let domain = env::var("DOMAIN").expect("domain must be set.");
 
-    let token = encode_jwt(&request.username, ChronoDuration::hours(1), &key);
+    let max_age = app.admin_session.policy.idle;
+    let token = encode_jwt(&request.username, credential.role, session.session_id, max_age, &key);
 
     // cookieを作成
-    let cookie = Cookie::build(("token", token))
-        .path("/")
-        .domain(domain)
-        .max_age(RocketDuration::hours(1))
-        .secure(true)
-        .same_site(SameSite::Strict)
-        .http_only(true);
-
-    jar.add(cookie);
+    jar.add(admin_token_cookie(token, max_age));
 
     Status::Created
 }
 
 /// ### 管理者ログアウトAPI
+///
+/// Cookieのjwtのセッションを失効させ、Cookieを削除する
 #[utoipa::path(context_path = "/api")]
 #[post("/logout")]
-pub async fn logout(jar: &CookieJar<'_>) -> Status {
-
-    dotenv().ok();
-    let domain = env::var("DOMAIN").expect("domain must be set.");
-
-    let expired_cookie = Cookie::build(("token", ""))
-        .path("/")
-        .domain(domain)
-        .max_age(RocketDuration::seconds(0)) // 即無効化する
-        .secure(true)
-        .same_site(SameSite::Strict)
-        .http_only(true);
+pub async fn logout(jar: &CookieJar<'_>, app: &State<App>) -> Status {
+
+    // セッションの失効、既に失効している場合も成功とする
+    if let Some(claims) = jar.get("token").and_then(|cookie| decode_jwt(cookie.value())) {
+        match app.admin_session.revoke(&claims.jti).await {
+            Ok(_) => {},
+            Err(status) if status == Status::NotFound => {},
+            Err(status) => return status,
+        }
+    }
 
-    jar.add(expired_cookie);
+    // 即無効化する
+    jar.add(admin_token_cookie(String::new(), ChronoDuration::zero()));
 
     Status::Ok
 }
@@ -557,144 +875,144 @@ pub async fn logout(jar: &CookieJar<'_>) -> Status {
 /// nameは申請者の名前のみ受け付ける
 #[utoipa::path(context_path = "/api/admin/locker")]
 #[get("/user-search/<year>?<floor>&<familyname>&<givenname>")]
-pub async fn user_search(year: i32, floor: Option<i8>, familyname: Option<String>, givenname: Option<String>, jar: &CookieJar<'_>, app: &State<App>) -> Result<Json<UserSearchResponse>, Status> {
+pub async fn user_search(year: i32, floor: Option<i8>, familyname: Option<String>, givenname: Option<String>, _admin: LockerStaff, app: &State<App>) -> Result<Json<UserSearchResponse>, Status> {
 
-    // Cookieからjwtの取得
-    let jwt = match jar.get("token").map(|c| c.value()) {
-        None => return Err(Status::BadRequest),
-        Some(t) => String::from(t),
-    };
+    // データのバリデーション
 
-    // jwtの検証
-    match decode_jwt(&jwt) {
-        None => Err(Status::BadRequest),
-        Some(_) => {
-            // データのバリデーション
+    // year
+    if year < 2024 {
+        return Err(Status::BadRequest);
+    }
 
-            // year
-            if year < 2024 {
+    // floor
+    if let Some(floor) = floor {
+        if !(2..=6).contains(&floor) {
+            return Err(Status::BadRequest);
+        }
+    }
+
+    // familyname
+    let family_name_val = match familyname {
+        None => String::from(""),
+        Some(x) => {
+            let name = String::from(RawStr::new(&x).url_decode().unwrap());
+            let re = Regex::new(r"^[A-Za-z\p{Kana}\p{Hira}\p{Han}]+$").unwrap();
+            if !(re.is_match(name.as_str())) {
                 return Err(Status::BadRequest);
             }
-
-            // floor
-            if let Some(floor) = floor {
-                if !(2..=6).contains(&floor) {
-                    return Err(Status::BadRequest);
-                }
+            else {
+                name
+            }
+        },
+    };
+    // givenname
+    let given_name_val = match givenname {
+        None => String::from(""),
+        Some(x) => {
+            let name = String::from(RawStr::new(&x).url_decode().unwrap());
+            let re = Regex::new(r"^[A-Za-z\p{Kana}\p{Hira}\p{Han}]+$").unwrap();
+            if !(re.is_match(name.as_str())) {
+                return Err(Status::BadRequest);
             }
+            else {
+                name
+            }
+        },
+    };
 
-            // familyname
-            let family_name_val = match familyname {
-                None => String::from(""),
-                Some(x) => {
-                    let name = String::from(RawStr::new(&x).url_decode().unwrap());
-                    let re = Regex::new(r"^[A-Za-z\p{Kana}\p{Hira}\p{Han}]+$").unwrap();
-                    if !(re.is_match(name.as_str())) {
-                        return Err(Status::BadRequest);
-                    }
-                    else {
-                        name
-                    }
-                },
-            };
-            // givenname
-            let given_name_val = match givenname {
-                None => String::from(""),
-                Some(x) => {
-                    let name = String::from(RawStr::new(&x).url_decode().unwrap());
-                    let re = Regex::new(r"^[A-Za-z\p{Kana}\p{Hira}\p{Han}]+$").unwrap();
-                    if !(re.is_match(name.as_str())) {
-                        return Err(Status::BadRequest);
-                    }
-                    else {
-                        name
-                    }
-                },
-            };
+    let match_user = match app.student.get_by_name(&family_name_val, &given_name_val).await {
+        Ok(student) => student,
+        Err(_) => return Err(Status::InternalServerError),
+    };
 
-            let match_user = match app.student.get_by_name(&family_name_val, &given_name_val).await {
-                Ok(student) => student,
-                Err(_) => return Err(Status::InternalServerError),
-            };
+    let mut user_pairs= Vec::new();
+    for element in match_user {
+        match app.student_pair.get_by_id_and_year(&element.student_id, &year).await {
+            Ok(Some(student_pair)) => {
+                user_pairs.push(student_pair);
+            },
+            Ok(None) => {},
+            Err(_) => return Err(Status::InternalServerError),
+        };
+    }
 
-            let mut user_pairs= Vec::new();
-            for element in match_user {
-                match app.student_pair.get_by_id(&element.student_id).await {
-                    Ok(Some(student_pair)) => {
-                        user_pairs.push(student_pair);
-                    },
-                    Ok(None) => {},
-                    Err(_) => return Err(Status::InternalServerError),
-                };
-            }
+    let unique_user_pair: HashSet<StudentPair> = user_pairs.into_iter().collect();
+
+    let mut matched_record: Vec<AssignmentRecord> = Vec::new();
+    for element in unique_user_pair {
+        let mut get_result = match app.assignment_record.get(&year, floor, &element.pair_id).await {
+            Ok(res) => res,
+            Err(_) => return Err(Status::InternalServerError),
+        };
+        matched_record.append(&mut get_result);
+    }
 
-            let unique_user_pair: HashSet<StudentPair> = user_pairs.into_iter().collect();
+    let mut result: Vec<UserSearchResult> = Vec::new();
 
-            let mut matched_record: Vec<AssignmentRecord> = Vec::new();
-            for element in unique_user_pair {
-                let mut get_result = match app.assignment_record.get(&year, floor, &element.pair_id).await {
-                    Ok(res) => res,
-                    Err(_) => return Err(Status::InternalServerError),
-                };
-                matched_record.append(&mut get_result);
-            }
+    for element in matched_record {
+        let pair = match app.student_pair.get_by_pair_id_and_year(&element.pair_id, &year).await {
+            Ok(studentpair) => studentpair,
+            Err(_) => return Err(Status::InternalServerError),
+        };
 
-            let mut result: Vec<UserSearchResult> = Vec::new();
-
-            for element in matched_record {
-                let pair = match app.student_pair.get_by_pair_id(&element.pair_id).await {
-                    Ok(studentpair) => studentpair,
-                    Err(_) => return Err(Status::InternalServerError),
-                };
-
-                let main_user = match app.student.get_by_id(&pair.student_id1).await {
-                    Ok(student) => student,
-                    Err(_) => return Err(Status::InternalServerError),
-                };
-
-                let co_user = match app.student.get_by_id(&pair.student_id2).await {
-                    Ok(student) => student,
-                    Err(_) => return Err(Status::InternalServerError),
-                };
-
-                let main_user_info = UserInfo {
-                    student_id: main_user.student_id.clone(),
-                    family_name: main_user.family_name.clone(),
-                    given_name: main_user.given_name.clone(),
-                };
-
-                let co_user_info = UserInfo {
-                    student_id: co_user.student_id,
-                    family_name: co_user.family_name,
-                    given_name: co_user.given_name,
-                };
-
-                let locker_id_borrow = element.locker_id.clone();
-
-                let num = UserSearchResult {
-                    locker_id: element.locker_id,
-                    floor: locker_id_borrow.chars().next().unwrap().to_digit(10).unwrap() as i8,
-                    main_user: main_user_info,
-                    co_user: co_user_info,
-                    year,
-                };
-
-                result.push(num);
-            }
+        let main_user = match app.student.get_by_id(&pair.student_id1).await {
+            Ok(student) => student,
+            Err(_) => return Err(Status::InternalServerError),
+        };
 
-            result.sort_by(|lt, rt| lt.locker_id.cmp(&rt.locker_id));
+        let co_user = match app.student.get_by_id(&pair.student_id2).await {
+            Ok(student) => student,
+            Err(_) => return Err(Status::InternalServerError),
+        };
 
-            Ok(Json(UserSearchResponse{
-                data: result,
-            }))
-        }
+        let main_user_info = UserInfo {
+            student_id: main_user.student_id.clone(),
+            family_name: main_user.family_name.clone(),
+            given_name: main_user.given_name.clone(),
+        };
+
+        let co_user_info = UserInfo {
+            student_id: co_user.student_id,
+            family_name: co_user.family_name,
+            given_name: co_user.given_name,
+        };
+
+        // 学籍番号から学部、学科を判定する
+        let affiliation = |student_id: &str| app.student_identity.affiliation(student_id).map(|affiliation| UserAffiliation {
+            faculty: affiliation.faculty.clone(),
+            department: affiliation.department.clone(),
+        });
+        let main_affiliation = affiliation(&main_user_info.student_id);
+        let co_affiliation = affiliation(&co_user_info.student_id);
+
+        let locker_id_borrow = element.locker_id.clone();
+
+        let num = UserSearchResult {
+            locker_id: element.locker_id,
+            floor: locker_id_borrow.chars().next().unwrap().to_digit(10).unwrap() as i8,
+            main_user: main_user_info,
+            co_user: co_user_info,
+            main_affiliation,
+            co_affiliation,
+            year,
+        };
+
+        result.push(num);
     }
+
+    result.sort_by(|lt, rt| lt.locker_id.cmp(&rt.locker_id));
+
+    Ok(Json(UserSearchResponse{
+        data: result,
+    }))
 }
 
 /// ロッカーリセットAPI
+///
+/// 今年度の割り当てをすべて取り消し、故障中などを除くロッカーを空きに戻す
 #[utoipa::path(context_path = "/api/admin/locker")]
 #[post("/reset", data = "<request>")]
-pub async fn reset(request: Json<LockerResetRequest>, jar: &CookieJar<'_>, app: &State<App>) -> (Status, &'static str) {
+pub async fn reset(request: Json<LockerResetRequest>, _admin: SuperAdmin, app: &State<App>) -> (Status, &'static str) {
     // バリデーション
 
     // password
@@ -703,30 +1021,532 @@ pub async fn reset(request: Json<LockerResetRequest>, jar: &CookieJar<'_>, app:
         return (Status::BadRequest, "request data is not valid");
     }
 
-    // Cookieからjwtの取得
-    let jwt = match jar.get("token").map(|c| c.value()) {
-        None => return (Status::Unauthorized, "request is unauthorized"),
-        Some(t) => String::from(t),
+    // passwordの検証
+    dotenv().ok();
+    let password = env::var("LOCKER_RESET_PASSWORD_HASH").expect("locker reset password hash must be set");
+    match verify_password_hash(request.password.clone(), password) {
+        Ok(_) => {},
+        Err(_) => {
+            return (Status::BadRequest, "invalid password")},
+    }
+
+    if app.assignment_record.release_year(&Local::now().year()).await.is_err() {
+        return (Status::InternalServerError, "failed to reset locker status")
     };
 
-    // jwtの検証
-    match decode_jwt(&jwt) {
-        None => return (Status::Unauthorized, "request token is not valid"),
-        Some(_) => {
-            // passwordの検証
-            dotenv().ok();
-            let password = env::var("LOCKER_RESET_PASSWORD_HASH").expect("locker reset password hash must be set");
-            match verify_password_hash(request.password.clone(), password) {
-                Ok(_) => {},
-                Err(_) => {
-                    return (Status::BadRequest, "invalid password")},
-            }
+    (Status::Ok, "successfully reset locker")
+}
+
+/// ### ロッカー年度切り替えAPI
+///
+/// 指定年度の割り当て、studentpairをアーカイブした上で割り当てを論理削除し、ロッカーを空きに戻す
+///
+/// dry_runがtrueの場合は対象の確認のみ行う
+#[utoipa::path(context_path = "/api/admin/locker")]
+#[post("/rollover", data = "<request>")]
+pub async fn rollover(request: Json<LockerRolloverRequest>, _admin: SuperAdmin, app: &State<App>) -> Result<Json<LockerRolloverResponse>, (Status, &'static str)> {
+    // バリデーション
+
+    // password
+    let re = Regex::new(r"^[A-Za-z\d]+$").unwrap();
+    if !(re.is_match(request.password.as_str())) {
+        return Err((Status::BadRequest, "request data is not valid"));
+    }
+
+    // year、今年度以前のみ締め切れる
+    if request.year < 2024 || request.year > Local::now().year() {
+        return Err((Status::BadRequest, "request data is not valid"));
+    }
+
+    // passwordの検証
+    dotenv().ok();
+    let password = env::var("LOCKER_RESET_PASSWORD_HASH").expect("locker reset password hash must be set");
+    if verify_password_hash(request.password.clone(), password).is_err() {
+        return Err((Status::BadRequest, "invalid password"));
+    }
+
+    // 論理削除とアーカイブの作成、dry-runの場合はアーカイブを作成しない
+    let result = match app.assignment_record.rollover(&request.year, request.dry_run).await {
+        Ok(result) => result,
+        Err(status) => return Err((status, "failed to rollover locker")),
+    };
+
+    // 現在時刻を "yymmddhhmmss" 形式で取得
+    let filename = result.archive.as_ref().map(|_| {
+        let timestamp = Utc::now().format("%y%m%d%H%M%S").to_string();
+        format!("Locker_{}_{}.zip", request.year, timestamp)
+    });
+
+    Ok(Json(LockerRolloverResponse {
+        dry_run: request.dry_run,
+        year: request.year,
+        closed_assignments: result.records.len(),
+        archived_pairs: result.pairs.len(),
+        reset_lockers: result.reset_lockers,
+        assignments: result.assignments,
+        zip_data: result.archive,
+        filename,
+    }))
+}
+
+/// ### ロッカー登録受付期間設定API
+///
+/// yearを指定すると、前年度の割り当てが残っていてもその年度の登録を受け付けられる
+#[utoipa::path(context_path = "/api/admin/locker")]
+#[post("/period/setting", data = "<request>")]
+pub async fn period_setting_post(request: Json<LockerPeriodSetting>, _admin: LockerStaff, app: &State<App>) -> (Status, &'static str) {
+
+    // 時間情報を整形
+    let (start_time, end_time) = match (DateTime::parse_from_rfc3339(&request.start), DateTime::parse_from_rfc3339(&request.end)) {
+        (Ok(start), Ok(end)) => (start.naive_utc(), end.naive_utc()),
+        _ => return (Status::BadRequest, "request data is not valid"),
+    };
+    if start_time >= end_time {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    // 年度
+    if let Some(year) = request.year {
+        if year < 2024 {
+            return (Status::BadRequest, "request data is not valid");
+        }
+    }
+
+    // 受付期間をDBに保存
+    if app.time.register("locker_registration", &start_time, &end_time, &request.year).await.is_err() {
+        return (Status::InternalServerError, "failed to insert time");
+    }
+
+    (Status::Created, "success set registration period")
+}
 
-            if app.locker.reset_status().await.is_err() {
-                return (Status::InternalServerError, "failed to reset locker status")
+/// ### ロッカー一覧取得API
+///
+/// 階数、場所、状態で絞り込みができる。使用中かどうかはyearの割り当てから判定し、yearを省略した場合は今年度とする
+#[utoipa::path(context_path = "/api/admin/locker")]
+#[get("/lockers?<floor>&<location>&<status>&<year>")]
+pub async fn locker_list(floor: Option<i8>, location: Option<String>, status: Option<String>, year: Option<i32>, _admin: LockerStaff, app: &State<App>) -> Result<Json<LockerListResponse>, Status> {
+
+    // データのバリデーション
+
+    // floor
+    if let Some(floor) = floor {
+        if !(2..=6).contains(&floor) {
+            return Err(Status::BadRequest);
+        }
+    }
+
+    // status
+    let status = match status {
+        None => None,
+        Some(status) => match status.parse::<locker::LockerStatus>() {
+            Ok(status) => Some(status),
+            Err(_) => return Err(Status::BadRequest),
+        },
+    };
+
+    // year
+    let year = year.unwrap_or_else(|| Local::now().year());
+    if year < 2024 {
+        return Err(Status::BadRequest);
+    }
+
+    let result = app.locker.search(&floor, &location, &status, &year).await?;
+
+    let data = result.into_iter().map(|element| LockerDetail{
+        floor: element.locker_id.chars().next().unwrap().to_digit(10).unwrap() as i8,
+        locker_id: element.locker_id,
+        location: element.location,
+        status: element.status,
+    }).collect();
+
+    Ok(Json(LockerListResponse{
+        data,
+    }))
+}
+
+/// ### ロッカー登録API
+#[utoipa::path(context_path = "/api/admin/locker")]
+#[post("/lockers", data = "<request>")]
+pub async fn locker_create(request: Json<LockerCreateRequest>, _admin: LockerStaff, app: &State<App>) -> (Status, &'static str) {
+
+    // データのバリデーション
+
+    // ロッカー番号
+    let re = Regex::new(r"^[2-6]\d{3}$").unwrap();
+    if !(re.is_match(request.locker_id.as_str())) {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    // 場所
+    if request.location.trim().is_empty() || request.location.chars().count() > 64 {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    // 状態、使用中は割り当てによってのみ設定される
+    let status = match request.status.parse::<locker::LockerStatus>() {
+        Ok(locker::LockerStatus::Occupied) | Err(_) => return (Status::BadRequest, "request data is not valid"),
+        Ok(status) => status,
+    };
+
+    match app.locker.register(&request.locker_id, &request.location, &status).await {
+        Ok(_) => {},
+        Err(status) if status == Status::Conflict => return (Status::Conflict, "locker already exists"),
+        Err(status) => return (status, "failed to insert locker"),
+    }
+
+    (Status::Created, "success create locker")
+}
+
+/// ### ロッカー情報更新API
+///
+/// 場所、状態を更新する。使用中のロッカーの状態は変更できない
+#[utoipa::path(context_path = "/api/admin/locker")]
+#[patch("/lockers/<locker_id>", data = "<request>")]
+pub async fn locker_update(locker_id: String, request: Json<LockerUpdateRequest>, _admin: LockerStaff, app: &State<App>) -> (Status, &'static str) {
+
+    // データのバリデーション
+
+    // ロッカー番号
+    let re = Regex::new(r"^[2-6]\d{3}$").unwrap();
+    if !(re.is_match(locker_id.as_str())) {
+        return (Status::BadRequest, "request parameter is not valid");
+    }
+
+    // 場所
+    if let Some(location) = &request.location {
+        if location.trim().is_empty() || location.chars().count() > 64 {
+            return (Status::BadRequest, "request data is not valid");
+        }
+    }
+
+    // 状態、使用中は割り当てによってのみ設定される
+    let status = match &request.status {
+        None => None,
+        Some(status) => match status.parse::<locker::LockerStatus>() {
+            Ok(locker::LockerStatus::Occupied) | Err(_) => return (Status::BadRequest, "request data is not valid"),
+            Ok(status) => Some(status),
+        },
+    };
+
+    if request.location.is_none() && status.is_none() {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    // 今年度または登録を受け付けている年度に割り当てがあるロッカーの状態は変更しない
+    if status.is_some() {
+        let registration_year = match app.time.year("locker_registration").await {
+            Ok(year) => year,
+            Err(status) => return (status, "failed to get registration period"),
+        };
+        let floor = locker_id.chars().next().unwrap().to_digit(10).unwrap() as i8;
+        let mut years = vec![Local::now().year(), registration_year];
+        years.dedup();
+        for year in years {
+            let lockers = match app.locker.get_by_floor(&Some(floor), &year).await {
+                Ok(lockers) => lockers,
+                Err(status) => return (status, "failed to get locker"),
             };
+            match lockers.iter().find(|locker| locker.locker_id == locker_id) {
+                None => return (Status::NotFound, "locker not found"),
+                Some(current) if current.status == locker::LockerStatus::Occupied => return (Status::Conflict, "locker is in use"),
+                Some(_) => {},
+            }
         }
     }
 
-    (Status::Ok, "successfully reset locker")
-}
\ No newline at end of file
+    match app.locker.update(&locker_id, &request.location, &status).await {
+        Ok(_) => {},
+        Err(status) if status == Status::NotFound => return (Status::NotFound, "locker not found"),
+        Err(status) => return (status, "failed to update locker"),
+    }
+
+    (Status::Ok, "success update locker")
+}
+
+/// ### ロッカー削除API
+///
+/// 割り当て履歴のないロッカーのみ削除できる
+#[utoipa::path(context_path = "/api/admin/locker")]
+#[delete("/lockers/<locker_id>")]
+pub async fn locker_delete(locker_id: String, _admin: LockerStaff, app: &State<App>) -> (Status, &'static str) {
+
+    // データのバリデーション
+
+    // ロッカー番号
+    let re = Regex::new(r"^[2-6]\d{3}$").unwrap();
+    if !(re.is_match(locker_id.as_str())) {
+        return (Status::BadRequest, "request parameter is not valid");
+    }
+
+    match app.locker.delete(&locker_id).await {
+        Ok(_) => {},
+        Err(status) if status == Status::NotFound => return (Status::NotFound, "locker not found"),
+        Err(status) if status == Status::Conflict => return (Status::Conflict, "locker is in use"),
+        Err(status) => return (status, "failed to delete locker"),
+    }
+
+    (Status::Ok, "success delete locker")
+}
+
+/// ロッカー一括登録APIで受け付けるCSVの上限(MiB)
+const LOCKER_IMPORT_LIMIT: u64 = 1;
+
+/// ### ロッカー一括登録API
+///
+/// lockerdata.csvと同じ形式(locker_id,location,status)のCSVをtext/csvで受け取り、全てのロッカーを1トランザクションで登録または更新する
+///
+/// dry_runがtrueの場合は差分のみ返す
+///
+/// 今年度または登録を受け付けている年度に割り当てがあるロッカーの状態を変更する行はinUseとして返し、dry_runでない場合は409で全体を拒否する
+#[utoipa::path(context_path = "/api/admin/locker", request_body(content = String, content_type = "text/csv", example = "\"2001\",\"講義棟\",\"vacant\"\n"))]
+#[post("/lockers/import?<dry_run>", format = "text/csv", data = "<csv>")]
+pub async fn locker_import(csv: Data<'_>, dry_run: Option<bool>, _admin: LockerStaff, app: &State<App>) -> Result<Json<LockerImportResponse>, (Status, String)> {
+
+    let dry_run = dry_run.unwrap_or(false);
+    let csv = csv.open(LOCKER_IMPORT_LIMIT.mebibytes()).into_string().await
+        .map_err(|e| (Status::BadRequest, format!("invalid csv: {}", e)))?;
+    if !csv.is_complete() {
+        return Err((Status::PayloadTooLarge, String::from("csv is too large")));
+    }
+
+    // CSVの読み込みとバリデーション
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .trim(Trim::All)
+        .from_reader(csv.as_bytes());
+
+    let re = Regex::new(r"^[2-6]\d{3}$").unwrap();
+    let mut locker_ids = HashSet::new();
+    let mut lockers: Vec<locker::LockerInfo> = Vec::new();
+
+    for result in reader.records() {
+        let record = result.map_err(|e| (Status::BadRequest, format!("invalid csv: {}", e)))?;
+        let line = record.position().map(|p| p.line()).unwrap_or(0);
+
+        let row = record.deserialize::<locker::LockerInfo>(None)
+            .map_err(|_| (Status::BadRequest, format!("line {}: invalid format", line)))?;
+
+        // ロッカー番号
+        if !(re.is_match(row.locker_id.as_str())) {
+            return Err((Status::BadRequest, format!("line {}: invalid locker_id", line)));
+        }
+        if !locker_ids.insert(row.locker_id.clone()) {
+            return Err((Status::BadRequest, format!("line {}: duplicate locker_id", line)));
+        }
+
+        // 場所
+        if row.location.is_empty() || row.location.chars().count() > 64 {
+            return Err((Status::BadRequest, format!("line {}: invalid location", line)));
+        }
+
+        // 状態、使用中かどうかは年度ごとの割り当てから判定するため指定できない
+        if row.status == locker::LockerStatus::Occupied {
+            return Err((Status::BadRequest, format!("line {}: invalid status", line)));
+        }
+
+        lockers.push(row);
+    }
+
+    if lockers.is_empty() {
+        return Err((Status::BadRequest, String::from("csv is empty")));
+    }
+
+    // 今年度または登録を受け付けている年度に割り当てがあるロッカーの状態は変更しない
+    let registration_year = app.time.year("locker_registration").await
+        .map_err(|status| (status, String::from("failed to get registration period")))?;
+    let mut years = vec![Local::now().year(), registration_year];
+    years.dedup();
+
+    let diff = app.locker.import(&lockers, &years, dry_run).await
+        .map_err(|status| (status, String::from("failed to import lockers")))?;
+
+    if !dry_run && !diff.in_use.is_empty() {
+        return Err((Status::Conflict, format!("locker is in use: {}", diff.in_use.join(","))));
+    }
+
+    Ok(Json(LockerImportResponse{
+        dry_run,
+        created: diff.created,
+        updated: diff.updated,
+        unchanged: diff.unchanged,
+        in_use: diff.in_use,
+    }))
+}
+
+/// ### 管理者用ロッカー割り当てAPI
+///
+/// メール認証を経ずにstudent、studentpairを登録し、指定したロッカーを割り当てる
+#[utoipa::path(context_path = "/api/admin/locker")]
+#[post("/assignments", data = "<request>")]
+pub async fn assignment_create(request: Json<LockerAssignmentRequest>, _admin: LockerStaff, app: &State<App>) -> (Status, &'static str) {
+
+    let data = &request.data;
+
+    // データのバリデーション
+
+    // 学籍番号
+    if !(app.student_identity.is_valid(StudentIdFlow::Locker, &data.main_user.student_id)) || !(app.student_identity.is_valid(StudentIdFlow::Locker, &data.co_user.student_id)) {
+        return (Status::BadRequest, "request data is not valid");
+    }
+    if !app.option.same_student_enable && data.main_user.student_id == data.co_user.student_id {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    // 氏名
+    let re = Regex::new(r"^[A-Za-z\p{Kana}\p{Hira}\p{Han}]+$").unwrap();
+    for name in [&data.main_user.family_name, &data.main_user.given_name, &data.co_user.family_name, &data.co_user.given_name] {
+        if !(re.is_match(name.as_str())) {
+            return (Status::BadRequest, "request data is not valid");
+        }
+    }
+
+    // ロッカー番号
+    let re = Regex::new(r"^[2-6]\d{3}$").unwrap();
+    if !(re.is_match(request.locker_id.as_str())) {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    // 登録を受け付けている年度
+    let year = match app.time.year("locker_registration").await {
+        Ok(year) => year,
+        Err(status) => return (status, "failed to get registration period"),
+    };
+
+    // studentpairに同じ学籍番号がないか確認
+    for student_id in [&data.main_user.student_id, &data.co_user.student_id] {
+        match app.student_pair.get_by_id_and_year(student_id, &year).await {
+            Ok(None) => {},
+            Ok(_) => return (Status::Conflict, "student is already registered"),
+            Err(status) => return (status, "failed to get student_pair"),
+        }
+    }
+
+    // student、studentpairの登録とロッカーの割り当て
+    match app.assignment_record.register_with_pair(data, &request.locker_id, &year).await {
+        Ok(_) => {},
+        Err(status) if status == Status::NotFound => return (Status::NotFound, "locker not found"),
+        Err(status) if status == Status::Conflict => return (Status::Conflict, "locker is not vacant"),
+        Err(status) if status == Status::UnprocessableEntity => return (Status::Conflict, "this pair already has a locker"),
+        Err(status) => return (status, "failed to insert assignment"),
+    }
+
+    // メールの作成
+    for user in [&data.main_user, &data.co_user] {
+        let user_address = app.student_identity.email(&user.student_id);
+        let mail = match app.mail_template.render("locker_assignment_create", json!({
+            "family_name": user.family_name,
+            "given_name": user.given_name,
+            "locker_id": request.locker_id,
+            "main": {
+                "student_id": data.main_user.student_id,
+                "family_name": data.main_user.family_name,
+                "given_name": data.main_user.given_name,
+            },
+            "co": {
+                "student_id": data.co_user.student_id,
+                "family_name": data.co_user.family_name,
+                "given_name": data.co_user.given_name,
+            },
+        })).await {
+            Ok(mail) => mail,
+            Err(status) => return (status, "failed to render notification email"),
+        };
+
+        let audit = MailAudit{
+            template: String::from("locker_assignment_create"),
+            student_id: Some(user.student_id.clone()),
+            ..Default::default()
+        };
+
+        // メールの送信
+        if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+            return (Status::InternalServerError, "Failed to send notification email");
+        }
+    }
+
+    (Status::Created, "success create assignment")
+}
+
+/// ### 管理者用ロッカー付け替えAPI
+///
+/// 登録を受け付けている年度の割り当てを別の空きロッカーへ付け替える。付け替え元のロッカーはprev_statusに更新する
+#[utoipa::path(context_path = "/api/admin/locker")]
+#[patch("/assignments/<locker_id>", data = "<request>")]
+pub async fn assignment_update(locker_id: String, request: Json<LockerReassignmentRequest>, _admin: LockerStaff, app: &State<App>) -> (Status, &'static str) {
+
+    // データのバリデーション
+
+    // ロッカー番号
+    let re = Regex::new(r"^[2-6]\d{3}$").unwrap();
+    if !(re.is_match(locker_id.as_str())) {
+        return (Status::BadRequest, "request parameter is not valid");
+    }
+    if !(re.is_match(request.locker_id.as_str())) || request.locker_id == locker_id {
+        return (Status::BadRequest, "request data is not valid");
+    }
+
+    // 付け替え元の状態、使用中は割り当てによってのみ設定される
+    let prev_status = match &request.prev_status {
+        None => locker::LockerStatus::Vacant,
+        Some(status) => match status.parse::<locker::LockerStatus>() {
+            Ok(locker::LockerStatus::Occupied) | Err(_) => return (Status::BadRequest, "request data is not valid"),
+            Ok(status) => status,
+        },
+    };
+
+    // 登録を受け付けている年度
+    let year = match app.time.year("locker_registration").await {
+        Ok(year) => year,
+        Err(status) => return (status, "failed to get registration period"),
+    };
+
+    // 割り当ての付け替え
+    let record = match app.assignment_record.reassign(&locker_id, &request.locker_id, &year, &prev_status).await {
+        Ok(record) => record,
+        Err(status) if status == Status::NotFound => return (Status::NotFound, "assignment or locker not found"),
+        Err(status) if status == Status::Conflict => return (Status::Conflict, "locker is not vacant"),
+        Err(status) => return (status, "failed to update assignment"),
+    };
+
+    // ペアの情報を取得
+    let pair = match app.student_pair.get_by_pair_id_and_year(&record.pair_id, &year).await {
+        Ok(pair) => pair,
+        Err(status) => return (status, "failed to get student_pair"),
+    };
+
+    let mut users = Vec::new();
+    for student_id in [&pair.student_id1, &pair.student_id2] {
+        match app.student.get_by_id(student_id).await {
+            Ok(student) => users.push(student),
+            Err(status) => return (status, "failed to get student"),
+        }
+    }
+
+    // メールの作成
+    for user in users {
+        let user_address = app.student_identity.email(&user.student_id);
+        let mail = match app.mail_template.render("locker_assignment_update", json!({
+            "family_name": user.family_name,
+            "given_name": user.given_name,
+            "prev_locker_id": locker_id,
+            "locker_id": record.locker_id,
+        })).await {
+            Ok(mail) => mail,
+            Err(status) => return (status, "failed to render notification email"),
+        };
+
+        let audit = MailAudit{
+            template: String::from("locker_assignment_update"),
+            student_id: Some(user.student_id.clone()),
+            ..Default::default()
+        };
+
+        // メールの送信
+        if app.mail_outbox.enqueue(user_address, mail, audit).await.is_err(){
+            return (Status::InternalServerError, "Failed to send notification email");
+        }
+    }
+
+    (Status::Ok, "success update assignment")
+}
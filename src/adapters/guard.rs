@@ -0,0 +1,206 @@
+use crate::domain::admin::AdminRole;
+use crate::infrastructure::router::App;
+use crate::usecase::{admin::AdminUsecase, admin_session::AdminSessionUsecase, time::TimeUsecase};
+use crate::utils::jwt::{encode_jwt, decode_jwt};
+
+use std::env;
+use chrono::{TimeDelta, Utc};
+use dotenv::dotenv;
+use rocket::{http::{Cookie, SameSite, Status}, request::{FromRequest, Outcome, Request}};
+use rocket::time::Duration as RocketDuration;
+use uuid::Uuid;
+
+/// ### GuardMessage
+/// リクエストガードがリクエストを拒否した理由
+///
+/// リクエストのlocal_cacheに保存し、catcherでレスポンスの本文として返す
+pub struct GuardMessage(pub &'static str);
+
+/// ### reject
+/// リクエストを拒否し、拒否した理由をcatcherに渡す
+fn reject<T>(req: &Request<'_>, status: Status, message: &'static str) -> Outcome<T, &'static str> {
+    req.local_cache(|| GuardMessage(message));
+    Outcome::Error((status, message))
+}
+
+/// ### admin_token_cookie
+/// 管理者のjwtを保存するCookieを作成する、max_ageが0の場合はCookieを削除する
+pub fn admin_token_cookie(token: String, max_age: TimeDelta) -> Cookie<'static> {
+    dotenv().ok();
+    let domain = env::var("DOMAIN").expect("domain must be set.");
+
+    Cookie::build(("token", token))
+        .path("/")
+        .domain(domain)
+        .max_age(RocketDuration::seconds(max_age.num_seconds()))
+        .secure(true)
+        .same_site(SameSite::Strict)
+        .http_only(true)
+        .build()
+}
+
+/// ### CircleAccessWindow
+/// 団体登録の受付期間内であることを確認するリクエストガード
+///
+/// timeテーブルのaccess_restrictionsを参照し、期間外の場合は403を返す。ロッカーの受付期間と同様に、期間が設定されていない場合は常に受け付ける
+pub struct CircleAccessWindow;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CircleAccessWindow {
+    type Error = &'static str;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let app = match req.rocket().state::<App>() {
+            Some(app) => app,
+            None => return reject(req, Status::InternalServerError, "app state is not managed"),
+        };
+
+        match app.time.is_open("access_restrictions").await {
+            Ok(Some(false)) => reject(req, Status::Forbidden, "circle registration is closed"),
+            Ok(_) => Outcome::Success(CircleAccessWindow),
+            Err(status) => reject(req, status, "failed to get access restrictions"),
+        }
+    }
+}
+
+/// ### AdminUser
+/// ログインしている管理者であることを確認するリクエストガード
+///
+/// Cookieのjwtを検証し、jtiのセッションが有効であることを確認した上でadminテーブルから管理者を取得する。権限はjwtではなくadminテーブルの値を使用するため、権限の変更や無効化は発行済みのjwtにも反映される
+///
+/// セッションの有効期限はリクエストのたびに延長し、jwtの残り時間が延長後の時間の半分を下回った場合はjwtを再発行する
+///
+/// jwtがない、不正、セッションが失効している、または管理者が存在しない場合は401、無効化されている場合は403を返す
+///
+/// 検証結果はリクエストごとに1回だけ求め、同じリクエストの他のガードはキャッシュした結果を使用する
+#[derive(Clone)]
+pub struct AdminUser {
+    pub username: String,
+    pub role: AdminRole,
+    pub session_id: Uuid,
+}
+
+// リクエストのlocal_cacheに保存するAdminUserの検証結果
+struct AdminUserOutcome(Outcome<AdminUser, &'static str>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminUser {
+    type Error = &'static str;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        req.local_cache_async(async { AdminUserOutcome(authenticate(req).await) }).await.0.clone()
+    }
+}
+
+/// ### authenticate
+/// jwtとセッションを検証してログインしている管理者を取得する、セッションの延長とjwtの再発行も行う
+async fn authenticate(req: &Request<'_>) -> Outcome<AdminUser, &'static str> {
+    // Cookieからjwtの取得
+    let jwt = match req.cookies().get("token") {
+        Some(cookie) => cookie.value().to_string(),
+        None => return reject(req, Status::Unauthorized, "request is unauthorized"),
+    };
+
+    // jwtの検証
+    let claims = match decode_jwt(&jwt) {
+        Some(claims) => claims,
+        None => return reject(req, Status::Unauthorized, "request token is not valid"),
+    };
+
+    let app = match req.rocket().state::<App>() {
+        Some(app) => app,
+        None => return reject(req, Status::InternalServerError, "app state is not managed"),
+    };
+
+    // セッションの確認と有効期限の延長
+    let session = match app.admin_session.refresh(&claims.jti).await {
+        Ok(session) if session.username == claims.sub => session,
+        Ok(_) => return reject(req, Status::Unauthorized, "request token is not valid"),
+        Err(status) if status == Status::NotFound => return reject(req, Status::Unauthorized, "request token is not valid"),
+        Err(status) if status == Status::Unauthorized => return reject(req, Status::Unauthorized, "session has expired or been revoked"),
+        Err(status) => return reject(req, status, "failed to get admin session"),
+    };
+
+    // jwtの持ち主をadminテーブルから取得
+    let admin = match app.admin.get_by_name(&claims.sub).await {
+        Ok(admin) => admin,
+        Err(status) if status == Status::NotFound => return reject(req, Status::Unauthorized, "request token is not valid"),
+        Err(status) => return reject(req, status, "failed to get admin"),
+    };
+
+    if admin.disabled {
+        return reject(req, Status::Forbidden, "admin is disabled");
+    }
+
+    // jwtの有効期限が近い場合はセッションの有効期限まで延長したjwtを再発行する
+    let now = Utc::now().naive_utc();
+    let remaining = TimeDelta::seconds(claims.exp as i64 - now.and_utc().timestamp());
+    if remaining < app.admin_session.policy.idle / 2 {
+        dotenv().ok();
+        let key = env::var("TOKEN_KEY").expect("token key must be set.");
+        let max_age = session.expires_at - now;
+        let token = encode_jwt(&admin.username, admin.role, session.session_id, max_age, &key);
+        req.cookies().add(admin_token_cookie(token, max_age));
+    }
+
+    Outcome::Success(AdminUser {
+        username: admin.username,
+        role: admin.role,
+        session_id: session.session_id,
+    })
+}
+
+/// ### authorize
+/// ログインしている管理者がrequiredの権限を持つか確認する、権限がない場合は403を返す
+async fn authorize(req: &Request<'_>, required: AdminRole) -> Outcome<AdminUser, &'static str> {
+    let admin = match AdminUser::from_request(req).await {
+        Outcome::Success(admin) => admin,
+        Outcome::Error(e) => return Outcome::Error(e),
+        Outcome::Forward(status) => return Outcome::Forward(status),
+    };
+
+    if !admin.role.permits(required) {
+        return reject(req, Status::Forbidden, "request is forbidden");
+    }
+
+    Outcome::Success(admin)
+}
+
+/// ### SuperAdmin
+/// superadminの管理者であることを確認するリクエストガード
+pub struct SuperAdmin(pub AdminUser);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SuperAdmin {
+    type Error = &'static str;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        authorize(req, AdminRole::Superadmin).await.map(SuperAdmin)
+    }
+}
+
+/// ### LockerStaff
+/// ロッカーの管理機能を使用できる管理者であることを確認するリクエストガード
+pub struct LockerStaff(pub AdminUser);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LockerStaff {
+    type Error = &'static str;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        authorize(req, AdminRole::LockerStaff).await.map(LockerStaff)
+    }
+}
+
+/// ### CircleStaff
+/// 団体の管理機能を使用できる管理者であることを確認するリクエストガード
+pub struct CircleStaff(pub AdminUser);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CircleStaff {
+    type Error = &'static str;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        authorize(req, AdminRole::CircleStaff).await.map(CircleStaff)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::domain::{assignment::AssignmentInfo, circle::{OrganizationInfo, OrganizationUpdateInfo}, student::UserInfo, student_pair::PairInfo};
+use crate::domain::{admin::{AdminRole, LoginResult}, assignment::{AssignmentInfo, RolloverAssignment}, mail::MailStatus, circle::{OrganizationInfo, OrganizationUpdateInfo, AcceptanceStatus, AuthenticationStatus, FormConfirmationStatus, RegistrationCompleteStatus}, locker::{self, LockerInfo, LockerChange}, student::UserInfo, student_pair::PairInfo};
 
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -60,6 +60,32 @@ pub struct CircleUpdateTokenGenRequest {
     pub data: OrganizationUpdateInfo,
 }
 
+/// ### LockerResendRequest
+///
+/// ロッカー登録の認証メール再送信APIのリクエストに使用
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerResendRequest {
+    #[schema(example = "4622999")]
+    pub student_id: String,
+    pub recaptcha_token: String,
+}
+
+/// ### CircleResendRequest
+///
+/// 団体登録の認証メール再送信APIのリクエストに使用
+///
+/// 情報更新の認証中の場合はorganization_idが必要
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CircleResendRequest {
+    #[schema(example = "4622999")]
+    pub student_id: String,
+    #[schema(example = "C00001")]
+    pub organization_id: Option<String>,
+    pub recaptcha_token: String,
+}
+
 /// ### AuthCheckResponse
 ///
 /// 認証検証APIのレスポンスに使用
@@ -78,7 +104,8 @@ pub struct AuthCheckResponse {
 pub struct LockerStatus{
     pub locker_id: String,
     pub floor: i8,
-    pub status: String,
+    #[schema(inline)]
+    pub status: locker::LockerStatus,
 }
 
 /// ### LockerStatusResponse
@@ -99,6 +126,17 @@ pub struct LockerResisterRequest {
     pub auth_id: String,
 }
 
+/// ### LockerCancelTokenGenRequest
+///
+/// ロッカー登録取り消しのtoken生成、メール送信APIのリクエストに使用
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerCancelTokenGenRequest {
+    #[schema(example = "4622999")]
+    pub student_id: String,
+    pub recaptcha_token: String,
+}
+
 /// ### 管理者パスワード照合APIのリクエストデータ
 ///
 /// username    : ユーザ名
@@ -113,9 +151,23 @@ pub struct LoginFormRequest{
     pub password : String,
 }
 
+/// ### UserAffiliation
+///
+/// 学籍番号の先頭から判定した学部、学科
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserAffiliation {
+    #[schema(example = "創域理工学部")]
+    pub faculty : String,
+    #[schema(example = "電気電子情報工学科")]
+    pub department : String,
+}
+
 /// ### UseSearchResult
 ///
 /// UserSearchResponseに使用する構造体
+///
+/// main_affiliation、co_affiliationは学籍番号から学部、学科を判定できない場合はnull
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UserSearchResult {
@@ -123,6 +175,8 @@ pub struct UserSearchResult {
     pub floor : i8,
     pub main_user : UserInfo,
     pub co_user : UserInfo,
+    pub main_affiliation : Option<UserAffiliation>,
+    pub co_affiliation : Option<UserAffiliation>,
     pub year : i32,
 }
 
@@ -135,6 +189,286 @@ pub struct UserSearchResponse {
     pub data: Vec<UserSearchResult>,
 }
 
+/// ### LockerPeriodSetting
+///
+/// ロッカー登録の受付期間に使用
+///
+/// year        : 受付で登録する割り当ての年度、省略した場合は今年度
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerPeriodSetting {
+    #[schema(example = "2025-04-01T00:00:00.000Z")]
+    pub start: String,
+    #[schema(example = "2025-04-15T00:00:00.000Z")]
+    pub end: String,
+    #[schema(example = 2025)]
+    pub year: Option<i32>,
+}
+
+/// ### AuthSweepStatusResponse
+///
+/// 放置された認証レコードの定期削除の実行状況に使用
+///
+/// 一度も実行されていない場合はlast_runがnull
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthSweepStatusResponse {
+    #[schema(example = "2025-04-01T00:00:00.000Z")]
+    pub last_run: Option<String>,
+    pub deleted_auth: usize,
+    pub deleted_locker_auth_info: usize,
+    pub deleted_circle_auth_info: usize,
+}
+
+/// ### MailTemplateSummary
+///
+/// MailTemplateListResponseに使用する構造体
+///
+/// 管理画面から編集されていない場合はupdated_atがnull
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MailTemplateSummary {
+    #[schema(example = "locker_register_complete")]
+    pub name: String,
+    pub overridden: bool,
+    #[schema(example = "2025-04-01T00:00:00.000Z")]
+    pub updated_at: Option<String>,
+}
+
+/// ### MailTemplateListResponse
+///
+/// メールテンプレート一覧の取得に使用
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MailTemplateListResponse {
+    #[schema(example = "ja")]
+    pub locale: String,
+    pub data: Vec<MailTemplateSummary>,
+}
+
+/// ### MailTemplateResponse
+///
+/// メールテンプレートの取得に使用
+///
+/// 管理画面から編集されていない場合はテンプレートファイルの内容を返す
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MailTemplateResponse {
+    #[schema(example = "locker_register_complete")]
+    pub name: String,
+    #[schema(example = "ja")]
+    pub locale: String,
+    #[schema(example = "【ロッカー登録システム】 ロッカー登録完了のお知らせ")]
+    pub subject: String,
+    #[schema(example = "ロッカー番号: {{ locker_id }}\n\n{{ signature }}")]
+    pub body_text: String,
+    pub body_html: Option<String>,
+    pub overridden: bool,
+    #[schema(example = "2025-04-01T00:00:00.000Z")]
+    pub updated_at: Option<String>,
+}
+
+/// ### MailTemplateRequest
+///
+/// メールテンプレートの編集に使用
+///
+/// body_htmlを省略した場合はテキストのみのメールを送信する
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MailTemplateRequest {
+    #[schema(example = "【ロッカー登録システム】 ロッカー登録完了のお知らせ")]
+    pub subject: String,
+    #[schema(example = "ロッカー番号: {{ locker_id }}\n\n{{ signature }}")]
+    pub body_text: String,
+    pub body_html: Option<String>,
+}
+
+/// ### MailOutboxItem
+///
+/// MailOutboxListResponseに使用する構造体
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MailOutboxItem {
+    pub mail_id: String,
+    #[schema(example = "4622999@ed.tus.ac.jp")]
+    pub to_address: String,
+    pub subject: String,
+    #[schema(example = "locker_main_auth")]
+    pub template: Option<String>,
+    #[schema(example = "4622999")]
+    pub student_id: Option<String>,
+    pub auth_id: Option<String>,
+    #[schema(example = "C00001")]
+    pub organization_id: Option<String>,
+    #[schema(inline)]
+    pub status: MailStatus,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    #[schema(example = "2025-04-01T00:00:00.000Z")]
+    pub next_attempt_at: String,
+    #[schema(example = "2025-04-01T00:00:00.000Z")]
+    pub created_at: String,
+    #[schema(example = "2025-04-01T00:00:00.000Z")]
+    pub sent_at: Option<String>,
+}
+
+/// ### MailOutboxListResponse
+///
+/// 送信待ちメール一覧、メール送信履歴の取得に使用
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MailOutboxListResponse {
+    pub data: Vec<MailOutboxItem>,
+}
+
+/// ### MailOutboxRetryResponse
+///
+/// 送信失敗メールの一括再送に使用
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MailOutboxRetryResponse {
+    pub retried: usize,
+}
+
+/// ### AdminSummary
+///
+/// AdminListResponseに使用する構造体
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminSummary {
+    #[schema(example = "user000")]
+    pub username: String,
+    pub role: AdminRole,
+    pub disabled: bool,
+}
+
+/// ### AdminListResponse
+///
+/// 管理者一覧取得APIのレスポンスに使用
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminListResponse {
+    pub data: Vec<AdminSummary>,
+}
+
+/// ### AdminCreateRequest
+///
+/// 管理者登録APIのリクエストデータ
+///
+/// role        : superadmin、locker_staff、circle_staffのいずれか
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminCreateRequest {
+    #[schema(example = "user000")]
+    pub username: String,
+    pub password: String,
+    #[schema(example = "locker_staff")]
+    pub role: String,
+}
+
+/// ### AdminUpdateRequest
+///
+/// 管理者情報更新APIのリクエストデータ
+///
+/// 指定された項目のみ更新する
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminUpdateRequest {
+    #[schema(example = "circle_staff")]
+    pub role: Option<String>,
+    #[schema(example = true)]
+    pub disabled: Option<bool>,
+}
+
+/// ### AdminPasswordResetRequest
+///
+/// 管理者パスワード再設定APIのリクエストデータ
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminPasswordResetRequest {
+    pub password: String,
+}
+
+/// ### AdminPasswordChangeRequest
+///
+/// 管理者パスワード変更APIのリクエストデータ
+///
+/// current_password    : 現在のパスワード
+///
+/// new_password        : 新しいパスワード
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminPasswordChangeRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// ### AdminSessionItem
+///
+/// AdminSessionListResponseに使用する構造体
+///
+/// current     : リクエストしたセッションであるか
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminSessionItem {
+    pub session_id: String,
+    #[schema(example = "user000")]
+    pub username: String,
+    #[schema(example = "192.0.2.1")]
+    pub ip_address: Option<String>,
+    #[schema(example = "2025-04-01T00:00:00.000Z")]
+    pub created_at: String,
+    #[schema(example = "2025-04-01T00:00:00.000Z")]
+    pub last_seen_at: String,
+    #[schema(example = "2025-04-01T01:00:00.000Z")]
+    pub expires_at: String,
+    pub current: bool,
+}
+
+/// ### AdminSessionListResponse
+///
+/// 管理者セッション一覧取得APIのレスポンスに使用
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminSessionListResponse {
+    pub data: Vec<AdminSessionItem>,
+}
+
+/// ### AdminSessionRevokeResponse
+///
+/// 管理者セッション一括失効APIのレスポンスに使用
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminSessionRevokeResponse {
+    pub revoked: usize,
+}
+
+/// ### LoginAttemptItem
+///
+/// LoginAttemptListResponseに使用する構造体
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginAttemptItem {
+    pub attempt_id: String,
+    #[schema(example = "user000")]
+    pub username: String,
+    #[schema(example = "192.0.2.1")]
+    pub ip_address: Option<String>,
+    #[schema(inline)]
+    pub result: LoginResult,
+    #[schema(example = "2025-04-01T00:00:00.000Z")]
+    pub attempted_at: String,
+}
+
+/// ### LoginAttemptListResponse
+///
+/// ログイン履歴取得APIのレスポンスに使用
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginAttemptListResponse {
+    pub data: Vec<LoginAttemptItem>,
+}
+
 /// ### LockerResetRequest
 ///
 /// ロッカーリセットのリクエストデータ
@@ -144,6 +478,133 @@ pub struct LockerResetRequest {
     pub password: String,
 }
 
+/// ### LockerRolloverRequest
+///
+/// ロッカー年度切り替えAPIのリクエストデータ
+///
+/// year        : 締め切る年度
+///
+/// dry_run     : trueの場合は対象の確認のみ行い、DBは更新しない
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerRolloverRequest {
+    pub password: String,
+    #[schema(example = 2025)]
+    pub year: i32,
+    pub dry_run: bool,
+}
+
+/// ### LockerRolloverResponse
+///
+/// ロッカー年度切り替えAPIのレスポンスに使用
+///
+/// zip_data、filenameは対象年度のアーカイブで、dry-runの場合はnull
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerRolloverResponse {
+    pub dry_run: bool,
+    pub year: i32,
+    pub closed_assignments: usize,
+    pub archived_pairs: usize,
+    pub reset_lockers: usize,
+    pub assignments: Vec<RolloverAssignment>,
+    pub zip_data: Option<Vec<u8>>,
+    pub filename: Option<String>,
+}
+
+/// ### LockerDetail
+///
+/// 管理者用ロッカー一覧のレスポンスに使用する構造体
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerDetail {
+    pub locker_id: String,
+    pub floor: i8,
+    pub location: String,
+    #[schema(inline)]
+    pub status: locker::LockerStatus,
+}
+
+/// ### LockerListResponse
+///
+/// 管理者用ロッカー一覧取得APIのレスポンスに使用
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct LockerListResponse {
+    pub data: Vec<LockerDetail>,
+}
+
+/// ### LockerCreateRequest
+///
+/// 管理者用ロッカー登録APIのリクエストデータ
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerCreateRequest {
+    #[schema(example = "2001")]
+    pub locker_id: String,
+    #[schema(example = "講義棟")]
+    pub location: String,
+    #[schema(example = "vacant")]
+    pub status: String,
+}
+
+/// ### LockerUpdateRequest
+///
+/// 管理者用ロッカー情報更新APIのリクエストデータ
+///
+/// 指定された項目のみ更新する
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerUpdateRequest {
+    #[schema(example = "講義棟")]
+    pub location: Option<String>,
+    #[schema(example = "maintenance")]
+    pub status: Option<String>,
+}
+
+/// ### LockerImportResponse
+///
+/// ロッカー一括登録APIのレスポンスに使用
+///
+/// in_useは割り当てがあるため状態を変更できないロッカー番号
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerImportResponse {
+    pub dry_run: bool,
+    pub created: Vec<LockerInfo>,
+    pub updated: Vec<LockerChange>,
+    pub unchanged: usize,
+    pub in_use: Vec<String>,
+}
+
+/// ### LockerAssignmentRequest
+///
+/// 管理者用ロッカー割り当てAPIのリクエストデータ
+///
+/// メール認証を経ずにstudentpairを登録し、指定したロッカーを割り当てる
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerAssignmentRequest {
+    pub data: PairInfo,
+    #[schema(example = "2001")]
+    pub locker_id: String,
+}
+
+/// ### LockerReassignmentRequest
+///
+/// 管理者用ロッカー付け替えAPIのリクエストデータ
+///
+/// locker_id   : 付け替え先のロッカー番号
+///
+/// prev_status : 付け替え元のロッカーの状態、省略した場合はvacant
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerReassignmentRequest {
+    #[schema(example = "2002")]
+    pub locker_id: String,
+    #[schema(example = "broken")]
+    pub prev_status: Option<String>,
+}
+
 /// ### CircleUpdateRequest
 ///
 /// 団体情報更新のリクエストデータ
@@ -172,10 +633,10 @@ pub struct CircleUpdateRequest {
 pub struct OrganizationStatus {
     pub organization_id: String,
     pub organization_name: String,
-    pub status_acceptance: String,
-    pub status_authentication: String,
-    pub status_form_confirmation: String,
-    pub status_registration_complete: String,
+    pub status_acceptance: AcceptanceStatus,
+    pub status_authentication: AuthenticationStatus,
+    pub status_form_confirmation: FormConfirmationStatus,
+    pub status_registration_complete: RegistrationCompleteStatus,
 }
 
 /// ### OrganizationStatusResponse
@@ -221,10 +682,10 @@ pub struct OrganizationList {
     pub b_url: String,
     pub c_url: String,
     pub d_url: String,
-    pub status_acceptance: String,
-    pub status_authentication: String,
-    pub status_form_confirmation: String,
-    pub status_registration_complete: String,
+    pub status_acceptance: AcceptanceStatus,
+    pub status_authentication: AuthenticationStatus,
+    pub status_form_confirmation: FormConfirmationStatus,
+    pub status_registration_complete: RegistrationCompleteStatus,
 }
 
 /// ### OrganizationListResponse
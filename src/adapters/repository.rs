@@ -3,6 +3,8 @@ pub mod student_pair;
 pub mod locker;
 pub mod assignment_record;
 pub mod admin;
+pub mod admin_session;
+pub mod login_attempt;
 pub mod auth;
 pub mod locker_auth_info;
 pub mod circle_auth_info;
@@ -10,10 +12,16 @@ pub mod registration;
 pub mod representatives;
 pub mod organization;
 pub mod time;
+pub mod mail_template;
+pub mod mail_outbox;
 
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
 use diesel::result::Error as DieselError;
 use diesel::r2d2::PoolError as PoolError;
 
+use crate::infrastructure::router::Pool;
+
 #[derive(Debug, thiserror::Error)]
 pub enum RepositoryError {
     #[error("DBConnectionError: {0}")]
@@ -21,4 +29,34 @@ pub enum RepositoryError {
 
     #[error("QueryError: {0}")]
     DieselError(#[from] DieselError),
-}
\ No newline at end of file
+}
+
+/// ### Transaction
+/// UnitOfWork::run の中で各リポジトリに渡すトランザクションハンドル
+///
+/// 同じハンドルを渡したリポジトリ操作は、まとめてコミットまたはロールバックされる
+pub struct Transaction<'a> {
+    conn: &'a mut PgConnection,
+}
+
+/// ### UnitOfWork
+/// 複数のリポジトリにまたがる処理を1つのトランザクションで実行する
+#[derive(Clone)]
+pub struct UnitOfWork {
+    pool: Pool<PgConnection>,
+}
+
+impl UnitOfWork {
+    pub fn new(pool: Pool<PgConnection>) -> Self {
+        UnitOfWork { pool }
+    }
+
+    /// クロージャがOkを返した場合はコミット、Errを返した場合はロールバックする
+    pub fn run<T, F>(&self, f: F) -> Result<T, RepositoryError>
+    where
+        F: FnOnce(&mut Transaction) -> Result<T, RepositoryError>,
+    {
+        let mut conn = self.pool.get()?;
+        conn.transaction::<_, RepositoryError, _>(|conn| f(&mut Transaction { conn }))
+    }
+}
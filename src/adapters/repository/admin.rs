@@ -1,17 +1,19 @@
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 
+use crate::domain::admin::AdminRole;
 use crate::infrastructure::schema::*;
 use crate::infrastructure::models::*;
 use crate::infrastructure::router::Pool;
-use super::RepositoryError;
+use super::{RepositoryError, Transaction};
 
 /// # admin
 pub trait AdminRepository: Send + Sync {
     fn insert(
         &self,
         username: String,
-        password: String
+        password: String,
+        role: AdminRole,
     ) -> Result<Admin, RepositoryError>;
 
     fn get_by_name(
@@ -19,6 +21,34 @@ pub trait AdminRepository: Send + Sync {
         username: String,
     ) -> Result<Admin, RepositoryError>;
 
+    fn get_all(
+        &self,
+    ) -> Result<Vec<Admin>, RepositoryError>;
+
+    fn update(
+        &self,
+        username: String,
+        password: Option<String>,
+        role: Option<AdminRole>,
+        disabled: Option<bool>,
+    ) -> Result<Admin, RepositoryError>;
+
+    /// トランザクション内でupdateを実行する
+    fn update_tx(
+        &self,
+        tx: &mut Transaction,
+        username: String,
+        password: Option<String>,
+        role: Option<AdminRole>,
+        disabled: Option<bool>,
+    ) -> Result<Admin, RepositoryError>;
+
+    /// トランザクション内で無効化されたものを含む全てのsuperadminを取得し、ロックする
+    fn get_superadmins_for_update_tx(
+        &self,
+        tx: &mut Transaction,
+    ) -> Result<Vec<Admin>, RepositoryError>;
+
     fn delete_by_name(
         &self,
         username: String,
@@ -44,10 +74,12 @@ impl AdminRepository for AdminRepositorySqlImpl {
         &self,
         username: String,
         password: String,
+        role: AdminRole,
     ) -> Result<Admin, RepositoryError> {
         let new_admin = NewAdmin {
             username: &username,
             password: &password,
+            role,
         };
         let mut conn = self.pool.get()?;
         let result = diesel::insert_into(admin::table)
@@ -68,6 +100,61 @@ impl AdminRepository for AdminRepositorySqlImpl {
         Ok(result)
     }
 
+    fn get_all(
+        &self,
+    ) -> Result<Vec<Admin>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = admin::table
+            .order(admin::username.asc())
+            .load::<Admin>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn update(
+        &self,
+        username: String,
+        password: Option<String>,
+        role: Option<AdminRole>,
+        disabled: Option<bool>,
+    ) -> Result<Admin, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        self.update_tx(&mut Transaction { conn: &mut conn }, username, password, role, disabled)
+    }
+
+    fn update_tx(
+        &self,
+        tx: &mut Transaction,
+        username: String,
+        password: Option<String>,
+        role: Option<AdminRole>,
+        disabled: Option<bool>,
+    ) -> Result<Admin, RepositoryError> {
+        let changeset = AdminChangeset {
+            password: password.as_ref(),
+            role: role.as_ref(),
+            disabled,
+        };
+        let result = diesel::update(admin::table.find(username))
+            .set(&changeset)
+            .get_result::<Admin>(tx.conn)?;
+
+        Ok(result)
+    }
+
+    fn get_superadmins_for_update_tx(
+        &self,
+        tx: &mut Transaction,
+    ) -> Result<Vec<Admin>, RepositoryError> {
+        let result = admin::table
+            .filter(admin::role.eq(AdminRole::Superadmin))
+            .order(admin::username.asc())
+            .for_update()
+            .get_results::<Admin>(tx.conn)?;
+
+        Ok(result)
+    }
+
     fn delete_by_name(
             &self,
             username: String,
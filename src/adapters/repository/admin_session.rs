@@ -0,0 +1,182 @@
+use chrono::NaiveDateTime;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::infrastructure::schema::*;
+use crate::infrastructure::models::*;
+use crate::infrastructure::router::Pool;
+use super::RepositoryError;
+
+/// # admin_session
+pub trait AdminSessionRepository: Send + Sync {
+    fn insert(
+        &self,
+        username: String,
+        ip_address: Option<String>,
+        expires_at: NaiveDateTime,
+    ) -> Result<AdminSession, RepositoryError>;
+
+    fn get_by_id(
+        &self,
+        session_id: Uuid,
+    ) -> Result<AdminSession, RepositoryError>;
+
+    /// 失効していない、有効期限内のセッションを取得する、usernameを指定しない場合は全管理者のセッションを取得する
+    fn get_active(
+        &self,
+        username: Option<String>,
+        now: NaiveDateTime,
+    ) -> Result<Vec<AdminSession>, RepositoryError>;
+
+    /// 最終アクセス時刻と有効期限を更新する
+    fn touch(
+        &self,
+        session_id: Uuid,
+        last_seen_at: NaiveDateTime,
+        expires_at: NaiveDateTime,
+    ) -> Result<usize, RepositoryError>;
+
+    fn revoke(
+        &self,
+        session_id: Uuid,
+    ) -> Result<usize, RepositoryError>;
+
+    /// usernameの全てのセッションを失効させる、exceptを指定した場合はそのセッションを除く
+    fn revoke_by_username(
+        &self,
+        username: String,
+        except: Option<Uuid>,
+    ) -> Result<usize, RepositoryError>;
+
+    fn delete_all(
+        &self
+    ) -> Result<usize, RepositoryError>;
+}
+
+pub struct AdminSessionRepositorySqlImpl {
+    pool: Pool<PgConnection>
+}
+
+impl AdminSessionRepositorySqlImpl {
+    pub fn new(pool: Pool<PgConnection>) -> Self {
+        AdminSessionRepositorySqlImpl { pool }
+    }
+}
+
+impl AdminSessionRepository for AdminSessionRepositorySqlImpl {
+    fn insert(
+        &self,
+        username: String,
+        ip_address: Option<String>,
+        expires_at: NaiveDateTime,
+    ) -> Result<AdminSession, RepositoryError> {
+        let new_session = NewAdminSession {
+            username: &username,
+            ip_address: ip_address.as_ref(),
+            expires_at,
+        };
+        let mut conn = self.pool.get()?;
+        let result = diesel::insert_into(admin_session::table)
+            .values(&new_session)
+            .get_result::<AdminSession>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn get_by_id(
+        &self,
+        session_id: Uuid,
+    ) -> Result<AdminSession, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = admin_session::table.find(session_id)
+            .first::<AdminSession>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn get_active(
+        &self,
+        username: Option<String>,
+        now: NaiveDateTime,
+    ) -> Result<Vec<AdminSession>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let mut query = admin_session::table
+            .filter(admin_session::revoked_at.is_null())
+            .filter(admin_session::expires_at.gt(now))
+            .into_boxed();
+
+        if let Some(username) = username {
+            query = query.filter(admin_session::username.eq(username));
+        }
+
+        let result = query
+            .order(admin_session::last_seen_at.desc())
+            .load::<AdminSession>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn touch(
+        &self,
+        session_id: Uuid,
+        last_seen_at: NaiveDateTime,
+        expires_at: NaiveDateTime,
+    ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::update(admin_session::table
+            .filter(admin_session::session_id.eq(session_id))
+            .filter(admin_session::revoked_at.is_null()))
+            .set((
+                admin_session::last_seen_at.eq(last_seen_at),
+                admin_session::expires_at.eq(expires_at),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn revoke(
+        &self,
+        session_id: Uuid,
+    ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::update(admin_session::table
+            .filter(admin_session::session_id.eq(session_id))
+            .filter(admin_session::revoked_at.is_null()))
+            .set(admin_session::revoked_at.eq(diesel::dsl::now))
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn revoke_by_username(
+        &self,
+        username: String,
+        except: Option<Uuid>,
+    ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let mut query = diesel::update(admin_session::table)
+            .filter(admin_session::username.eq(username))
+            .filter(admin_session::revoked_at.is_null())
+            .into_boxed();
+        if let Some(except) = except {
+            query = query.filter(admin_session::session_id.ne(except));
+        }
+        let result = query
+            .set(admin_session::revoked_at.eq(diesel::dsl::now))
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn delete_all(
+        &self
+    ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::delete(admin_session::table)
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+}
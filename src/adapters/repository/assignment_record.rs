@@ -2,10 +2,11 @@ use diesel::pg::PgConnection;
 use diesel::prelude::*;
 use uuid::Uuid;
 
+use crate::domain::locker::LockerStatus;
 use crate::infrastructure::schema::*;
 use crate::infrastructure::models::*;
 use crate::infrastructure::router::Pool;
-use super::RepositoryError;
+use super::{RepositoryError, Transaction};
 
 /// # assignment_record
 pub trait AssignmentRecordRepository: Send + Sync {
@@ -16,10 +17,63 @@ pub trait AssignmentRecordRepository: Send + Sync {
         year: i32,
     ) -> Result<AssignmentRecord, RepositoryError>;
 
+    fn assign(
+        &self,
+        pair_id: Uuid,
+        locker_id: String,
+        year: i32,
+    ) -> Result<Option<AssignmentRecord>, RepositoryError>;
+
+    /// トランザクション内でassignを実行する
+    fn assign_tx(
+        &self,
+        tx: &mut Transaction,
+        pair_id: Uuid,
+        locker_id: String,
+        year: i32,
+    ) -> Result<Option<AssignmentRecord>, RepositoryError>;
+
+    fn reassign(
+        &self,
+        prev_locker_id: String,
+        locker_id: String,
+        year: i32,
+        prev_status: LockerStatus,
+    ) -> Result<Option<AssignmentRecord>, RepositoryError>;
+
+    fn release(
+        &self,
+        record_id: Uuid,
+    ) -> Result<Option<AssignmentRecord>, RepositoryError>;
+
+    fn close_year(
+        &self,
+        year: i32,
+    ) -> Result<Vec<AssignmentRecord>, RepositoryError>;
+
+    /// トランザクション内でclose_yearを実行する
+    fn close_year_tx(
+        &self,
+        tx: &mut Transaction,
+        year: i32,
+    ) -> Result<Vec<AssignmentRecord>, RepositoryError>;
+
     fn get_all(
         &self,
     ) -> Result<Vec<AssignmentRecord>, RepositoryError>;
 
+    fn get_by_year(
+        &self,
+        year: i32,
+    ) -> Result<Vec<AssignmentRecord>, RepositoryError>;
+
+    /// トランザクション内で指定年度の有効な割り当てを取得し、ロックする
+    fn get_by_year_for_update_tx(
+        &self,
+        tx: &mut Transaction,
+        year: i32,
+    ) -> Result<Vec<AssignmentRecord>, RepositoryError>;
+
     fn get(
         &self,
         year: i32,
@@ -38,6 +92,17 @@ pub trait AssignmentRecordRepository: Send + Sync {
     ) -> Result<usize, RepositoryError>;
 }
 
+/// 指定年度にロッカーの有効な割り当てがあるか確認する
+fn is_assigned(conn: &mut PgConnection, locker_id: &str, year: i32) -> Result<bool, RepositoryError> {
+    let result = diesel::select(diesel::dsl::exists(
+        assignment_record::table
+            .filter(assignment_record::locker_id.eq(locker_id).and(assignment_record::year.eq(year)))
+            .filter(assignment_record::deleted_at.is_null())
+    )).get_result::<bool>(conn)?;
+
+    Ok(result)
+}
+
 pub struct AssignmentRecordRepositorySqlImpl {
     pool : Pool<PgConnection>
 }
@@ -68,6 +133,141 @@ impl AssignmentRecordRepository for AssignmentRecordRepositorySqlImpl {
         Ok(result)
     }
 
+    /// ロッカー行をロックした上で空きを確認し、割り当ての登録を1トランザクションで行う
+    ///
+    /// ロッカーが使用可能でないか、指定年度に有効な割り当てがある場合はNoneを返す
+    fn assign(
+        &self,
+        pair_id: Uuid,
+        locker_id: String,
+        year: i32,
+    ) -> Result<Option<AssignmentRecord>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = conn.transaction::<_, RepositoryError, _>(|conn| {
+            self.assign_tx(&mut Transaction { conn }, pair_id, locker_id, year)
+        })?;
+
+        Ok(result)
+    }
+
+    fn assign_tx(
+        &self,
+        tx: &mut Transaction,
+        pair_id: Uuid,
+        locker_id: String,
+        year: i32,
+    ) -> Result<Option<AssignmentRecord>, RepositoryError> {
+        // SELECT ... FOR UPDATE で対象ロッカーをロック
+        let locker = locker::table
+            .find(&locker_id)
+            .for_update()
+            .first::<Locker>(tx.conn)?;
+
+        if locker.status != LockerStatus::Vacant || is_assigned(tx.conn, &locker_id, year)? {
+            return Ok(None);
+        }
+
+        let new_assignmentrecord = NewAssignmentRecord {
+            pair_id: &pair_id,
+            locker_id: &locker_id,
+            year: &year
+        };
+        let record = diesel::insert_into(assignment_record::table)
+            .values(&new_assignmentrecord)
+            .get_result::<AssignmentRecord>(tx.conn)?;
+
+        Ok(Some(record))
+    }
+
+    /// 割り当て中のロッカーを別の空きロッカーへ付け替える処理を1トランザクションで行う
+    ///
+    /// 付け替え元のロッカーはprev_statusに更新する
+    ///
+    /// 付け替え先のロッカーが使用可能でないか、指定年度に有効な割り当てがある場合はNoneを返す
+    fn reassign(
+        &self,
+        prev_locker_id: String,
+        locker_id: String,
+        year: i32,
+        prev_status: LockerStatus,
+    ) -> Result<Option<AssignmentRecord>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = conn.transaction::<_, RepositoryError, _>(|conn| {
+            // デッドロックを避けるため、ロッカー番号順に両方のロッカーをロック
+            let lockers = locker::table
+                .filter(locker::locker_id.eq_any([&prev_locker_id, &locker_id]))
+                .order(locker::locker_id.asc())
+                .for_update()
+                .get_results::<Locker>(conn)?;
+
+            let record = assignment_record::table
+                .filter(assignment_record::locker_id.eq(&prev_locker_id).and(assignment_record::year.eq(year)))
+                .filter(assignment_record::deleted_at.is_null())
+                .first::<AssignmentRecord>(conn)?;
+
+            match lockers.iter().find(|locker| locker.locker_id == locker_id) {
+                None => return Err(RepositoryError::DieselError(diesel::result::Error::NotFound)),
+                Some(locker) if locker.status != LockerStatus::Vacant => return Ok(None),
+                Some(_) => {},
+            }
+            if is_assigned(conn, &locker_id, year)? {
+                return Ok(None);
+            }
+
+            let record = diesel::update(assignment_record::table.find(record.record_id))
+                .set(assignment_record::locker_id.eq(&locker_id))
+                .get_result::<AssignmentRecord>(conn)?;
+
+            diesel::update(locker::table.find(&prev_locker_id))
+                .set(locker::status.eq(prev_status))
+                .execute(conn)?;
+
+            Ok(Some(record))
+        })?;
+
+        Ok(result)
+    }
+
+    /// 割り当てを論理削除する
+    ///
+    /// 対象の割り当てが存在しないか既に削除済みの場合はNoneを返す
+    fn release(
+        &self,
+        record_id: Uuid,
+    ) -> Result<Option<AssignmentRecord>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::update(assignment_record::table.find(record_id))
+            .filter(assignment_record::deleted_at.is_null())
+            .set(assignment_record::deleted_at.eq(diesel::dsl::now))
+            .get_result::<AssignmentRecord>(&mut conn)
+            .optional()?;
+
+        Ok(result)
+    }
+
+    fn close_year(
+        &self,
+        year: i32,
+    ) -> Result<Vec<AssignmentRecord>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        self.close_year_tx(&mut Transaction { conn: &mut conn }, year)
+    }
+
+    /// 指定年度の有効な割り当てをすべて論理削除し、削除した割り当てを返す
+    fn close_year_tx(
+        &self,
+        tx: &mut Transaction,
+        year: i32,
+    ) -> Result<Vec<AssignmentRecord>, RepositoryError> {
+        let result = diesel::update(assignment_record::table)
+            .filter(assignment_record::year.eq(year))
+            .filter(assignment_record::deleted_at.is_null())
+            .set(assignment_record::deleted_at.eq(diesel::dsl::now))
+            .get_results::<AssignmentRecord>(tx.conn)?;
+
+        Ok(result)
+    }
+
     fn get_all(
         &self,
     ) -> Result<Vec<AssignmentRecord>, RepositoryError> {
@@ -78,6 +278,33 @@ impl AssignmentRecordRepository for AssignmentRecordRepositorySqlImpl {
         Ok(result)
     }
 
+    fn get_by_year(
+        &self,
+        year: i32,
+    ) -> Result<Vec<AssignmentRecord>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = assignment_record::table
+            .filter(assignment_record::year.eq(year))
+            .filter(assignment_record::deleted_at.is_null())
+            .get_results::<AssignmentRecord>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn get_by_year_for_update_tx(
+        &self,
+        tx: &mut Transaction,
+        year: i32,
+    ) -> Result<Vec<AssignmentRecord>, RepositoryError> {
+        let result = assignment_record::table
+            .filter(assignment_record::year.eq(year))
+            .filter(assignment_record::deleted_at.is_null())
+            .for_update()
+            .get_results::<AssignmentRecord>(tx.conn)?;
+
+        Ok(result)
+    }
+
     fn get (
         &self,
         year: i32,
@@ -92,6 +319,7 @@ impl AssignmentRecordRepository for AssignmentRecordRepositorySqlImpl {
             .filter(assignment_record::locker_id
                 .like(floor_ex)
             ).filter(assignment_record::pair_id.eq(pair_id).and(assignment_record::year.eq(year))
+            ).filter(assignment_record::deleted_at.is_null()
             ).get_results::<AssignmentRecord>(&mut conn)?;
 
         Ok(result)
@@ -106,6 +334,7 @@ impl AssignmentRecordRepository for AssignmentRecordRepositorySqlImpl {
 
         let result = assignment_record::table
             .filter(assignment_record::pair_id.eq(pair_id).and(assignment_record::year.eq(year)))
+            .filter(assignment_record::deleted_at.is_null())
             .get_result::<AssignmentRecord>(&mut conn)?;
 
         Ok(result)
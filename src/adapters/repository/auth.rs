@@ -1,3 +1,4 @@
+use chrono::NaiveDateTime;
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 use uuid::Uuid;
@@ -5,7 +6,7 @@ use uuid::Uuid;
 use crate::infrastructure::schema::*;
 use crate::infrastructure::models::*;
 use crate::infrastructure::router::Pool;
-use super::RepositoryError;
+use super::{RepositoryError, Transaction};
 
 /// # auth
 pub trait AuthRepository: Send + Sync {
@@ -16,6 +17,15 @@ pub trait AuthRepository: Send + Sync {
         phase: String,
     ) -> Result<Auth, RepositoryError>;
 
+    /// トランザクション内でinsertを実行する
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        main_auth_token: String,
+        co_auth_token: String,
+        phase: String,
+    ) -> Result<Auth, RepositoryError>;
+
     fn get_by_token(
         &self,
         auth_token: String,
@@ -27,11 +37,51 @@ pub trait AuthRepository: Send + Sync {
         phase: String,
     ) -> Result<usize, RepositoryError>;
 
+    /// tokenのハッシュ値を更新し、有効期限の起点を現在時刻にする
+    fn update_token(
+        &self,
+        auth_id: Uuid,
+        is_main: bool,
+        token_hash: String,
+    ) -> Result<usize, RepositoryError>;
+
+    /// threshold 以前から更新されていない場合のみtokenのハッシュ値を更新する
+    /// 同時に再送信された場合は先に更新した方のみが成功し、それ以外は0件になる
+    fn update_token_if_idle(
+        &self,
+        auth_id: Uuid,
+        is_main: bool,
+        token_hash: String,
+        threshold: NaiveDateTime,
+    ) -> Result<usize, RepositoryError>;
+
+    /// tokenのハッシュ値が一致する場合のみ無効化する、同じtokenを共有している場合は両方を無効化する
+    fn consume_token(
+        &self,
+        auth_id: Uuid,
+        is_main: bool,
+        token_hash: String,
+    ) -> Result<usize, RepositoryError>;
+
     fn delete(
         &self,
         auth_ud: Uuid,
     ) -> Result<usize, RepositoryError>;
 
+    /// トランザクション内でdeleteを実行する
+    fn delete_tx(
+        &self,
+        tx: &mut Transaction,
+        auth_ud: Uuid,
+    ) -> Result<usize, RepositoryError>;
+
+    /// threshold より前に更新されたauthを削除する
+    fn delete_stale_tx(
+        &self,
+        tx: &mut Transaction,
+        threshold: NaiveDateTime,
+    ) -> Result<usize, RepositoryError>;
+
     fn delete_all(
         &self
     ) -> Result<usize, RepositoryError>;
@@ -53,16 +103,26 @@ impl AuthRepository for AuthRepositorySqlImpl {
         main_auth_token: String,
         co_auth_token: String,
         phase: String,
+    ) -> Result<Auth, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        self.insert_tx(&mut Transaction { conn: &mut conn }, main_auth_token, co_auth_token, phase)
+    }
+
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        main_auth_token: String,
+        co_auth_token: String,
+        phase: String,
     ) -> Result<Auth, RepositoryError> {
         let new_auth = NewAuth {
             main_auth_token: &main_auth_token,
             co_auth_token: &co_auth_token,
             phase: &phase,
         };
-        let mut conn = self.pool.get()?;
         let result = diesel::insert_into(auth::table)
             .values(&new_auth)
-            .get_result::<Auth>(&mut conn)?;
+            .get_result::<Auth>(tx.conn)?;
 
         Ok(result)
     }
@@ -90,19 +150,115 @@ impl AuthRepository for AuthRepositorySqlImpl {
         ) -> Result<usize, RepositoryError> {
         let mut conn = self.pool.get()?;
         let result = diesel::update(auth::table.find(auth_id))
-            .set(auth::phase.eq(phase))
+            .set((auth::phase.eq(phase), auth::updated_at.eq(diesel::dsl::now)))
             .execute(&mut conn)?;
 
         Ok(result)
     }
 
+    fn update_token(
+            &self,
+            auth_id: Uuid,
+            is_main: bool,
+            token_hash: String,
+        ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let target = diesel::update(auth::table.find(auth_id));
+        let result = if is_main {
+            target.set((auth::main_auth_token.eq(token_hash), auth::updated_at.eq(diesel::dsl::now)))
+                .execute(&mut conn)?
+        } else {
+            target.set((auth::co_auth_token.eq(token_hash), auth::updated_at.eq(diesel::dsl::now)))
+                .execute(&mut conn)?
+        };
+
+        Ok(result)
+    }
+
+    fn update_token_if_idle(
+            &self,
+            auth_id: Uuid,
+            is_main: bool,
+            token_hash: String,
+            threshold: NaiveDateTime,
+        ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let target = diesel::update(auth::table.find(auth_id).filter(auth::updated_at.le(threshold)));
+        let result = if is_main {
+            target.set((auth::main_auth_token.eq(token_hash), auth::updated_at.eq(diesel::dsl::now)))
+                .execute(&mut conn)?
+        } else {
+            target.set((auth::co_auth_token.eq(token_hash), auth::updated_at.eq(diesel::dsl::now)))
+                .execute(&mut conn)?
+        };
+
+        Ok(result)
+    }
+
+    fn consume_token(
+            &self,
+            auth_id: Uuid,
+            is_main: bool,
+            token_hash: String,
+        ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = conn.transaction::<_, RepositoryError, _>(|conn| {
+            // 先に更新した方のみが成功し、同時に使用された場合は0件になる
+            let consumed = if is_main {
+                diesel::update(auth::table.find(auth_id).filter(auth::main_auth_token.eq(&token_hash)))
+                    .set(auth::main_auth_token.eq(""))
+                    .execute(conn)?
+            } else {
+                diesel::update(auth::table.find(auth_id).filter(auth::co_auth_token.eq(&token_hash)))
+                    .set(auth::co_auth_token.eq(""))
+                    .execute(conn)?
+            };
+
+            if consumed > 0 {
+                if is_main {
+                    diesel::update(auth::table.find(auth_id).filter(auth::co_auth_token.eq(&token_hash)))
+                        .set(auth::co_auth_token.eq(""))
+                        .execute(conn)?;
+                } else {
+                    diesel::update(auth::table.find(auth_id).filter(auth::main_auth_token.eq(&token_hash)))
+                        .set(auth::main_auth_token.eq(""))
+                        .execute(conn)?;
+                }
+            }
+
+            Ok(consumed)
+        })?;
+
+        Ok(result)
+    }
+
     fn delete(
             &self,
             auth_id: Uuid
         ) -> Result<usize, RepositoryError> {
         let mut conn = self.pool.get()?;
+        self.delete_tx(&mut Transaction { conn: &mut conn }, auth_id)
+    }
+
+    fn delete_tx(
+            &self,
+            tx: &mut Transaction,
+            auth_id: Uuid
+        ) -> Result<usize, RepositoryError> {
         let result = diesel::delete(auth::table.find(auth_id))
-            .execute(&mut conn)?;
+            .execute(tx.conn)?;
+
+        Ok(result)
+    }
+
+    fn delete_stale_tx(
+            &self,
+            tx: &mut Transaction,
+            threshold: NaiveDateTime,
+        ) -> Result<usize, RepositoryError> {
+        let result = diesel::delete(auth::table)
+            .filter(auth::updated_at.lt(threshold))
+            .execute(tx.conn)?;
 
         Ok(result)
     }
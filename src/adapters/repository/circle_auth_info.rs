@@ -1,3 +1,4 @@
+use chrono::NaiveDateTime;
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 use uuid::Uuid;
@@ -5,7 +6,7 @@ use uuid::Uuid;
 use crate::infrastructure::schema::*;
 use crate::infrastructure::models::*;
 use crate::infrastructure::router::Pool;
-use super::RepositoryError;
+use super::{RepositoryError, Transaction};
 
 /// # circle_auth_info
 pub trait CircleAuthInfoRepository: Send + Sync {
@@ -35,11 +36,24 @@ pub trait CircleAuthInfoRepository: Send + Sync {
         auth_id: Uuid,
     ) -> Result<CircleAuthInfo, RepositoryError>;
 
+    /// 学籍番号を含む認証中のレコードのうち、最後に更新されたものを取得する
+    fn get_pending_by_student_id(
+        &self,
+        student_id: String,
+    ) -> Result<Option<(Auth, CircleAuthInfo)>, RepositoryError>;
+
     fn delete(
         &self,
         auth_id: Uuid,
     ) -> Result<usize, RepositoryError>;
 
+    /// threshold より前に更新されたauthに紐づくcircle_auth_infoを削除する
+    fn delete_stale_tx(
+        &self,
+        tx: &mut Transaction,
+        threshold: NaiveDateTime,
+    ) -> Result<usize, RepositoryError>;
+
     fn delete_all(
         &self
     ) -> Result<usize, RepositoryError>;
@@ -114,6 +128,22 @@ impl CircleAuthInfoRepository for CircleAuthInfoRepositorySqlImpl {
         Ok(result)
     }
 
+    fn get_pending_by_student_id(
+            &self,
+            student_id: String,
+        ) -> Result<Option<(Auth, CircleAuthInfo)>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = auth::table
+            .inner_join(circle_auth_info::table)
+            .filter(circle_auth_info::main_student_id.eq(student_id.clone()).or(circle_auth_info::co_student_id.eq(student_id)))
+            .filter(auth::phase.eq_any(["main_auth", "co_auth"]))
+            .order(auth::updated_at.desc())
+            .first::<(Auth, CircleAuthInfo)>(&mut conn)
+            .optional()?;
+
+        Ok(result)
+    }
+
     fn delete(
             &self,
             auth_id: Uuid,
@@ -125,6 +155,18 @@ impl CircleAuthInfoRepository for CircleAuthInfoRepositorySqlImpl {
         Ok(result)
     }
 
+    fn delete_stale_tx(
+            &self,
+            tx: &mut Transaction,
+            threshold: NaiveDateTime,
+        ) -> Result<usize, RepositoryError> {
+        let result = diesel::delete(circle_auth_info::table)
+            .filter(circle_auth_info::auth_id.eq_any(auth::table.filter(auth::updated_at.lt(threshold)).select(auth::auth_id)))
+            .execute(tx.conn)?;
+
+        Ok(result)
+    }
+
     fn delete_all(
             &self
         ) -> Result<usize, RepositoryError> {
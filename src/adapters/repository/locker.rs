@@ -1,10 +1,11 @@
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 
+use crate::domain::locker::{LockerInfo, LockerStatus};
 use crate::infrastructure::schema::*;
 use crate::infrastructure::models::*;
 use crate::infrastructure::router::Pool;
-use super::RepositoryError;
+use super::{RepositoryError, Transaction};
 
 /// # locker
 pub trait LockerRepository: Send + Sync {
@@ -12,29 +13,35 @@ pub trait LockerRepository: Send + Sync {
         &self,
         locker_id: String,
         location: String,
-        status: String,
+        status: LockerStatus,
     ) -> Result<Locker, RepositoryError>;
 
     fn get_all(
         &self,
     ) -> Result<Vec<Locker>, RepositoryError>;
 
+    /// トランザクション内でget_allを実行する
+    fn get_all_tx(
+        &self,
+        tx: &mut Transaction,
+    ) -> Result<Vec<Locker>, RepositoryError>;
+
     fn update_status(
         &self,
         floor: String,
-        prev_status: String,
-        new_status: String,
+        prev_status: LockerStatus,
+        new_status: LockerStatus,
     ) -> Result<usize, RepositoryError>;
 
     fn update_status_by_id(
         &self,
         locker_id: String,
-        status: String,
+        status: LockerStatus,
     ) -> Result<usize, RepositoryError>;
 
     fn update_all_status(
         &self,
-        status: String,
+        status: LockerStatus,
     ) -> Result<usize, RepositoryError>;
 
     fn get_by_id(
@@ -47,11 +54,60 @@ pub trait LockerRepository: Send + Sync {
         floor: String,
     ) -> Result<Vec<Locker>, RepositoryError>;
 
+    fn get_by_floor_and_year(
+        &self,
+        floor: String,
+        year: i32,
+    ) -> Result<Vec<Locker>, RepositoryError>;
+
     fn get_by_status(
         &self,
-        status: String,
+        status: LockerStatus,
     ) -> Result<Vec<Locker>, RepositoryError>;
 
+    /// 使用中かどうかはyearの割り当てから判定し、判定後の状態で絞り込む
+    fn search(
+        &self,
+        floor: String,
+        location: Option<String>,
+        status: Option<LockerStatus>,
+        year: i32,
+    ) -> Result<Vec<Locker>, RepositoryError>;
+
+    fn update(
+        &self,
+        locker_id: String,
+        location: Option<String>,
+        status: Option<LockerStatus>,
+    ) -> Result<Locker, RepositoryError>;
+
+    fn delete(
+        &self,
+        locker_id: String,
+    ) -> Result<usize, RepositoryError>;
+
+    /// トランザクション内でlocker_idsのロッカーを取得し、ロックする
+    fn get_for_update_tx(
+        &self,
+        tx: &mut Transaction,
+        locker_ids: Vec<String>,
+    ) -> Result<Vec<Locker>, RepositoryError>;
+
+    /// トランザクション内でlocker_idsのうち、yearsのいずれかの年度に有効な割り当てがあるロッカー番号を取得する
+    fn get_in_use_tx(
+        &self,
+        tx: &mut Transaction,
+        locker_ids: Vec<String>,
+        years: Vec<i32>,
+    ) -> Result<Vec<String>, RepositoryError>;
+
+    /// トランザクション内で全てのロッカーを登録または更新する
+    fn upsert_all_tx(
+        &self,
+        tx: &mut Transaction,
+        lockers: Vec<LockerInfo>,
+    ) -> Result<usize, RepositoryError>;
+
     fn delete_all(
         &self
     ) -> Result<usize, RepositoryError>;
@@ -72,7 +128,7 @@ impl LockerRepository for LockerRepositorySqlImpl {
         &self,
         locker_id: String,
         location: String,
-        status: String,
+        status: LockerStatus,
     ) -> Result<Locker, RepositoryError> {
         let new_locker = NewLocker {
             locker_id: &locker_id,
@@ -91,8 +147,15 @@ impl LockerRepository for LockerRepositorySqlImpl {
         &self,
     ) -> Result<Vec<Locker>, RepositoryError> {
         let mut conn = self.pool.get()?;
+        self.get_all_tx(&mut Transaction { conn: &mut conn })
+    }
+
+    fn get_all_tx(
+        &self,
+        tx: &mut Transaction,
+    ) -> Result<Vec<Locker>, RepositoryError> {
         let result = locker::table
-            .get_results::<Locker>(&mut conn)?;
+            .get_results::<Locker>(tx.conn)?;
 
         Ok(result)
     }
@@ -100,16 +163,15 @@ impl LockerRepository for LockerRepositorySqlImpl {
     fn update_status(
             &self,
             floor: String,
-            prev_status: String,
-            new_status: String,
+            prev_status: LockerStatus,
+            new_status: LockerStatus,
         ) -> Result<usize, RepositoryError> {
         let mut conn = self.pool.get()?;
         let floor_ex = format!("{}%", floor);
-        let status_ex = format!("{}%", prev_status);
         let result = diesel::update(
             locker::table.filter(
                 locker::locker_id.like(floor_ex)
-            ).filter(locker::status.like(status_ex))
+            ).filter(locker::status.eq(prev_status))
             ).set(locker::status.eq(new_status))
             .execute(&mut conn)?;
 
@@ -119,7 +181,7 @@ impl LockerRepository for LockerRepositorySqlImpl {
     fn update_status_by_id(
             &self,
             locker_id: String,
-            status: String,
+            status: LockerStatus,
         ) -> Result<usize, RepositoryError> {
         let mut conn = self.pool.get()?;
         let result = diesel::update(locker::table.find(locker_id))
@@ -131,7 +193,7 @@ impl LockerRepository for LockerRepositorySqlImpl {
 
     fn update_all_status(
             &self,
-            status: String,
+            status: LockerStatus,
         ) -> Result<usize, RepositoryError> {
         let mut conn = self.pool.get()?;
         let result = diesel::update(locker::table)
@@ -170,19 +232,176 @@ impl LockerRepository for LockerRepositorySqlImpl {
         Ok(result)
     }
 
+    /// 指定年度に有効な割り当てがあるロッカーの状態を使用中として返す
+    fn get_by_floor_and_year(
+            &self,
+            floor: String,
+            year: i32,
+        ) -> Result<Vec<Locker>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let floor_ex = format!("{}%", floor);
+        let lockers = locker::table
+            .filter(
+                locker::locker_id
+                .like(&floor_ex)
+            ).get_results::<Locker>(&mut conn)?;
+
+        let assigned = assignment_record::table
+            .filter(assignment_record::locker_id.like(&floor_ex))
+            .filter(assignment_record::year.eq(year))
+            .filter(assignment_record::deleted_at.is_null())
+            .select(assignment_record::locker_id)
+            .get_results::<String>(&mut conn)?;
+
+        let result = lockers.into_iter()
+            .map(|mut locker| {
+                if assigned.contains(&locker.locker_id) {
+                    locker.status = LockerStatus::Occupied;
+                }
+                locker
+            })
+            .collect();
+
+        Ok(result)
+    }
+
     fn get_by_status(
             &self,
-            status: String,
+            status: LockerStatus,
         ) -> Result<Vec<Locker>, RepositoryError> {
         let mut conn = self.pool.get()?;
         let result = locker::table
             .filter(
-                locker::status.like(status)
+                locker::status.eq(status)
             ).get_results::<Locker>(&mut conn)?;
 
         Ok(result)
     }
 
+    fn search(
+            &self,
+            floor: String,
+            location: Option<String>,
+            status: Option<LockerStatus>,
+            year: i32,
+        ) -> Result<Vec<Locker>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let floor_ex = format!("{}%", floor);
+        let mut query = locker::table
+            .filter(locker::locker_id.like(&floor_ex))
+            .into_boxed();
+
+        if let Some(location) = location {
+            query = query.filter(locker::location.eq(location));
+        }
+
+        let lockers = query
+            .order(locker::locker_id.asc())
+            .get_results::<Locker>(&mut conn)?;
+
+        let assigned = assignment_record::table
+            .filter(assignment_record::locker_id.like(&floor_ex))
+            .filter(assignment_record::year.eq(year))
+            .filter(assignment_record::deleted_at.is_null())
+            .select(assignment_record::locker_id)
+            .get_results::<String>(&mut conn)?;
+
+        let result = lockers.into_iter()
+            .map(|mut locker| {
+                if assigned.contains(&locker.locker_id) {
+                    locker.status = LockerStatus::Occupied;
+                }
+                locker
+            })
+            .filter(|locker| status.is_none_or(|status| locker.status == status))
+            .collect();
+
+        Ok(result)
+    }
+
+    fn update(
+            &self,
+            locker_id: String,
+            location: Option<String>,
+            status: Option<LockerStatus>,
+        ) -> Result<Locker, RepositoryError> {
+        let changeset = LockerChangeset {
+            location: location.as_ref(),
+            status: status.as_ref(),
+        };
+        let mut conn = self.pool.get()?;
+        let result = diesel::update(locker::table.find(locker_id))
+            .set(&changeset)
+            .get_result::<Locker>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn delete(
+            &self,
+            locker_id: String,
+        ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::delete(locker::table.find(locker_id))
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn get_for_update_tx(
+            &self,
+            tx: &mut Transaction,
+            locker_ids: Vec<String>,
+        ) -> Result<Vec<Locker>, RepositoryError> {
+        let result = locker::table
+            .filter(locker::locker_id.eq_any(locker_ids))
+            .order(locker::locker_id.asc())
+            .for_update()
+            .get_results::<Locker>(tx.conn)?;
+
+        Ok(result)
+    }
+
+    fn get_in_use_tx(
+            &self,
+            tx: &mut Transaction,
+            locker_ids: Vec<String>,
+            years: Vec<i32>,
+        ) -> Result<Vec<String>, RepositoryError> {
+        let result = assignment_record::table
+            .filter(assignment_record::locker_id.eq_any(locker_ids))
+            .filter(assignment_record::year.eq_any(years))
+            .filter(assignment_record::deleted_at.is_null())
+            .select(assignment_record::locker_id)
+            .distinct()
+            .get_results::<String>(tx.conn)?;
+
+        Ok(result)
+    }
+
+    fn upsert_all_tx(
+            &self,
+            tx: &mut Transaction,
+            lockers: Vec<LockerInfo>,
+        ) -> Result<usize, RepositoryError> {
+        let mut result = 0;
+        for row in &lockers {
+            let new_locker = NewLocker {
+                locker_id: &row.locker_id,
+                location: &row.location,
+                status: &row.status,
+            };
+            result += diesel::insert_into(locker::table)
+                .values(&new_locker)
+                .on_conflict(locker::locker_id)
+                .do_update()
+                .set((locker::location.eq(&row.location), locker::status.eq(row.status)))
+                .execute(tx.conn)?;
+        }
+
+        Ok(result)
+    }
+
     fn delete_all(
         &self
     ) -> Result<usize, RepositoryError> {
@@ -1,3 +1,4 @@
+use chrono::NaiveDateTime;
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 use uuid::Uuid;
@@ -5,7 +6,7 @@ use uuid::Uuid;
 use crate::infrastructure::schema::*;
 use crate::infrastructure::models::*;
 use crate::infrastructure::router::Pool;
-use super::RepositoryError;
+use super::{RepositoryError, Transaction};
 
 /// # locker_auth_info
 pub trait LockerAuthInfoRepository: Send + Sync {
@@ -20,16 +21,49 @@ pub trait LockerAuthInfoRepository: Send + Sync {
         co_given_name: String,
     ) -> Result<LockerAuthInfo, RepositoryError>;
 
+    /// トランザクション内でinsertを実行する
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        new_auth_info: &NewLockerAuthInfo,
+    ) -> Result<LockerAuthInfo, RepositoryError>;
+
     fn get_by_id(
         &self,
         auth_id: Uuid,
     ) -> Result<LockerAuthInfo, RepositoryError>;
 
+    /// 学籍番号を含む認証中のレコードのうち、最後に更新されたものを取得する
+    fn get_pending_by_student_id(
+        &self,
+        student_id: String,
+    ) -> Result<Option<(Auth, LockerAuthInfo)>, RepositoryError>;
+
+    /// 申請者の学籍番号が一致する登録取り消し中のレコードを取得する
+    fn get_cancel_by_student_id(
+        &self,
+        student_id: String,
+    ) -> Result<Option<(Auth, LockerAuthInfo)>, RepositoryError>;
+
     fn delete(
         &self,
         auth_id: Uuid,
     ) -> Result<usize, RepositoryError>;
 
+    /// トランザクション内でdeleteを実行する
+    fn delete_tx(
+        &self,
+        tx: &mut Transaction,
+        auth_id: Uuid,
+    ) -> Result<usize, RepositoryError>;
+
+    /// threshold より前に更新されたauthに紐づくlocker_auth_infoを削除する
+    fn delete_stale_tx(
+        &self,
+        tx: &mut Transaction,
+        threshold: NaiveDateTime,
+    ) -> Result<usize, RepositoryError>;
+
     fn delete_all(
         &self
     ) -> Result<usize, RepositoryError>;
@@ -66,9 +100,17 @@ impl LockerAuthInfoRepository for LockerAuthInfoRepositorySqlImpl {
             co_given_name: &co_given_name,
         };
         let mut conn = self.pool.get()?;
+        self.insert_tx(&mut Transaction { conn: &mut conn }, &new_auth_info)
+    }
+
+    fn insert_tx(
+            &self,
+            tx: &mut Transaction,
+            new_auth_info: &NewLockerAuthInfo,
+    ) -> Result<LockerAuthInfo, RepositoryError> {
         let result = diesel::insert_into(locker_auth_info::table)
-            .values(&new_auth_info)
-            .get_result::<LockerAuthInfo>(&mut conn)?;
+            .values(new_auth_info)
+            .get_result::<LockerAuthInfo>(tx.conn)?;
 
         Ok(result)
     }
@@ -84,13 +126,65 @@ impl LockerAuthInfoRepository for LockerAuthInfoRepositorySqlImpl {
         Ok(result)
     }
 
+    fn get_pending_by_student_id(
+            &self,
+            student_id: String,
+        ) -> Result<Option<(Auth, LockerAuthInfo)>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = auth::table
+            .inner_join(locker_auth_info::table)
+            .filter(locker_auth_info::main_student_id.eq(student_id.clone()).or(locker_auth_info::co_student_id.eq(student_id)))
+            .filter(auth::phase.eq_any(["main_auth", "co_auth", "auth_check"]))
+            .order(auth::updated_at.desc())
+            .first::<(Auth, LockerAuthInfo)>(&mut conn)
+            .optional()?;
+
+        Ok(result)
+    }
+
+    fn get_cancel_by_student_id(
+            &self,
+            student_id: String,
+        ) -> Result<Option<(Auth, LockerAuthInfo)>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = auth::table
+            .inner_join(locker_auth_info::table)
+            .filter(locker_auth_info::main_student_id.eq(student_id))
+            .filter(auth::phase.eq("cancel"))
+            .order(auth::updated_at.desc())
+            .first::<(Auth, LockerAuthInfo)>(&mut conn)
+            .optional()?;
+
+        Ok(result)
+    }
+
     fn delete(
             &self,
             auth_id: Uuid,
         ) -> Result<usize, RepositoryError> {
         let mut conn = self.pool.get()?;
+        self.delete_tx(&mut Transaction { conn: &mut conn }, auth_id)
+    }
+
+    fn delete_tx(
+            &self,
+            tx: &mut Transaction,
+            auth_id: Uuid,
+        ) -> Result<usize, RepositoryError> {
         let result = diesel::delete(locker_auth_info::table.find(auth_id))
-            .execute(&mut conn)?;
+            .execute(tx.conn)?;
+
+        Ok(result)
+    }
+
+    fn delete_stale_tx(
+            &self,
+            tx: &mut Transaction,
+            threshold: NaiveDateTime,
+        ) -> Result<usize, RepositoryError> {
+        let result = diesel::delete(locker_auth_info::table)
+            .filter(locker_auth_info::auth_id.eq_any(auth::table.filter(auth::updated_at.lt(threshold)).select(auth::auth_id)))
+            .execute(tx.conn)?;
 
         Ok(result)
     }
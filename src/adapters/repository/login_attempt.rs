@@ -0,0 +1,254 @@
+use chrono::NaiveDateTime;
+use diesel::pg::{Pg, PgConnection};
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Text};
+use uuid::Uuid;
+
+use crate::domain::admin::LoginResult;
+use crate::infrastructure::schema::*;
+use crate::infrastructure::models::*;
+use crate::infrastructure::router::Pool;
+use super::{RepositoryError, Transaction};
+
+/// アドバイザリロックのキーの種類、ユーザーネームとIPアドレスのハッシュ値が衝突しないように分ける
+const USERNAME_LOCK_CLASS: i32 = 1;
+const IP_ADDRESS_LOCK_CLASS: i32 = 2;
+
+/// ### LoginFailures
+/// 最後にログインに成功した後の連続した失敗回数と最後に失敗した時刻
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LoginFailures {
+    pub count: i64,
+    pub last_failed_at: Option<NaiveDateTime>,
+}
+
+/// ### LoginAttemptFilter
+/// 失敗回数を数えるログインの試行の条件
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoginAttemptFilter {
+    Username(String),
+    IpAddress(String),
+}
+
+/// # login_attempt
+pub trait LoginAttemptRepository: Send + Sync {
+    fn insert(
+        &self,
+        username: String,
+        ip_address: Option<String>,
+        result: LoginResult,
+    ) -> Result<LoginAttempt, RepositoryError>;
+
+    /// トランザクション内でinsertを実行する
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        username: String,
+        ip_address: Option<String>,
+        result: LoginResult,
+    ) -> Result<LoginAttempt, RepositoryError>;
+
+    fn update_result(
+        &self,
+        attempt_id: Uuid,
+        result: LoginResult,
+    ) -> Result<usize, RepositoryError>;
+
+    /// トランザクション内でユーザーネーム、IPアドレスごとのアドバイザリロックを取得する、ロックはトランザクションの終了時に解放される
+    fn lock_tx(
+        &self,
+        tx: &mut Transaction,
+        username: String,
+        ip_address: Option<String>,
+    ) -> Result<(), RepositoryError>;
+
+    /// since以降、最後にログインに成功した後にパスワードの誤りで失敗した回数をfilterの条件ごとに数える
+    fn count_failures(
+        &self,
+        filter: LoginAttemptFilter,
+        since: NaiveDateTime,
+    ) -> Result<LoginFailures, RepositoryError>;
+
+    /// トランザクション内でcount_failuresを実行する
+    fn count_failures_tx(
+        &self,
+        tx: &mut Transaction,
+        filter: LoginAttemptFilter,
+        since: NaiveDateTime,
+    ) -> Result<LoginFailures, RepositoryError>;
+
+    /// resultsのいずれかの結果のログインの試行をユーザーネーム、IPアドレスで検索する、指定しない条件は絞り込まない
+    fn search(
+        &self,
+        username: Option<String>,
+        ip_address: Option<String>,
+        results: Vec<LoginResult>,
+        limit: i64,
+    ) -> Result<Vec<LoginAttempt>, RepositoryError>;
+
+    fn delete_all(
+        &self
+    ) -> Result<usize, RepositoryError>;
+}
+
+pub struct LoginAttemptRepositorySqlImpl {
+    pool: Pool<PgConnection>
+}
+
+impl LoginAttemptRepositorySqlImpl {
+    pub fn new(pool: Pool<PgConnection>) -> Self {
+        LoginAttemptRepositorySqlImpl { pool }
+    }
+}
+
+impl LoginAttemptRepository for LoginAttemptRepositorySqlImpl {
+    fn insert(
+        &self,
+        username: String,
+        ip_address: Option<String>,
+        result: LoginResult,
+    ) -> Result<LoginAttempt, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        self.insert_tx(&mut Transaction { conn: &mut conn }, username, ip_address, result)
+    }
+
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        username: String,
+        ip_address: Option<String>,
+        result: LoginResult,
+    ) -> Result<LoginAttempt, RepositoryError> {
+        let new_attempt = NewLoginAttempt {
+            username: &username,
+            ip_address: ip_address.as_ref(),
+            result,
+        };
+        let result = diesel::insert_into(login_attempt::table)
+            .values(&new_attempt)
+            .get_result::<LoginAttempt>(tx.conn)?;
+
+        Ok(result)
+    }
+
+    fn update_result(
+        &self,
+        attempt_id: Uuid,
+        result: LoginResult,
+    ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::update(login_attempt::table.find(attempt_id))
+            .set(login_attempt::result.eq(result))
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn lock_tx(
+        &self,
+        tx: &mut Transaction,
+        username: String,
+        ip_address: Option<String>,
+    ) -> Result<(), RepositoryError> {
+        // 常にユーザーネーム、IPアドレスの順にロックし、デッドロックしないようにする
+        diesel::sql_query("SELECT pg_advisory_xact_lock($1, hashtext($2))")
+            .bind::<Integer, _>(USERNAME_LOCK_CLASS)
+            .bind::<Text, _>(username)
+            .execute(tx.conn)?;
+        if let Some(ip_address) = ip_address {
+            diesel::sql_query("SELECT pg_advisory_xact_lock($1, hashtext($2))")
+                .bind::<Integer, _>(IP_ADDRESS_LOCK_CLASS)
+                .bind::<Text, _>(ip_address)
+                .execute(tx.conn)?;
+        }
+
+        Ok(())
+    }
+
+    fn count_failures(
+        &self,
+        filter: LoginAttemptFilter,
+        since: NaiveDateTime,
+    ) -> Result<LoginFailures, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        self.count_failures_tx(&mut Transaction { conn: &mut conn }, filter, since)
+    }
+
+    fn count_failures_tx(
+        &self,
+        tx: &mut Transaction,
+        filter: LoginAttemptFilter,
+        since: NaiveDateTime,
+    ) -> Result<LoginFailures, RepositoryError> {
+        let last_success = filtered(&filter)
+            .filter(login_attempt::result.eq(LoginResult::Success))
+            .select(login_attempt::attempted_at)
+            .order(login_attempt::attempted_at.desc())
+            .first::<NaiveDateTime>(tx.conn)
+            .optional()?;
+
+        let since = last_success.map_or(since, |last_success| last_success.max(since));
+        let failures = || filtered(&filter)
+            .filter(login_attempt::result.eq(LoginResult::InvalidCredentials))
+            .filter(login_attempt::attempted_at.gt(since));
+        let count = failures()
+            .count()
+            .get_result::<i64>(tx.conn)?;
+        let last_failed_at = failures()
+            .select(login_attempt::attempted_at)
+            .order(login_attempt::attempted_at.desc())
+            .first::<NaiveDateTime>(tx.conn)
+            .optional()?;
+
+        Ok(LoginFailures { count, last_failed_at })
+    }
+
+    fn search(
+        &self,
+        username: Option<String>,
+        ip_address: Option<String>,
+        results: Vec<LoginResult>,
+        limit: i64,
+    ) -> Result<Vec<LoginAttempt>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let mut query = login_attempt::table
+            .filter(login_attempt::result.eq_any(results))
+            .into_boxed();
+
+        if let Some(username) = username {
+            query = query.filter(login_attempt::username.eq(username));
+        }
+        if let Some(ip_address) = ip_address {
+            query = query.filter(login_attempt::ip_address.eq(ip_address));
+        }
+
+        let result = query
+            .order(login_attempt::attempted_at.desc())
+            .limit(limit)
+            .load::<LoginAttempt>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn delete_all(
+        &self
+    ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::delete(login_attempt::table)
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+}
+
+// filterの条件で絞り込んだログインの試行
+fn filtered(filter: &LoginAttemptFilter) -> login_attempt::BoxedQuery<'_, Pg> {
+    match filter {
+        LoginAttemptFilter::Username(username) => login_attempt::table
+            .filter(login_attempt::username.eq(username))
+            .into_boxed(),
+        LoginAttemptFilter::IpAddress(ip_address) => login_attempt::table
+            .filter(login_attempt::ip_address.eq(ip_address))
+            .into_boxed(),
+    }
+}
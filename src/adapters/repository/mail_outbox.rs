@@ -0,0 +1,308 @@
+use chrono::NaiveDateTime;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::domain::mail::{MailAudit, MailStatus};
+use crate::infrastructure::schema::*;
+use crate::infrastructure::models::*;
+use crate::infrastructure::router::Pool;
+use super::RepositoryError;
+
+/// # mail_outbox
+pub trait MailOutboxRepository: Send + Sync {
+    fn insert(
+        &self,
+        to_address: String,
+        subject: String,
+        body_text: String,
+        body_html: Option<String>,
+        audit: MailAudit,
+    ) -> Result<MailOutbox, RepositoryError>;
+
+    /// 送信時刻を過ぎた送信待ちメールを取得し、lease_untilまで他のワーカーから取得されないようにする
+    fn claim_due(
+        &self,
+        now: NaiveDateTime,
+        lease_until: NaiveDateTime,
+        limit: i64,
+    ) -> Result<Vec<MailOutbox>, RepositoryError>;
+
+    fn get_by_status(
+        &self,
+        statuses: Vec<MailStatus>,
+        limit: i64,
+    ) -> Result<Vec<MailOutbox>, RepositoryError>;
+
+    /// 学籍番号、メールアドレス(前方一致)で送信履歴を検索する、指定しない条件は絞り込まない
+    fn search(
+        &self,
+        student_id: Option<String>,
+        email: Option<String>,
+        limit: i64,
+    ) -> Result<Vec<MailOutbox>, RepositoryError>;
+
+    /// 送信済みにする、本文はtokenを含むため削除する
+    ///
+    /// claim_dueで取得したlease_untilのまま送信待ちのメールのみ更新する、leaseが切れて他のワーカーが取得した場合は0を返す
+    fn mark_sent(
+        &self,
+        mail_id: Uuid,
+        lease_until: NaiveDateTime,
+        attempts: i32,
+    ) -> Result<usize, RepositoryError>;
+
+    /// 送信に失敗した結果を保存する、next_attempt_atがNoneの場合は再送しない
+    ///
+    /// mark_sentと同様に、leaseが切れている場合は0を返す
+    fn mark_failed(
+        &self,
+        mail_id: Uuid,
+        lease_until: NaiveDateTime,
+        attempts: i32,
+        next_attempt_at: Option<NaiveDateTime>,
+        last_error: String,
+    ) -> Result<usize, RepositoryError>;
+
+    /// 送信済みでなく、本文が残っているメールを送信待ちに戻し、再送回数をリセットする
+    fn retry(
+        &self,
+        mail_id: Uuid,
+    ) -> Result<usize, RepositoryError>;
+
+    fn retry_failed(
+        &self,
+    ) -> Result<usize, RepositoryError>;
+
+    /// threshold より前に作成された送信済み、送信に失敗したメールの本文を削除する、送信履歴は残す
+    fn clear_bodies_before(
+        &self,
+        threshold: NaiveDateTime,
+    ) -> Result<usize, RepositoryError>;
+
+    fn delete_all(
+        &self
+    ) -> Result<usize, RepositoryError>;
+}
+
+pub struct MailOutboxRepositorySqlImpl {
+    pool: Pool<PgConnection>
+}
+
+impl MailOutboxRepositorySqlImpl {
+    pub fn new(pool: Pool<PgConnection>) -> Self {
+        MailOutboxRepositorySqlImpl { pool }
+    }
+}
+
+impl MailOutboxRepository for MailOutboxRepositorySqlImpl {
+    fn insert(
+            &self,
+            to_address: String,
+            subject: String,
+            body_text: String,
+            body_html: Option<String>,
+            audit: MailAudit,
+        ) -> Result<MailOutbox, RepositoryError> {
+        let new_mail = NewMailOutbox{
+            to_address: &to_address,
+            subject: &subject,
+            body_text: &body_text,
+            body_html: body_html.as_ref(),
+            template: &audit.template,
+            student_id: audit.student_id.as_ref(),
+            auth_id: audit.auth_id.as_ref(),
+            organization_id: audit.organization_id.as_ref(),
+        };
+        let mut conn = self.pool.get()?;
+        let result = diesel::insert_into(mail_outbox::table)
+            .values(&new_mail)
+            .get_result::<MailOutbox>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn claim_due(
+            &self,
+            now: NaiveDateTime,
+            lease_until: NaiveDateTime,
+            limit: i64,
+        ) -> Result<Vec<MailOutbox>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let mail_ids = mail_outbox::table
+                .filter(mail_outbox::status.eq(MailStatus::Pending))
+                .filter(mail_outbox::next_attempt_at.le(now))
+                .order(mail_outbox::next_attempt_at.asc())
+                .limit(limit)
+                .select(mail_outbox::mail_id)
+                .for_update()
+                .skip_locked()
+                .load::<Uuid>(conn)?;
+
+            diesel::update(mail_outbox::table.filter(mail_outbox::mail_id.eq_any(&mail_ids)))
+                .set(mail_outbox::next_attempt_at.eq(lease_until))
+                .get_results::<MailOutbox>(conn)
+        })?;
+
+        Ok(result)
+    }
+
+    fn get_by_status(
+            &self,
+            statuses: Vec<MailStatus>,
+            limit: i64,
+        ) -> Result<Vec<MailOutbox>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = mail_outbox::table
+            .filter(mail_outbox::status.eq_any(statuses))
+            .order(mail_outbox::created_at.desc())
+            .limit(limit)
+            .get_results::<MailOutbox>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn search(
+            &self,
+            student_id: Option<String>,
+            email: Option<String>,
+            limit: i64,
+        ) -> Result<Vec<MailOutbox>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let mut query = mail_outbox::table.into_boxed();
+        if let Some(student_id) = student_id {
+            query = query.filter(mail_outbox::student_id.eq(student_id));
+        }
+        if let Some(email) = email {
+            let email_ex = format!("{}%", email);
+            query = query.filter(mail_outbox::to_address.ilike(email_ex));
+        }
+        let result = query
+            .order(mail_outbox::created_at.desc())
+            .limit(limit)
+            .get_results::<MailOutbox>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn mark_sent(
+            &self,
+            mail_id: Uuid,
+            lease_until: NaiveDateTime,
+            attempts: i32,
+        ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::update(mail_outbox::table
+            .filter(mail_outbox::mail_id.eq(mail_id))
+            .filter(mail_outbox::status.eq(MailStatus::Pending))
+            .filter(mail_outbox::next_attempt_at.eq(lease_until)))
+            .set((
+                mail_outbox::status.eq(MailStatus::Sent),
+                mail_outbox::attempts.eq(attempts),
+                mail_outbox::sent_at.eq(diesel::dsl::now),
+                mail_outbox::body_text.eq(""),
+                mail_outbox::body_html.eq(None::<String>),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn mark_failed(
+            &self,
+            mail_id: Uuid,
+            lease_until: NaiveDateTime,
+            attempts: i32,
+            next_attempt_at: Option<NaiveDateTime>,
+            last_error: String,
+        ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let leased = mail_outbox::table
+            .filter(mail_outbox::mail_id.eq(mail_id))
+            .filter(mail_outbox::status.eq(MailStatus::Pending))
+            .filter(mail_outbox::next_attempt_at.eq(lease_until));
+        let result = match next_attempt_at {
+            Some(next_attempt_at) => diesel::update(leased)
+                .set((
+                    mail_outbox::attempts.eq(attempts),
+                    mail_outbox::next_attempt_at.eq(next_attempt_at),
+                    mail_outbox::last_error.eq(last_error),
+                ))
+                .execute(&mut conn)?,
+            None => diesel::update(leased)
+                .set((
+                    mail_outbox::status.eq(MailStatus::Failed),
+                    mail_outbox::attempts.eq(attempts),
+                    mail_outbox::last_error.eq(last_error),
+                ))
+                .execute(&mut conn)?,
+        };
+
+        Ok(result)
+    }
+
+    fn retry(
+            &self,
+            mail_id: Uuid,
+        ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::update(mail_outbox::table
+            .filter(mail_outbox::mail_id.eq(mail_id))
+            .filter(mail_outbox::status.ne(MailStatus::Sent))
+            .filter(mail_outbox::body_text.ne("")))
+            .set((
+                mail_outbox::status.eq(MailStatus::Pending),
+                mail_outbox::attempts.eq(0),
+                mail_outbox::next_attempt_at.eq(diesel::dsl::now),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn retry_failed(
+            &self,
+        ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::update(mail_outbox::table
+            .filter(mail_outbox::status.eq(MailStatus::Failed))
+            .filter(mail_outbox::body_text.ne("")))
+            .set((
+                mail_outbox::status.eq(MailStatus::Pending),
+                mail_outbox::attempts.eq(0),
+                mail_outbox::next_attempt_at.eq(diesel::dsl::now),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn clear_bodies_before(
+            &self,
+            threshold: NaiveDateTime,
+        ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::update(mail_outbox::table
+            .filter(mail_outbox::status.ne(MailStatus::Pending))
+            .filter(mail_outbox::created_at.lt(threshold))
+            .filter(mail_outbox::body_text.ne("").or(mail_outbox::body_html.is_not_null())))
+            .set((
+                mail_outbox::body_text.eq(""),
+                mail_outbox::body_html.eq(None::<String>),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn delete_all(
+        &self
+    ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::delete(mail_outbox::table)
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,135 @@
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+
+use crate::infrastructure::schema::*;
+use crate::infrastructure::models::*;
+use crate::infrastructure::router::Pool;
+use super::RepositoryError;
+
+/// # mail_template
+pub trait MailTemplateRepository: Send + Sync {
+    fn upsert(
+        &self,
+        name: String,
+        locale: String,
+        subject: String,
+        body_text: String,
+        body_html: Option<String>,
+    ) -> Result<MailTemplate, RepositoryError>;
+
+    fn get_all(
+        &self,
+        locale: String,
+    ) -> Result<Vec<MailTemplate>, RepositoryError>;
+
+    fn get_by_name(
+        &self,
+        name: String,
+        locale: String,
+    ) -> Result<Option<MailTemplate>, RepositoryError>;
+
+    fn delete(
+        &self,
+        name: String,
+        locale: String,
+    ) -> Result<usize, RepositoryError>;
+
+    fn delete_all(
+        &self
+    ) -> Result<usize, RepositoryError>;
+}
+
+pub struct MailTemplateRepositorySqlImpl {
+    pool: Pool<PgConnection>
+}
+
+impl MailTemplateRepositorySqlImpl {
+    pub fn new(pool: Pool<PgConnection>) -> Self {
+        MailTemplateRepositorySqlImpl { pool }
+    }
+}
+
+impl MailTemplateRepository for MailTemplateRepositorySqlImpl {
+    fn upsert(
+            &self,
+            name: String,
+            locale: String,
+            subject: String,
+            body_text: String,
+            body_html: Option<String>,
+        ) -> Result<MailTemplate, RepositoryError> {
+        let new_mail_template = NewMailTemplate{
+            name: &name,
+            locale: &locale,
+            subject: &subject,
+            body_text: &body_text,
+            body_html: body_html.as_ref(),
+        };
+        let mut conn = self.pool.get()?;
+        let result = diesel::insert_into(mail_template::table)
+            .values(&new_mail_template)
+            .on_conflict((mail_template::name, mail_template::locale))
+            .do_update()
+            .set((
+                mail_template::subject.eq(&subject),
+                mail_template::body_text.eq(&body_text),
+                mail_template::body_html.eq(&body_html),
+                mail_template::updated_at.eq(diesel::dsl::now),
+            ))
+            .get_result::<MailTemplate>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn get_all(
+            &self,
+            locale: String,
+        ) -> Result<Vec<MailTemplate>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = mail_template::table
+            .filter(mail_template::locale.eq(locale))
+            .order(mail_template::name.asc())
+            .get_results::<MailTemplate>(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn get_by_name(
+            &self,
+            name: String,
+            locale: String,
+        ) -> Result<Option<MailTemplate>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = mail_template::table
+            .filter(mail_template::name.eq(name))
+            .filter(mail_template::locale.eq(locale))
+            .get_result::<MailTemplate>(&mut conn)
+            .optional()?;
+
+        Ok(result)
+    }
+
+    fn delete(
+            &self,
+            name: String,
+            locale: String,
+        ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::delete(mail_template::table
+            .filter(mail_template::name.eq(name))
+            .filter(mail_template::locale.eq(locale)))
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+
+    fn delete_all(
+        &self
+    ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::delete(mail_template::table)
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
+}
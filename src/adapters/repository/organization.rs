@@ -4,7 +4,7 @@ use diesel::prelude::*;
 use crate::infrastructure::schema::*;
 use crate::infrastructure::models::*;
 use crate::infrastructure::router::Pool;
-use super::RepositoryError;
+use super::{RepositoryError, Transaction};
 
 /// # organization
 pub trait OrganizationRepository: Send + Sync {
@@ -15,6 +15,15 @@ pub trait OrganizationRepository: Send + Sync {
         organization_email: String,
     ) -> Result<Organization, RepositoryError>;
 
+    /// トランザクション内でinsertを実行する
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        organization_name: String,
+        organization_ruby: String,
+        organization_email: String,
+    ) -> Result<Organization, RepositoryError>;
+
     fn get_all(
         &self,
     ) -> Result<Vec<Organization>, RepositoryError>;
@@ -29,6 +38,14 @@ pub trait OrganizationRepository: Send + Sync {
         organization_id: i32,
         organization_email: String,
     ) -> Result<Organization, RepositoryError>;
+
+    /// トランザクション内でupdate_email_by_idを実行する
+    fn update_email_by_id_tx(
+        &self,
+        tx: &mut Transaction,
+        organization_id: i32,
+        organization_email: String,
+    ) -> Result<Organization, RepositoryError>;
 }
 
 pub struct OrganizationRepositorySqlImpl {
@@ -48,15 +65,25 @@ impl OrganizationRepository for OrganizationRepositorySqlImpl {
             organization_ruby: String,
             organization_email: String,
         ) -> Result<Organization, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        self.insert_tx(&mut Transaction { conn: &mut conn }, organization_name, organization_ruby, organization_email)
+    }
+
+    fn insert_tx(
+            &self,
+            tx: &mut Transaction,
+            organization_name: String,
+            organization_ruby: String,
+            organization_email: String,
+        ) -> Result<Organization, RepositoryError> {
         let new_organization = NewOrganization{
             organization_name: &organization_name,
             organization_ruby: &organization_ruby,
             organization_email: &organization_email,
         };
-        let mut conn = self.pool.get()?;
         let result = diesel::insert_into(organization::table)
             .values(new_organization)
-            .get_result::<Organization>(&mut conn)?;
+            .get_result::<Organization>(tx.conn)?;
 
         Ok(result)
     }
@@ -88,10 +115,19 @@ impl OrganizationRepository for OrganizationRepositorySqlImpl {
             organization_email: String,
         ) -> Result<Organization, RepositoryError> {
         let mut conn = self.pool.get()?;
+        self.update_email_by_id_tx(&mut Transaction { conn: &mut conn }, organization_id, organization_email)
+    }
+
+    fn update_email_by_id_tx(
+            &self,
+            tx: &mut Transaction,
+            organization_id: i32,
+            organization_email: String,
+        ) -> Result<Organization, RepositoryError> {
         let result = diesel::update(organization::table)
             .filter(organization::organization_id.eq(organization_id))
             .set((organization::organization_email.eq(organization_email), organization::updated_at.eq(diesel::dsl::now)))
-            .get_result::<Organization>(&mut conn)?;
+            .get_result::<Organization>(tx.conn)?;
 
         Ok(result)
     }
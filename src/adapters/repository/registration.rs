@@ -1,10 +1,11 @@
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 
+use crate::domain::circle::{AcceptanceStatus, AuthenticationStatus, FormConfirmationStatus, RegistrationCompleteStatus};
 use crate::infrastructure::schema::*;
 use crate::infrastructure::models::*;
 use crate::infrastructure::router::Pool;
-use super::RepositoryError;
+use super::{RepositoryError, Transaction};
 
 /// # registration
 pub trait RegistrationRepository: Send + Sync {
@@ -14,15 +15,22 @@ pub trait RegistrationRepository: Send + Sync {
         year: i32,
         main_student_id: String,
         co_student_id: String,
-        status_acceptance: String,
-        status_authentication: String,
-        status_form_confirmation: String,
-        status_registration_complete: String,
+        status_acceptance: AcceptanceStatus,
+        status_authentication: AuthenticationStatus,
+        status_form_confirmation: FormConfirmationStatus,
+        status_registration_complete: RegistrationCompleteStatus,
         b_url: String,
         c_url: String,
         d_url: String,
     ) -> Result<Registration, RepositoryError>;
 
+    /// トランザクション内でinsertを実行する
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        new_registration: &NewRegistration,
+    ) -> Result<Registration, RepositoryError>;
+
     fn update_student_by_id (
         &self,
         organization_id: i32,
@@ -30,13 +38,22 @@ pub trait RegistrationRepository: Send + Sync {
         co_student_id: String,
     ) -> Result<Registration, RepositoryError>;
 
+    /// トランザクション内でupdate_student_by_idを実行する
+    fn update_student_by_id_tx(
+        &self,
+        tx: &mut Transaction,
+        organization_id: i32,
+        main_student_id: String,
+        co_student_id: String,
+    ) -> Result<Registration, RepositoryError>;
+
     fn update_status_by_id (
         &self,
         organization_id: i32,
-        status_acceptance: String,
-        status_authentication: String,
-        status_form_confirmation: String,
-        status_registration_complete: String,
+        status_acceptance: AcceptanceStatus,
+        status_authentication: AuthenticationStatus,
+        status_form_confirmation: FormConfirmationStatus,
+        status_registration_complete: RegistrationCompleteStatus,
     ) -> Result<Registration, RepositoryError>;
 
     fn get_all (
@@ -63,15 +80,14 @@ impl RegistrationRepository for RegistrationRepositorySqlImpl {
             year: i32,
             main_student_id: String,
             co_student_id: String,
-            status_acceptance: String,
-            status_authentication: String,
-            status_form_confirmation: String,
-            status_registration_complete: String,
+            status_acceptance: AcceptanceStatus,
+            status_authentication: AuthenticationStatus,
+            status_form_confirmation: FormConfirmationStatus,
+            status_registration_complete: RegistrationCompleteStatus,
             b_doc: String,
             c_doc: String,
             d_doc: String,
         ) -> Result<Registration, RepositoryError> {
-        let mut conn = self.pool.get()?;
         let new_registration = NewRegistration{
             organization_id: &organization_id,
             year: &year,
@@ -85,9 +101,18 @@ impl RegistrationRepository for RegistrationRepositorySqlImpl {
             c_doc: &c_doc,
             d_doc: &d_doc,
         };
+        let mut conn = self.pool.get()?;
+        self.insert_tx(&mut Transaction { conn: &mut conn }, &new_registration)
+    }
+
+    fn insert_tx(
+            &self,
+            tx: &mut Transaction,
+            new_registration: &NewRegistration,
+        ) -> Result<Registration, RepositoryError> {
         let result = diesel::insert_into(registration::table)
             .values(new_registration)
-            .get_result::<Registration>(&mut conn)?;
+            .get_result::<Registration>(tx.conn)?;
 
         Ok(result)
     }
@@ -99,10 +124,20 @@ impl RegistrationRepository for RegistrationRepositorySqlImpl {
             co_student_id: String,
         ) -> Result<Registration, RepositoryError> {
         let mut conn = self.pool.get()?;
+        self.update_student_by_id_tx(&mut Transaction { conn: &mut conn }, organization_id, main_student_id, co_student_id)
+    }
+
+    fn update_student_by_id_tx (
+            &self,
+            tx: &mut Transaction,
+            organization_id: i32,
+            main_student_id: String,
+            co_student_id: String,
+        ) -> Result<Registration, RepositoryError> {
         let result = diesel::update(registration::table)
             .filter(registration::organization_id.eq(organization_id))
             .set((registration::main_student_id.eq(main_student_id), registration::co_student_id.eq(co_student_id), registration::updated_at.eq(diesel::dsl::now)))
-            .get_result::<Registration>(&mut conn)?;
+            .get_result::<Registration>(tx.conn)?;
 
         Ok(result)
     }
@@ -110,10 +145,10 @@ impl RegistrationRepository for RegistrationRepositorySqlImpl {
     fn update_status_by_id (
             &self,
             organization_id: i32,
-            status_acceptance: String,
-            status_authentication: String,
-            status_form_confirmation: String,
-            status_registration_complete: String,
+            status_acceptance: AcceptanceStatus,
+            status_authentication: AuthenticationStatus,
+            status_form_confirmation: FormConfirmationStatus,
+            status_registration_complete: RegistrationCompleteStatus,
         ) -> Result<Registration, RepositoryError> {
         let mut conn = self.pool.get()?;
         let result = diesel::update(registration::table)
@@ -4,7 +4,7 @@ use diesel::prelude::*;
 use crate::infrastructure::schema::*;
 use crate::infrastructure::models::*;
 use crate::infrastructure::router::Pool;
-use super::RepositoryError;
+use super::{RepositoryError, Transaction};
 
 /// # representatives
 pub trait RepresentativesRepository: Send + Sync {
@@ -17,6 +17,17 @@ pub trait RepresentativesRepository: Send + Sync {
         phone: String,
     ) -> Result<Representatives, RepositoryError>;
 
+    /// トランザクション内でinsertを実行する
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        student_id: String,
+        family_name: String,
+        given_name: String,
+        email: String,
+        phone: String,
+    ) -> Result<Representatives, RepositoryError>;
+
     fn get_all(
         &self,
     ) -> Result<Vec<Representatives>, RepositoryError>;
@@ -46,6 +57,19 @@ impl RepresentativesRepository for RepresentativesRepositorySqlImpl {
             email: String,
             phone: String,
         ) -> Result<Representatives, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        self.insert_tx(&mut Transaction { conn: &mut conn }, student_id, family_name, given_name, email, phone)
+    }
+
+    fn insert_tx(
+            &self,
+            tx: &mut Transaction,
+            student_id: String,
+            family_name: String,
+            given_name: String,
+            email: String,
+            phone: String,
+        ) -> Result<Representatives, RepositoryError> {
         let new_representative = NewRepresentatives{
             student_id: &student_id,
             family_name: &family_name,
@@ -53,13 +77,12 @@ impl RepresentativesRepository for RepresentativesRepositorySqlImpl {
             email: &email.clone(),
             phone: &phone.clone(),
         };
-        let mut conn = self.pool.get()?;
         let result = diesel::insert_into(representatives::table)
             .values(new_representative)
             .on_conflict(representatives::student_id)
             .do_update()
             .set((representatives::updated_at.eq(diesel::dsl::now), representatives::email.eq(email), representatives::phone.eq(phone)))
-            .get_result::<Representatives>(&mut conn)?;
+            .get_result::<Representatives>(tx.conn)?;
 
         Ok(result)
     }
@@ -4,7 +4,7 @@ use diesel::prelude::*;
 use crate::infrastructure::schema::*;
 use crate::infrastructure::models::*;
 use crate::infrastructure::router::Pool;
-use super::RepositoryError;
+use super::{RepositoryError, Transaction};
 
 
 /// # student
@@ -16,10 +16,25 @@ pub trait StudentRepository: Send + Sync {
         given_name: String,
     ) -> Result<Student, RepositoryError>;
 
+    /// トランザクション内でinsertを実行する
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        student_id: String,
+        family_name: String,
+        given_name: String,
+    ) -> Result<Student, RepositoryError>;
+
     fn get_all(
         &self
     ) -> Result<Vec<Student>, RepositoryError>;
 
+    /// トランザクション内でget_allを実行する
+    fn get_all_tx(
+        &self,
+        tx: &mut Transaction,
+    ) -> Result<Vec<Student>, RepositoryError>;
+
      fn get_by_id(
         &self,
         student_id: String,
@@ -51,19 +66,29 @@ impl StudentRepository for StudentRepositorySqlImpl{
         student_id: String,
         family_name: String,
         given_name: String,
+    ) -> Result<Student, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        self.insert_tx(&mut Transaction { conn: &mut conn }, student_id, family_name, given_name)
+    }
+
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        student_id: String,
+        family_name: String,
+        given_name: String,
     ) -> Result<Student, RepositoryError> {
         let new_student = NewStudent {
             student_id: &student_id,
             family_name: &family_name,
             given_name: &given_name,
         };
-        let mut conn = self.pool.get()?;
         let result = diesel::insert_into(student::table)
             .values(&new_student)
             .on_conflict(student::student_id)
             .do_update()
             .set(student::updated_at.eq(diesel::dsl::now))
-            .get_result::<Student>(&mut conn)?;
+            .get_result::<Student>(tx.conn)?;
 
         Ok(result)
     }
@@ -72,8 +97,15 @@ impl StudentRepository for StudentRepositorySqlImpl{
         &self
     ) -> Result<Vec<Student>, RepositoryError> {
         let mut conn = self.pool.get()?;
+        self.get_all_tx(&mut Transaction { conn: &mut conn })
+    }
+
+     fn get_all_tx(
+        &self,
+        tx: &mut Transaction,
+    ) -> Result<Vec<Student>, RepositoryError> {
         let result = student::table
-            .get_results::<Student>(&mut conn)?;
+            .get_results::<Student>(tx.conn)?;
 
         Ok(result)
     }
@@ -5,7 +5,7 @@ use uuid::Uuid;
 use crate::infrastructure::schema::*;
 use crate::infrastructure::models::*;
 use crate::infrastructure::router::Pool;
-use super::RepositoryError;
+use super::{RepositoryError, Transaction};
 
 /// # student_pair
 pub trait StudentPairRepository: Send + Sync {
@@ -16,10 +16,31 @@ pub trait StudentPairRepository: Send + Sync {
         year: i32,
     ) -> Result<StudentPair, RepositoryError>;
 
+    /// トランザクション内でinsertを実行する
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        student_id1: String,
+        student_id2: String,
+        year: i32,
+    ) -> Result<StudentPair, RepositoryError>;
+
     fn get_all(
         &self,
     ) -> Result<Vec<StudentPair>, RepositoryError>;
 
+    fn get_by_year(
+        &self,
+        year: i32,
+    ) -> Result<Vec<StudentPair>, RepositoryError>;
+
+    /// トランザクション内でget_by_yearを実行する
+    fn get_by_year_tx(
+        &self,
+        tx: &mut Transaction,
+        year: i32,
+    ) -> Result<Vec<StudentPair>, RepositoryError>;
+
     fn get_by_student_id_and_year(
         &self,
         student_id: String,
@@ -59,16 +80,26 @@ impl StudentPairRepository for StudentPairRepositorySqlImpl{
         student_id1: String,
         student_id2: String,
         year: i32,
+    ) -> Result<StudentPair, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        self.insert_tx(&mut Transaction { conn: &mut conn }, student_id1, student_id2, year)
+    }
+
+    fn insert_tx(
+        &self,
+        tx: &mut Transaction,
+        student_id1: String,
+        student_id2: String,
+        year: i32,
     ) -> Result<StudentPair, RepositoryError> {
         let new_studentpair = NewStudentPair {
             student_id1: &student_id1,
             student_id2: &student_id2,
             year: &year,
         };
-        let mut conn = self.pool.get()?;
         let result = diesel::insert_into(student_pair::table)
             .values(&new_studentpair)
-            .get_result::<StudentPair>(&mut conn)?;
+            .get_result::<StudentPair>(tx.conn)?;
 
         Ok(result)
     }
@@ -83,6 +114,26 @@ impl StudentPairRepository for StudentPairRepositorySqlImpl{
         Ok(result)
     }
 
+    fn get_by_year(
+        &self,
+        year: i32,
+    ) -> Result<Vec<StudentPair>, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        self.get_by_year_tx(&mut Transaction { conn: &mut conn }, year)
+    }
+
+    fn get_by_year_tx(
+        &self,
+        tx: &mut Transaction,
+        year: i32,
+    ) -> Result<Vec<StudentPair>, RepositoryError> {
+        let result = student_pair::table
+            .filter(student_pair::year.eq(year))
+            .get_results::<StudentPair>(tx.conn)?;
+
+        Ok(result)
+    }
+
     fn get_by_student_id_and_year(
         &self,
         student_id: String,
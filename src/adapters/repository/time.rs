@@ -14,6 +14,7 @@ pub trait TimeRepository: Send + Sync {
         name: String,
         start_time: NaiveDateTime,
         end_time: NaiveDateTime,
+        year: Option<i32>,
     ) -> Result<Time, RepositoryError>;
 
     fn get_all(
@@ -24,6 +25,10 @@ pub trait TimeRepository: Send + Sync {
         &self,
         name: String,
     ) -> Result<Time, RepositoryError>;
+
+    fn delete_all(
+        &self
+    ) -> Result<usize, RepositoryError>;
 }
 
 pub struct TimeRepositorySqlImpl {
@@ -42,18 +47,20 @@ impl TimeRepository for TimeRepositorySqlImpl {
             name: String,
             start_time: NaiveDateTime,
             end_time: NaiveDateTime,
+            year: Option<i32>,
         ) -> Result<Time, RepositoryError> {
         let new_time = NewTime{
             name: &name,
             start_time: &start_time,
             end_time: &end_time,
+            year: year.as_ref(),
         };
         let mut conn = self.pool.get()?;
         let result = diesel::insert_into(time::table)
             .values(&new_time)
             .on_conflict(time::name)
             .do_update()
-            .set((time::start_time.eq(start_time), time::end_time.eq(end_time), time::updated_at.eq(diesel::dsl::now)))
+            .set((time::start_time.eq(start_time), time::end_time.eq(end_time), time::year.eq(year), time::updated_at.eq(diesel::dsl::now)))
             .get_result::<Time>(&mut conn)?;
 
         Ok(result)
@@ -80,4 +87,14 @@ impl TimeRepository for TimeRepositorySqlImpl {
 
         Ok(result)
     }
+
+    fn delete_all(
+        &self
+    ) -> Result<usize, RepositoryError> {
+        let mut conn = self.pool.get()?;
+        let result = diesel::delete(time::table)
+            .execute(&mut conn)?;
+
+        Ok(result)
+    }
 }
\ No newline at end of file
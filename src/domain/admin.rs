@@ -0,0 +1,35 @@
+text_enum! {
+    /// ### AdminRole
+    /// adminテーブルのroleカラムに保存される管理者の権限
+    pub enum AdminRole {
+        /// 全ての管理機能を使用できる
+        Superadmin = "superadmin",
+        /// ロッカーの管理機能を使用できる
+        LockerStaff = "locker_staff",
+        /// 団体の管理機能を使用できる
+        CircleStaff = "circle_staff",
+    }
+}
+
+text_enum! {
+    /// ### LoginResult
+    /// login_attemptテーブルのresultカラムに保存されるログインの結果
+    pub enum LoginResult {
+        /// ログインに成功した
+        Success = "success",
+        /// ユーザーネームまたはパスワードが異なる
+        InvalidCredentials = "invalid_credentials",
+        /// ロックアウト中のため拒否した
+        Locked = "locked",
+        /// 無効化された管理者のため拒否した
+        Disabled = "disabled",
+    }
+}
+
+impl AdminRole {
+    /// ### permits
+    /// requiredの権限が必要な操作を行えるか確認する、superadminは全ての操作を行える
+    pub fn permits(&self, required: AdminRole) -> bool {
+        *self == AdminRole::Superadmin || *self == required
+    }
+}
@@ -9,3 +9,16 @@ pub struct AssignmentInfo {
     #[schema(example = "2001")]
     pub locker_id: String,
 }
+
+/// ### RolloverAssignment
+/// 年度切り替えで論理削除される割り当て
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RolloverAssignment {
+    #[schema(example = "2001")]
+    pub locker_id: String,
+    #[schema(example = "4622999")]
+    pub student_id1: String,
+    #[schema(example = "4622000")]
+    pub student_id2: String,
+}
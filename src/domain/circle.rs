@@ -42,4 +42,40 @@ pub struct Organization {
     pub organization_ruby: String,
     #[schema(example = "rokect@example.com")]
     pub organization_email: String,
-}
\ No newline at end of file
+}
+
+text_enum! {
+    /// ### AcceptanceStatus
+    /// registrationテーブルの受理ステータス
+    pub enum AcceptanceStatus {
+        Pending = "pending",
+        Accepted = "accepted",
+    }
+}
+
+text_enum! {
+    /// ### AuthenticationStatus
+    /// registrationテーブルの認証ステータス
+    pub enum AuthenticationStatus {
+        NotAuthenticated = "not_authenticated",
+        Authenticated = "authenticated",
+    }
+}
+
+text_enum! {
+    /// ### FormConfirmationStatus
+    /// registrationテーブルの書類受理ステータス
+    pub enum FormConfirmationStatus {
+        NotConfirmed = "not_confirmed",
+        Confirmed = "confirmed",
+    }
+}
+
+text_enum! {
+    /// ### RegistrationCompleteStatus
+    /// registrationテーブルの登録完了ステータス
+    pub enum RegistrationCompleteStatus {
+        Incomplete = "incomplete",
+        Completed = "completed",
+    }
+}
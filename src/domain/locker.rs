@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+text_enum! {
+    /// ### LockerStatus
+    /// lockerテーブルのstatusカラムに保存されるロッカーの状態
+    pub enum LockerStatus {
+        /// 空き
+        Vacant = "vacant",
+        /// 使用中
+        Occupied = "occupied",
+        /// 故障中
+        OutOfWork = "out-of-work",
+        /// 破損
+        Broken = "broken",
+        /// メンテナンス中
+        Maintenance = "maintenance",
+    }
+}
+
+/// ### LockerInfo
+/// ロッカー情報、ロッカー一括登録CSVの1行に対応する
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerInfo {
+    #[schema(example = "2001")]
+    pub locker_id: String,
+    #[schema(example = "講義棟")]
+    pub location: String,
+    #[schema(inline)]
+    pub status: LockerStatus,
+}
+
+/// ### LockerChange
+/// ロッカー一括登録で更新されるロッカーの変更前後の情報
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerChange {
+    #[schema(example = "2001")]
+    pub locker_id: String,
+    pub prev_location: String,
+    #[schema(inline)]
+    pub prev_status: LockerStatus,
+    pub location: String,
+    #[schema(inline)]
+    pub status: LockerStatus,
+}
+
+/// ### LockerImportDiff
+/// ロッカー一括登録による差分
+///
+/// in_useは割り当てがあるため状態を変更できないロッカー番号
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerImportDiff {
+    pub created: Vec<LockerInfo>,
+    pub updated: Vec<LockerChange>,
+    pub unchanged: usize,
+    pub in_use: Vec<String>,
+}
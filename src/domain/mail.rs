@@ -0,0 +1,32 @@
+use uuid::Uuid;
+
+text_enum! {
+    /// ### MailStatus
+    /// mail_outboxテーブルのstatusカラムに保存されるメールの送信状態
+    pub enum MailStatus {
+        /// 送信待ち、送信に失敗して再送を待っている場合も含む
+        Pending = "pending",
+        /// 送信済み
+        Sent = "sent",
+        /// 再送回数の上限に達した
+        Failed = "failed",
+    }
+}
+
+/// ### MailAudit
+/// 送信履歴に保存するメールの関連情報
+///
+/// template        : 使用したメールテンプレート名
+///
+/// student_id      : 宛先の学籍番号
+///
+/// auth_id         : メールを送信した認証
+///
+/// organization_id : 団体情報更新の場合の団体ID(C00001の形式)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MailAudit {
+    pub template: String,
+    pub student_id: Option<String>,
+    pub auth_id: Option<Uuid>,
+    pub organization_id: Option<String>,
+}
@@ -1,3 +1,7 @@
 pub mod router;
 pub mod models;
-pub mod schema;
\ No newline at end of file
+pub mod schema;
+pub mod sweeper;
+pub mod mailer;
+pub mod mail_template;
+pub mod mail_worker;
\ No newline at end of file
@@ -0,0 +1,192 @@
+use std::{collections::BTreeSet, env, fs, path::PathBuf};
+use dotenv::dotenv;
+use serde_json::{json, Value};
+use tera::{Context, Tera};
+use crate::infrastructure::{mailer::MailContent, models::MailTemplate};
+
+/// テンプレートが見つからない場合に使用する言語
+pub const DEFAULT_LOCALE: &str = "ja";
+
+const SUBJECT: &str = "subject.txt";
+const BODY_TEXT: &str = "body.txt";
+const BODY_HTML: &str = "body.html";
+
+/// ### MailTemplateSource
+/// テンプレートファイルの内容
+pub struct MailTemplateSource {
+    pub locale: String,
+    pub subject: String,
+    pub body_text: String,
+    pub body_html: Option<String>,
+}
+
+/// ### MailTemplates
+/// メールテンプレートの読み込みと描画を行う
+///
+/// テンプレートは`{dir}/{locale}/{name}/`に`subject.txt`、`body.txt`、`body.html`(任意)として配置する
+///
+/// 全てのテンプレートで`app_url`、`signature`を使用できる
+pub struct MailTemplates {
+    tera: Tera,
+    dir: PathBuf,
+    pub locale: String,
+}
+
+impl MailTemplates {
+    pub fn new(dir: PathBuf, locale: String) -> Result<Self, tera::Error> {
+        let tera = Tera::new(&format!("{}/**/*", dir.display()))?;
+        Ok(MailTemplates { tera, dir, locale })
+    }
+
+    /// ### from_env
+    /// MAIL_TEMPLATE_DIRからテンプレートを読み込み、MAIL_LOCALEの言語を使用する
+    ///
+    /// 設定されていない場合はtemplates/mailディレクトリ、jaを使用する
+    pub fn from_env() -> Self {
+        dotenv().ok();
+        let dir = env::var("MAIL_TEMPLATE_DIR").unwrap_or_else(|_| String::from("templates/mail"));
+        let locale = env::var("MAIL_LOCALE").unwrap_or_else(|_| String::from(DEFAULT_LOCALE));
+        MailTemplates::new(PathBuf::from(dir), locale).expect("Failed to load mail templates")
+    }
+
+    /// ### names
+    /// 使用できるテンプレート名の一覧を取得する
+    pub fn names(&self) -> Vec<String> {
+        let names: BTreeSet<String> = self.tera.get_template_names()
+            .filter_map(|template| template.strip_suffix(&format!("/{}", BODY_TEXT)))
+            .filter_map(|template| template.split_once('/'))
+            .map(|(_, name)| name.to_string())
+            .collect();
+        names.into_iter().collect()
+    }
+
+    /// ### source
+    /// テンプレートファイルの内容を取得する、テンプレートが存在しない場合はNoneを返す
+    pub fn source(&self, name: &str) -> Option<MailTemplateSource> {
+        let locale = self.resolve_locale(name)?;
+        let read = |file: &str| fs::read_to_string(self.dir.join(&locale).join(name).join(file)).ok();
+
+        Some(MailTemplateSource {
+            subject: read(SUBJECT)?,
+            body_text: read(BODY_TEXT)?,
+            body_html: read(BODY_HTML),
+            locale,
+        })
+    }
+
+    /// ### render
+    /// テンプレートを描画する、overriddenがある場合はテンプレートファイルの代わりに使用する
+    pub fn render(&self, name: &str, value: Value, overridden: Option<&MailTemplate>) -> Result<MailContent, tera::Error> {
+        let mut context = Context::from_value(value)?;
+        dotenv().ok();
+        if !context.contains_key("app_url") {
+            context.insert("app_url", &env::var("APP_URL").expect("APP_URL must be set."));
+        }
+        if !context.contains_key("signature") {
+            context.insert("signature", &env::var("EMAIL_SIGNATURE").expect("EMAIL_SIGNATURE must be set."));
+        }
+
+        match overridden {
+            Some(template) => Ok(MailContent {
+                subject: Tera::one_off(&template.subject, &context, false)?.trim().to_string(),
+                text: Tera::one_off(&template.body_text, &context, false)?,
+                html: match &template.body_html {
+                    Some(html) => Some(Tera::one_off(html, &context, true)?),
+                    None => None,
+                },
+            }),
+            None => {
+                let locale = self.resolve_locale(name)
+                    .ok_or_else(|| tera::Error::template_not_found(name))?;
+                let path = |file: &str| format!("{}/{}/{}", locale, name, file);
+                let html = path(BODY_HTML);
+
+                Ok(MailContent {
+                    subject: self.tera.render(&path(SUBJECT), &context)?.trim().to_string(),
+                    text: self.tera.render(&path(BODY_TEXT), &context)?,
+                    html: match self.tera.get_template_names().any(|template| template == html) {
+                        true => Some(self.tera.render(&html, &context)?),
+                        false => None,
+                    },
+                })
+            },
+        }
+    }
+
+    /// ### validate
+    /// 管理画面から編集されたテンプレートを検証する
+    ///
+    /// テンプレート名ごとに使用できる変数のサンプル値で描画し、構文エラーや存在しない変数があればエラーを返す
+    pub fn validate(name: &str, subject: &str, body_text: &str, body_html: Option<&str>) -> Result<(), tera::Error> {
+        let context = Context::from_value(sample_context(name))?;
+        Tera::one_off(subject, &context, false)?;
+        Tera::one_off(body_text, &context, false)?;
+        if let Some(html) = body_html {
+            Tera::one_off(html, &context, true)?;
+        }
+        Ok(())
+    }
+
+    // 設定された言語のテンプレートがない場合はDEFAULT_LOCALEを使用する
+    fn resolve_locale(&self, name: &str) -> Option<String> {
+        [self.locale.as_str(), DEFAULT_LOCALE].into_iter()
+            .find(|locale| self.tera.get_template_names().any(|template| template == format!("{}/{}/{}", locale, name, BODY_TEXT)))
+            .map(|locale| locale.to_string())
+    }
+}
+
+// テンプレート名ごとに使用できる変数のサンプル値、templates/mail/README.mdの変数の一覧と対応する
+fn sample_context(name: &str) -> Value {
+    let locker_user = json!({
+        "student_id": "4622999",
+        "family_name": "テスト",
+        "given_name": "太郎",
+    });
+    let circle_user = json!({
+        "student_id": "4622999",
+        "family_name": "テスト",
+        "given_name": "太郎",
+        "email": "4622999@ed.tus.ac.jp",
+        "phone_number": "090-0000-0000",
+    });
+    let mut context = match name {
+        "locker_main_auth" | "locker_co_auth" => json!({
+            "family_name": "テスト", "given_name": "太郎", "token": "token",
+        }),
+        "locker_auth_check" => json!({
+            "family_name": "テスト", "given_name": "太郎", "token": "token",
+            "main": locker_user, "co": locker_user,
+        }),
+        "locker_register_complete" => json!({
+            "locker_id": "2001",
+        }),
+        "locker_cancel_confirm" => json!({
+            "family_name": "テスト", "given_name": "太郎", "token": "token", "locker_id": "2001",
+        }),
+        "locker_cancel_complete" => json!({
+            "family_name": "テスト", "given_name": "太郎", "locker_id": "2001",
+        }),
+        "locker_assignment_create" => json!({
+            "family_name": "テスト", "given_name": "太郎", "locker_id": "2001",
+            "main": locker_user, "co": locker_user,
+        }),
+        "locker_assignment_update" => json!({
+            "family_name": "テスト", "given_name": "太郎", "prev_locker_id": "2001", "locker_id": "2002",
+        }),
+        "circle_update_entry" => json!({
+            "organization_name": "団体", "family_name": "テスト", "given_name": "太郎", "form_url": "https://example.com",
+        }),
+        "circle_main_auth" | "circle_co_auth" => json!({
+            "family_name": "テスト", "given_name": "太郎", "token": "token", "organization_id": "1",
+        }),
+        "circle_update_complete" | "circle_register_complete" => json!({
+            "family_name": "テスト", "given_name": "太郎",
+            "organization_name": "団体", "organization_ruby": "だんたい", "organization_email": "circle@example.com",
+            "main": circle_user, "co": circle_user,
+        }),
+        _ => json!({}),
+    };
+    context["app_url"] = json!("https://example.com");
+    context["signature"] = json!("署名");
+    context
+}
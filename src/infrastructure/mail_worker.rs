@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::TimeDelta;
+use rocket::tokio;
+use crate::infrastructure::mailer::Mailer;
+use crate::usecase::mail_outbox::{MailOutboxUsecase, MailOutboxUsecaseImpl};
+use crate::utils::env::positive_from_env;
+
+/// ### MailWorkerOption
+/// 送信待ちメールを送信するタスクの設定
+///
+/// interval    : 送信待ちメールを確認する間隔、メールが追加された場合は待たずに送信する
+///
+/// batch_size  : 1回に送信する最大件数
+///
+/// retention   : 送信済み、送信に失敗したメールの本文を保存する期間、送信履歴は削除しない
+pub struct MailWorkerOption {
+    pub interval: Duration,
+    pub batch_size: i64,
+    pub retention: TimeDelta,
+}
+
+/// 保存期間を過ぎたメールの本文を削除する間隔
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+impl MailWorkerOption {
+    /// ### from_env
+    /// 環境変数から設定を読み込む、設定されていない場合は既定値を使用する
+    pub fn from_env() -> Self {
+        let interval_seconds = positive_from_env("MAIL_WORKER_INTERVAL_SECONDS").unwrap_or(30);
+        let batch_size = positive_from_env("MAIL_WORKER_BATCH_SIZE").unwrap_or(50);
+        let retention_days = positive_from_env("MAIL_RETENTION_DAYS").unwrap_or(30);
+
+        MailWorkerOption {
+            interval: Duration::from_secs(interval_seconds as u64),
+            batch_size,
+            retention: TimeDelta::days(retention_days),
+        }
+    }
+}
+
+/// ### spawn_mail_worker
+/// 送信待ちメールを送信するタスクと、保存期間を過ぎたメールの本文を削除するタスクを起動する
+pub fn spawn_mail_worker(outbox: MailOutboxUsecaseImpl, mailer: Arc<dyn Mailer>, option: MailWorkerOption) {
+    let purge_outbox = outbox.clone();
+    let retention = option.retention;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match purge_outbox.purge(retention).await {
+                Ok(count) => println!("mail worker cleared the bodies of {} mails.", count),
+                Err(status) => eprintln!("mail purge failed: {}", status),
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(option.interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = outbox.notify.notified() => {},
+            }
+
+            // batch_size件を超える場合は続けて送信する
+            loop {
+                match outbox.deliver_due(mailer.as_ref(), option.batch_size).await {
+                    Ok(report) => {
+                        if report.retrying > 0 || report.failed > 0 || report.expired > 0 {
+                            println!(
+                                "mail worker sent {} mails, {} mails will be retried, {} mails failed, {} mails expired.",
+                                report.sent, report.retrying, report.failed, report.expired
+                            );
+                        }
+                        if ((report.sent + report.retrying + report.failed + report.expired) as i64) < option.batch_size {
+                            break;
+                        }
+                    },
+                    Err(status) => {
+                        eprintln!("mail worker failed: {}", status);
+                        break;
+                    },
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,241 @@
+use std::{env, fs, path::PathBuf, sync::{Arc, Mutex}};
+use async_trait::async_trait;
+use chrono::Utc;
+use dotenv::dotenv;
+use lettre::message::{header::ContentType, MultiPart};
+use lettre::{Message, SmtpTransport, Transport, transport::smtp::{authentication::Credentials, client::{TlsParameters, Tls}}};
+use rocket::tokio::task;
+use crate::utils::token::generate_token;
+
+/// ### MailTransport
+/// メールの送信方法
+///
+/// Smtp                : SMTP_SERVERへ送信する(本番用)
+///
+/// AuthenticatedSmtp   : SENDER_MAIL_ADDRESS、MAIL_APP_KEYで認証してGmailから送信する
+///
+/// File                : MAIL_DROP_DIRに.emlファイルとして書き出す(ローカル開発用)
+///
+/// Memory              : メモリ上に保存する(テスト用)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MailTransport {
+    Smtp,
+    AuthenticatedSmtp,
+    File,
+    Memory,
+}
+
+/// ### MailContent
+/// 送信するメールの件名と本文
+///
+/// htmlがある場合はテキストとHTMLのmultipart/alternativeで送信する
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MailContent {
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+}
+
+/// ### SentMail
+/// MemoryMailerに保存された送信済みメール
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SentMail {
+    pub to: String,
+    pub subject: String,
+    pub content: String,
+    pub html: Option<String>,
+}
+
+/// ### MailError
+/// メール送信時のエラー、送信待ちメールの失敗理由として保存する
+#[derive(Debug, thiserror::Error)]
+pub enum MailError {
+    #[error("MessageError: {0}")]
+    MessageError(String),
+
+    #[error("TransportError: {0}")]
+    TransportError(String),
+
+    #[error("IoError: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// # Mailer
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, user_address: String, mail: MailContent) -> Result<(), MailError>;
+}
+
+/// ### build_mailer
+/// MailTransportに対応するMailerを作成する
+pub fn build_mailer(transport: MailTransport) -> Arc<dyn Mailer> {
+    match transport {
+        MailTransport::Smtp => Arc::new(SmtpMailer),
+        MailTransport::AuthenticatedSmtp => Arc::new(AuthenticatedSmtpMailer),
+        MailTransport::File => Arc::new(FileMailer::from_env()),
+        MailTransport::Memory => Arc::new(MemoryMailer::new()),
+    }
+}
+
+// 送信するメッセージを作成する
+fn build_message(sender_name: &str, sender_address: &str, user_address: &str, mail: MailContent) -> Result<Message, MailError> {
+    let builder = Message::builder()
+        .from(
+            format!("{} <{}>", sender_name, sender_address)
+                .parse()
+                .map_err(|e| MailError::MessageError(format!("{}", e)))?,
+        )
+        .to(format!("User <{}>", user_address)
+            .parse()
+            .map_err(|e| MailError::MessageError(format!("{}", e)))?)
+        .subject(mail.subject);
+
+    let message = match mail.html {
+        Some(html) => builder.multipart(MultiPart::alternative_plain_html(mail.text, html)),
+        None => builder.header(ContentType::TEXT_PLAIN).body(mail.text),
+    };
+
+    message.map_err(|e| MailError::MessageError(format!("{}", e)))
+}
+
+// SMTPでの送信はブロッキング処理のため別スレッドで行う
+async fn send_blocking(mailer: SmtpTransport, email: Message) -> Result<(), MailError> {
+    match task::spawn_blocking(move || mailer.send(&email)).await {
+        Err(e) => Err(MailError::TransportError(format!("thread panic in spawn_blocking: {}", e))),
+        Ok(Err(e)) => Err(MailError::TransportError(format!("{}", e))),
+        Ok(Ok(_)) => Ok(()),
+    }
+}
+
+/// ### SmtpMailer
+/// SMTP_SERVERの25番ポートへ送信する
+pub struct SmtpMailer;
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, user_address: String, mail: MailContent) -> Result<(), MailError> {
+        // 環境変数の読み取り
+        dotenv().ok();
+        let sender_address = env::var("SENDER_MAIL_ADDRESS").expect("SENDER_MAIL_ADDRESS must be set.");
+        let smtp_server = env::var("SMTP_SERVER").expect("SMTP_SERVER must be set.");
+
+        let email = build_message("Noreply", &sender_address, &user_address, mail)?;
+
+        // SMTPサーバーに接続する
+        let mailer = SmtpTransport::builder_dangerous(smtp_server.as_str())
+            .port(25)
+            .build();
+
+        send_blocking(mailer, email).await
+    }
+}
+
+/// ### AuthenticatedSmtpMailer
+/// SENDER_MAIL_ADDRESS、MAIL_APP_KEYで認証してGmailから送信する
+pub struct AuthenticatedSmtpMailer;
+
+#[async_trait]
+impl Mailer for AuthenticatedSmtpMailer {
+    async fn send(&self, user_address: String, mail: MailContent) -> Result<(), MailError> {
+        // 環境変数の読み取り
+        dotenv().ok();
+        let sender_address = env::var("SENDER_MAIL_ADDRESS").expect("SENDER_MAIL_ADDRESS must be set.");
+        let appkey = env::var("MAIL_APP_KEY").expect("MAIL_APP_KEY must be set.");
+
+        let email = build_message("Developer", &sender_address, &user_address, mail)?;
+
+        // SMTP認証情報
+        let creds = Credentials::new(sender_address.to_owned(), appkey.to_owned());
+
+        // TLSパラメータを生成
+        let tls_parameters = TlsParameters::builder("smtp.gmail.com".to_string())
+            .build()
+            .map_err(|e| MailError::TransportError(format!("{}", e)))?;
+
+        // Gmailにsmtp接続する
+        let mailer = SmtpTransport::relay("smtp.gmail.com")
+            .map_err(|e| MailError::TransportError(format!("{}", e)))?
+            .port(587)
+            .tls(Tls::Required(tls_parameters))
+            .credentials(creds)
+            .build();
+
+        send_blocking(mailer, email).await
+    }
+}
+
+/// ### FileMailer
+/// 送信する代わりにdirへ.emlファイルとして書き出す
+pub struct FileMailer {
+    pub dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(dir: PathBuf) -> Self {
+        FileMailer { dir }
+    }
+
+    /// ### from_env
+    /// MAIL_DROP_DIRを書き出し先にする、設定されていない場合はmailディレクトリを使用する
+    pub fn from_env() -> Self {
+        dotenv().ok();
+        let dir = env::var("MAIL_DROP_DIR").unwrap_or_else(|_| String::from("mail"));
+        FileMailer::new(PathBuf::from(dir))
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, user_address: String, mail: MailContent) -> Result<(), MailError> {
+        dotenv().ok();
+        let sender_address = env::var("SENDER_MAIL_ADDRESS").unwrap_or_else(|_| String::from("noreply@localhost"));
+
+        let email = build_message("Developer", &sender_address, &user_address, mail)?;
+
+        let path = self.dir.join(format!("{}_{}.eml", Utc::now().format("%y%m%d%H%M%S"), generate_token()));
+        fs::create_dir_all(&self.dir)?;
+        fs::write(&path, email.formatted())?;
+        println!("mail written to {}", path.display());
+
+        Ok(())
+    }
+}
+
+/// ### MemoryMailer
+/// 送信したメールをメモリ上に保存する、統合テストで送信内容を確認するために使用する
+pub struct MemoryMailer {
+    outbox: Mutex<Vec<SentMail>>,
+}
+
+impl MemoryMailer {
+    pub fn new() -> Self {
+        MemoryMailer { outbox: Mutex::new(Vec::new()) }
+    }
+
+    /// ### sent
+    /// 送信済みのメールを送信順に取得する
+    pub fn sent(&self) -> Vec<SentMail> {
+        self.outbox.lock().map(|outbox| outbox.clone()).unwrap_or_default()
+    }
+}
+
+impl Default for MemoryMailer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Mailer for MemoryMailer {
+    async fn send(&self, user_address: String, mail: MailContent) -> Result<(), MailError> {
+        let mut outbox = self.outbox.lock().map_err(|e| MailError::TransportError(format!("{}", e)))?;
+        outbox.push(SentMail {
+            to: user_address,
+            subject: mail.subject,
+            content: mail.text,
+            html: mail.html,
+        });
+
+        Ok(())
+    }
+}
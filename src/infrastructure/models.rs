@@ -1,6 +1,7 @@
-use diesel::{Insertable, Queryable};
+use diesel::{AsChangeset, Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 use crate::infrastructure::schema::*;
+use crate::domain::{admin::{AdminRole, LoginResult}, locker::LockerStatus, mail::MailStatus, circle::{AcceptanceStatus, AuthenticationStatus, FormConfirmationStatus, RegistrationCompleteStatus}};
 // student
 
 #[derive(Queryable, Serialize)]
@@ -45,7 +46,7 @@ pub struct NewStudentPair<'a> {
 pub struct Locker {
     pub locker_id: String,
     pub location: String,
-    pub status: String,
+    pub status: LockerStatus,
 }
 
 #[derive(Insertable)]
@@ -53,7 +54,14 @@ pub struct Locker {
 pub struct NewLocker<'a> {
     pub locker_id: &'a String,
     pub location: &'a String,
-    pub status: &'a String,
+    pub status: &'a LockerStatus,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = locker)]
+pub struct LockerChangeset<'a> {
+    pub location: Option<&'a String>,
+    pub status: Option<&'a LockerStatus>,
 }
 
 // assignment_record
@@ -85,6 +93,7 @@ pub struct Auth {
     pub co_auth_token: String,
     pub phase: String,
     pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
 }
 
 #[derive(Insertable)]
@@ -111,6 +120,8 @@ pub struct AssignmentRecordGetResult {
 pub struct Admin{
     pub username: String,
     pub password: String,
+    pub role: AdminRole,
+    pub disabled: bool,
 }
 
 #[derive(Insertable)]
@@ -118,6 +129,55 @@ pub struct Admin{
 pub struct NewAdmin<'a> {
     pub username: &'a String,
     pub password: &'a String,
+    pub role: AdminRole,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = admin)]
+pub struct AdminChangeset<'a> {
+    pub password: Option<&'a String>,
+    pub role: Option<&'a AdminRole>,
+    pub disabled: Option<bool>,
+}
+
+// admin session
+
+#[derive(Queryable)]
+pub struct AdminSession{
+    pub session_id: uuid::Uuid,
+    pub username: String,
+    pub ip_address: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub last_seen_at: chrono::NaiveDateTime,
+    pub expires_at: chrono::NaiveDateTime,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = admin_session)]
+pub struct NewAdminSession<'a>{
+    pub username: &'a String,
+    pub ip_address: Option<&'a String>,
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+// login attempt
+
+#[derive(Queryable)]
+pub struct LoginAttempt{
+    pub attempt_id: uuid::Uuid,
+    pub username: String,
+    pub ip_address: Option<String>,
+    pub result: LoginResult,
+    pub attempted_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = login_attempt)]
+pub struct NewLoginAttempt<'a>{
+    pub username: &'a String,
+    pub ip_address: Option<&'a String>,
+    pub result: LoginResult,
 }
 
 // locker auth info
@@ -243,10 +303,10 @@ pub struct Registration{
     pub year: i32,
     pub main_student_id: String,
     pub co_student_id: String,
-    pub status_acceptance: String,
-    pub status_authentication: String,
-    pub status_form_confirmation: String,
-    pub status_registration_complete: String,
+    pub status_acceptance: AcceptanceStatus,
+    pub status_authentication: AuthenticationStatus,
+    pub status_form_confirmation: FormConfirmationStatus,
+    pub status_registration_complete: RegistrationCompleteStatus,
     pub b_doc: String,
     pub c_doc: String,
     pub d_doc: String,
@@ -261,10 +321,10 @@ pub struct NewRegistration<'a>{
     pub year: &'a i32,
     pub main_student_id: &'a String,
     pub co_student_id: &'a String,
-    pub status_acceptance: &'a String,
-    pub status_authentication: &'a String,
-    pub status_form_confirmation: &'a String,
-    pub status_registration_complete: &'a String,
+    pub status_acceptance: &'a AcceptanceStatus,
+    pub status_authentication: &'a AuthenticationStatus,
+    pub status_form_confirmation: &'a FormConfirmationStatus,
+    pub status_registration_complete: &'a RegistrationCompleteStatus,
     pub b_doc: &'a String,
     pub c_doc: &'a String,
     pub d_doc: &'a String,
@@ -278,6 +338,7 @@ pub struct Time{
     pub start_time: chrono::NaiveDateTime,
     pub end_time: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    pub year: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -286,4 +347,61 @@ pub struct NewTime<'a>{
     pub name: &'a String,
     pub start_time: &'a chrono::NaiveDateTime,
     pub end_time: &'a chrono::NaiveDateTime,
-}
\ No newline at end of file
+    pub year: Option<&'a i32>,
+}
+
+// mail_template
+
+#[derive(Clone, Queryable, Serialize)]
+pub struct MailTemplate{
+    pub name: String,
+    pub locale: String,
+    pub subject: String,
+    pub body_text: String,
+    pub body_html: Option<String>,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = mail_template)]
+pub struct NewMailTemplate<'a>{
+    pub name: &'a String,
+    pub locale: &'a String,
+    pub subject: &'a String,
+    pub body_text: &'a String,
+    pub body_html: Option<&'a String>,
+}
+
+// mail_outbox
+
+#[derive(Clone, Queryable, Serialize)]
+pub struct MailOutbox{
+    pub mail_id: uuid::Uuid,
+    pub to_address: String,
+    pub subject: String,
+    pub body_text: String,
+    pub body_html: Option<String>,
+    pub status: MailStatus,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: chrono::NaiveDateTime,
+    pub created_at: chrono::NaiveDateTime,
+    pub sent_at: Option<chrono::NaiveDateTime>,
+    pub template: Option<String>,
+    pub student_id: Option<String>,
+    pub auth_id: Option<uuid::Uuid>,
+    pub organization_id: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = mail_outbox)]
+pub struct NewMailOutbox<'a>{
+    pub to_address: &'a String,
+    pub subject: &'a String,
+    pub body_text: &'a String,
+    pub body_html: Option<&'a String>,
+    pub template: &'a String,
+    pub student_id: Option<&'a String>,
+    pub auth_id: Option<&'a uuid::Uuid>,
+    pub organization_id: Option<&'a String>,
+}
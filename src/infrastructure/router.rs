@@ -3,8 +3,12 @@ use std::env;
 use std::time::Duration;
 use diesel::{PgConnection, r2d2::ConnectionManager};
 use dotenv::dotenv;
+use rocket::figment::Figment;
 use crate::adapters::repository::{
+                                UnitOfWork,
                                 admin::AdminRepositorySqlImpl,
+                                admin_session::AdminSessionRepositorySqlImpl,
+                                login_attempt::LoginAttemptRepositorySqlImpl,
                                 assignment_record::AssignmentRecordRepositorySqlImpl,
                                 auth::AuthRepositorySqlImpl,
                                 circle_auth_info::CircleAuthInfoRepositorySqlImpl,
@@ -16,6 +20,8 @@ use crate::adapters::repository::{
                                 student_pair::StudentPairRepositorySqlImpl,
                                 student::StudentRepositorySqlImpl,
                                 time::TimeRepositorySqlImpl,
+                                mail_template::MailTemplateRepositorySqlImpl,
+                                mail_outbox::MailOutboxRepositorySqlImpl,
                             };
 use crate::usecase::{
                     student::StudentUsecaseImpl,
@@ -23,25 +29,31 @@ use crate::usecase::{
                     assignment_record::AssignmentRecordUsecaseImpl,
                     auth::AuthUsecaseImpl,
                     admin::AdminUsecaseImpl,
+                    admin_session::{AdminSessionUsecaseImpl, SessionPolicy},
+                    login_attempt::{LoginAttemptUsecaseImpl, LoginThrottlePolicy},
                     locker::LockerUsecaseImpl,
                     representatives::RepresentativesUsecaseImpl,
                     organization::OrganizationUsecaseImpl,
                     registration::RegistrationUsecaseImpl,
                     time::TimeUsecaseImpl,
+                    mail_template::MailTemplateUsecaseImpl,
+                    mail_outbox::{MailOutboxUsecaseImpl, MailRetryPolicy},
                 };
+use crate::infrastructure::{mailer::{build_mailer, MailTransport, Mailer}, mail_template::MailTemplates};
+use crate::utils::{token::TokenTtl, student_identity::StudentIdentity};
 
 pub type Pool<T> = diesel::r2d2::Pool<ConnectionManager<T>>;
 
 pub struct AppOption{
     pub same_student_enable: bool,
-    pub local_mail_enable: bool,
+    pub mail_transport: MailTransport,
 }
 
 impl AppOption {
     pub fn new() -> Self {
         AppOption {
             same_student_enable: false,
-            local_mail_enable: false,
+            mail_transport: MailTransport::Smtp,
         }
     }
 }
@@ -52,18 +64,38 @@ impl Default for AppOption {
     }
 }
 
+/// Rocketの設定
+///
+/// TRUSTED_PROXY_IP_HEADERが設定されている場合のみ、そのヘッダーからクライアントのIPアドレスを取得する
+/// 設定されていない場合はX-Real-IPなどのヘッダーを信頼せず、接続元のIPアドレスを使用する
+pub fn rocket_config() -> Figment {
+    dotenv().ok();
+
+    let figment = rocket::Config::figment();
+    match env::var("TRUSTED_PROXY_IP_HEADER") {
+        Ok(header) if !header.trim().is_empty() => figment.merge((rocket::Config::IP_HEADER, header.trim().to_string())),
+        _ => figment.merge((rocket::Config::IP_HEADER, false)),
+    }
+}
+
 pub struct App{
     pub option: AppOption,
+    pub mailer: Arc<dyn Mailer>,
+    pub student_identity: StudentIdentity,
     pub student: StudentUsecaseImpl,
     pub student_pair: StudentPairUsecaseImpl,
     pub auth: AuthUsecaseImpl,
     pub locker: LockerUsecaseImpl,
     pub assignment_record: AssignmentRecordUsecaseImpl,
     pub admin: AdminUsecaseImpl,
+    pub admin_session: AdminSessionUsecaseImpl,
+    pub login_attempt: LoginAttemptUsecaseImpl,
     pub representatives: RepresentativesUsecaseImpl,
     pub organization: OrganizationUsecaseImpl,
     pub registration: RegistrationUsecaseImpl,
     pub time: TimeUsecaseImpl,
+    pub mail_template: MailTemplateUsecaseImpl,
+    pub mail_outbox: MailOutboxUsecaseImpl,
 }
 
 impl App{
@@ -71,6 +103,8 @@ impl App{
         dotenv().ok();
 
         let option = app_option;
+        let mailer = build_mailer(option.mail_transport);
+        let student_identity = StudentIdentity::from_env();
 
         let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set.");
         let manager = ConnectionManager::<PgConnection>::new(&database_url);
@@ -81,27 +115,37 @@ impl App{
 
         let student_repository = StudentUsecaseImpl::new(Arc::new(StudentRepositorySqlImpl::new(pool.clone())));
         let student_pair_repository = StudentPairUsecaseImpl::new(Arc::new(StudentPairRepositorySqlImpl::new(pool.clone())));
-        let auth_repository = AuthUsecaseImpl::new(Arc::new(AuthRepositorySqlImpl::new(pool.clone())), Arc::new(LockerAuthInfoRepositorySqlImpl::new(pool.clone())), Arc::new(CircleAuthInfoRepositorySqlImpl::new(pool.clone())));
-        let locker_repository = LockerUsecaseImpl::new(Arc::new(LockerRepositorySqlImpl::new(pool.clone())));
-        let assignment_record_repository = AssignmentRecordUsecaseImpl::new(Arc::new(AssignmentRecordRepositorySqlImpl::new(pool.clone())));
-        let admin_repository = AdminUsecaseImpl::new(Arc::new(AdminRepositorySqlImpl::new(pool.clone())));
+        let auth_repository = AuthUsecaseImpl::new(Arc::new(AuthRepositorySqlImpl::new(pool.clone())), Arc::new(LockerAuthInfoRepositorySqlImpl::new(pool.clone())), Arc::new(CircleAuthInfoRepositorySqlImpl::new(pool.clone())), Arc::new(StudentRepositorySqlImpl::new(pool.clone())), Arc::new(StudentPairRepositorySqlImpl::new(pool.clone())), UnitOfWork::new(pool.clone()), TokenTtl::from_env());
+        let locker_repository = LockerUsecaseImpl::new(Arc::new(LockerRepositorySqlImpl::new(pool.clone())), UnitOfWork::new(pool.clone()));
+        let assignment_record_repository = AssignmentRecordUsecaseImpl::new(Arc::new(AssignmentRecordRepositorySqlImpl::new(pool.clone())), Arc::new(StudentRepositorySqlImpl::new(pool.clone())), Arc::new(StudentPairRepositorySqlImpl::new(pool.clone())), Arc::new(LockerRepositorySqlImpl::new(pool.clone())), UnitOfWork::new(pool.clone()));
+        let admin_repository = AdminUsecaseImpl::new(Arc::new(AdminRepositorySqlImpl::new(pool.clone())), UnitOfWork::new(pool.clone()));
+        let admin_session_repository = AdminSessionUsecaseImpl::new(Arc::new(AdminSessionRepositorySqlImpl::new(pool.clone())), SessionPolicy::from_env());
+        let login_attempt_repository = LoginAttemptUsecaseImpl::new(Arc::new(LoginAttemptRepositorySqlImpl::new(pool.clone())), UnitOfWork::new(pool.clone()), LoginThrottlePolicy::from_env());
         let representatives_repository = RepresentativesUsecaseImpl::new(Arc::new(RepresentativesRepositorySqlImpl::new(pool.clone())));
         let organization_repository = OrganizationUsecaseImpl::new(Arc::new(OrganizationRepositorySqlImpl::new(pool.clone())));
-        let registration_repository = RegistrationUsecaseImpl::new(Arc::new(RegistrationRepositorySqlImpl::new(pool.clone())));
+        let registration_repository = RegistrationUsecaseImpl::new(Arc::new(RegistrationRepositorySqlImpl::new(pool.clone())), Arc::new(OrganizationRepositorySqlImpl::new(pool.clone())), Arc::new(RepresentativesRepositorySqlImpl::new(pool.clone())), UnitOfWork::new(pool.clone()));
         let time_repository = TimeUsecaseImpl::new(Arc::new(TimeRepositorySqlImpl::new(pool.clone())));
+        let mail_template_repository = MailTemplateUsecaseImpl::new(Arc::new(MailTemplateRepositorySqlImpl::new(pool.clone())), Arc::new(MailTemplates::from_env()));
+        let mail_outbox_repository = MailOutboxUsecaseImpl::new(Arc::new(MailOutboxRepositorySqlImpl::new(pool.clone())), MailRetryPolicy::from_env());
 
         App {
             option,
+            mailer,
+            student_identity,
             student: student_repository,
             student_pair: student_pair_repository,
             auth: auth_repository,
             locker: locker_repository,
             assignment_record: assignment_record_repository,
             admin: admin_repository,
+            admin_session: admin_session_repository,
+            login_attempt: login_attempt_repository,
             representatives: representatives_repository,
             organization: organization_repository,
             registration: registration_repository,
             time: time_repository,
+            mail_template: mail_template_repository,
+            mail_outbox: mail_outbox_repository,
         }
     }
 }
@@ -0,0 +1,48 @@
+use std::time::Duration;
+use chrono::TimeDelta;
+use rocket::tokio;
+use crate::usecase::auth::{AuthUsecase, AuthUsecaseImpl};
+use crate::utils::env::positive_from_env;
+
+/// ### AuthSweeperOption
+/// 放置された認証レコードを削除するタスクの設定
+///
+/// interval    : 実行間隔
+///
+/// max_age     : 削除対象とする最終更新からの経過時間、tokenの有効期限より長くする
+pub struct AuthSweeperOption {
+    pub interval: Duration,
+    pub max_age: TimeDelta,
+}
+
+impl AuthSweeperOption {
+    /// ### from_env
+    /// 環境変数から設定を読み込む、設定されていない場合は既定値を使用する
+    pub fn from_env() -> Self {
+        let interval_minutes = positive_from_env("AUTH_SWEEP_INTERVAL_MINUTES").unwrap_or(60);
+        let max_age_hours = positive_from_env("AUTH_SWEEP_MAX_AGE_HOURS").unwrap_or(72);
+
+        AuthSweeperOption {
+            interval: Duration::from_secs(interval_minutes as u64 * 60),
+            max_age: TimeDelta::hours(max_age_hours),
+        }
+    }
+}
+
+/// ### spawn_auth_sweeper
+/// 放置された認証レコードを定期的に削除するタスクを起動する
+pub fn spawn_auth_sweeper(auth: AuthUsecaseImpl, option: AuthSweeperOption) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(option.interval);
+        loop {
+            interval.tick().await;
+            match auth.sweep_stale(option.max_age).await {
+                Ok(report) => println!(
+                    "auth sweeper removed {} auth, {} locker_auth_info, {} circle_auth_info rows.",
+                    report.auth, report.locker_auth_info, report.circle_auth_info
+                ),
+                Err(status) => eprintln!("auth sweeper failed: {}", status),
+            }
+        }
+    });
+}
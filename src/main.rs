@@ -1,7 +1,7 @@
-use tus_yuurikai_system::{infrastructure::router::{App, AppOption}, adapters::controller::ApiDoc};
+use tus_yuurikai_system::{infrastructure::{router::{App, AppOption, rocket_config}, sweeper::{spawn_auth_sweeper, AuthSweeperOption}, mail_worker::{spawn_mail_worker, MailWorkerOption}, mailer::MailTransport}, adapters::controller::ApiDoc};
 use tus_yuurikai_system::adapters::controller::{*, locker::*, circle::*};
 
-use rocket::{routes, fs::{FileServer, relative, NamedFile}};
+use rocket::{routes, catchers, fs::{FileServer, relative, NamedFile}};
 use rocket_cors::{CorsOptions, AllowedOrigins};
 use utoipa_swagger_ui::SwaggerUi;
 use utoipa::OpenApi;
@@ -42,9 +42,13 @@ async fn main() -> Result<(), rocket::Error> {
                 println!("option same student changed.");
             },
             "local-mail" => {
-                app_option.local_mail_enable = true;
+                app_option.mail_transport = MailTransport::AuthenticatedSmtp;
                 println!("option local mail changed.");
             },
+            "file-mail" => {
+                app_option.mail_transport = MailTransport::File;
+                println!("option file mail changed.");
+            },
             _ => {
                 panic!("Error: Invalid option.")
             }
@@ -65,7 +69,14 @@ async fn main() -> Result<(), rocket::Error> {
     .expect("CORS設定に失敗しました");
 
     let app = App::new(app_option);
-    let _rocket = rocket::build()
+
+    // 放置された認証レコードの定期削除
+    spawn_auth_sweeper(app.auth.clone(), AuthSweeperOption::from_env());
+
+    // 送信待ちメールの送信
+    spawn_mail_worker(app.mail_outbox.clone(), app.mailer.clone(), MailWorkerOption::from_env());
+
+    let _rocket = rocket::custom(rocket_config())
         .manage(app)
         .attach(cors)
         .mount(
@@ -81,6 +92,24 @@ async fn main() -> Result<(), rocket::Error> {
             "/api/admin",
             routes![
                 download,
+                auth_sweep_status,
+                mail_template_list,
+                mail_template_get,
+                mail_template_put,
+                mail_template_delete,
+                mail_outbox_list,
+                mail_outbox_retry,
+                mail_outbox_retry_failed,
+                mail_log_search,
+                admin_list,
+                admin_create,
+                admin_update,
+                admin_password_reset,
+                admin_password_change,
+                admin_session_list,
+                admin_session_revoke,
+                admin_session_revoke_all,
+                login_attempt_list,
             ]
         )
         .mount(
@@ -88,6 +117,15 @@ async fn main() -> Result<(), rocket::Error> {
             routes![
                 user_search,
                 reset,
+                rollover,
+                period_setting_post,
+                locker_list,
+                locker_create,
+                locker_update,
+                locker_delete,
+                locker_import,
+                assignment_create,
+                assignment_update,
             ],
         )
         .mount(
@@ -105,8 +143,12 @@ async fn main() -> Result<(), rocket::Error> {
                 main_auth,
                 co_auth,
                 auth_check,
+                locker_resend,
                 locker_register,
-                availability
+                cancel_token_generator,
+                cancel,
+                availability,
+                period_setting_get,
             ],
         )
         .mount(
@@ -117,6 +159,7 @@ async fn main() -> Result<(), rocket::Error> {
                 update_token_generator,
                 circle_main_auth,
                 circle_co_auth,
+                circle_resend,
                 circle_status,
                 access_setting_get,
             ]
@@ -127,6 +170,7 @@ async fn main() -> Result<(), rocket::Error> {
         //     SwaggerUi::new("/swagger-ui/<_..>").url("/api-docs/openapi.json", ApiDoc::openapi()),
         // )
         .mount("/", routes![catch_all])
+        .register("/", catchers![token_expired, unauthorized, forbidden])
         .ignite().await?
         .launch().await?;
 
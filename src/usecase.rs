@@ -4,7 +4,11 @@ pub mod assignment_record;
 pub mod auth;
 pub mod locker;
 pub mod admin;
+pub mod admin_session;
+pub mod login_attempt;
 pub mod organization;
 pub mod registration;
 pub mod representatives;
-pub mod time;
\ No newline at end of file
+pub mod time;
+pub mod mail_template;
+pub mod mail_outbox;
\ No newline at end of file
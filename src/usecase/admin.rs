@@ -1,21 +1,48 @@
 use std::sync::Arc;
-use crate::adapters::repository::{RepositoryError, admin::AdminRepository};
+use crate::adapters::repository::{RepositoryError, UnitOfWork, admin::AdminRepository};
+use crate::domain::admin::AdminRole;
 use crate::infrastructure::models::Admin;
+use crate::utils::verify_password::compute_password_hash;
 use async_trait::async_trait;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError};
 use rocket::{tokio::task, http::Status};
 
 pub struct AdminUsecaseImpl {
     pub admin_repository: Arc<dyn AdminRepository>,
+    pub unit_of_work: UnitOfWork,
 }
 
 #[async_trait]
 pub trait AdminUsecase: Sync + Send {
     async fn get_by_name<'a>(&self, username: &'a str) -> Result<Admin, Status>;
+    async fn get_all(&self) -> Result<Vec<Admin>, Status>;
+    async fn register(&self, username: &str, password: &str, role: &AdminRole) -> Result<Admin, Status>;
+    async fn update(&self, username: &str, role: &Option<AdminRole>, disabled: &Option<bool>) -> Result<Admin, Status>;
+    async fn update_password(&self, username: &str, password: &str) -> Result<Admin, Status>;
 }
 
 impl AdminUsecaseImpl {
-    pub fn new(admin_repository: Arc<dyn AdminRepository>) -> Self {
-        AdminUsecaseImpl { admin_repository }
+    pub fn new(admin_repository: Arc<dyn AdminRepository>, unit_of_work: UnitOfWork) -> Self {
+        AdminUsecaseImpl { admin_repository, unit_of_work }
+    }
+}
+
+/// パスワードのハッシュ値を計算する、計算に時間がかかるためspawn_blockingで実行する
+async fn hash_password(password: &str) -> Result<String, Status> {
+    let password = password.to_string();
+
+    match task::spawn_blocking(move || {
+        compute_password_hash(password)
+    }).await {
+        Err(e) => {
+            eprintln!("Thread panic in spawn_blocking: {:?}", e);
+            Err(Status::InternalServerError)
+        },
+        Ok(Err(e)) => {
+            eprintln!("Password Hash Error: {:?}", e);
+            Err(Status::InternalServerError)
+        },
+        Ok(Ok(password_hash)) => Ok(password_hash),
     }
 }
 
@@ -36,6 +63,60 @@ impl AdminUsecase for AdminUsecaseImpl {
                 eprintln!("Connection Error: {:?}", e);
                 Err(Status::ServiceUnavailable)
             },
+            Ok(Err(RepositoryError::DieselError(diesel::result::Error::NotFound))) => {
+                Err(Status::NotFound)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(admin)) => Ok(admin)
+        }
+    }
+
+    async fn get_all(&self) -> Result<Vec<Admin>, Status> {
+        let repository = self.admin_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.get_all()
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(admins)) => Ok(admins)
+        }
+    }
+
+    async fn register(&self, username: &str, password: &str, role: &AdminRole) -> Result<Admin, Status> {
+        let username = username.to_string();
+        let password_hash = hash_password(password).await?;
+        let role = *role;
+        let repository = self.admin_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.insert(username, password_hash, role)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            // 同じユーザーネームの管理者が既に存在する
+            Ok(Err(RepositoryError::DieselError(DatabaseError(DatabaseErrorKind::UniqueViolation, _)))) => {
+                Err(Status::Conflict)
+            },
             Ok(Err(RepositoryError::DieselError(e))) => {
                 eprintln!("Repository Error: {:?}", e);
                 Err(Status::InternalServerError)
@@ -43,4 +124,74 @@ impl AdminUsecase for AdminUsecaseImpl {
             Ok(Ok(admin)) => Ok(admin)
         }
     }
-}
\ No newline at end of file
+
+    // superadminをロックしてから更新し、有効なsuperadminがいなくなる変更は行わない
+    async fn update(&self, username: &str, role: &Option<AdminRole>, disabled: &Option<bool>) -> Result<Admin, Status> {
+        let username = username.to_string();
+        let role = *role;
+        let disabled = *disabled;
+        let repository = self.admin_repository.clone();
+        let unit_of_work = self.unit_of_work.clone();
+
+        match task::spawn_blocking(move || {
+            unit_of_work.run(|tx| {
+                let superadmins = repository.get_superadmins_for_update_tx(tx)?;
+                if let Some(target) = superadmins.iter().find(|admin| admin.username == username) {
+                    let remains_enabled = role.unwrap_or(target.role) == AdminRole::Superadmin && !disabled.unwrap_or(target.disabled);
+                    let others_enabled = superadmins.iter().any(|admin| admin.username != username && !admin.disabled);
+                    if !target.disabled && !remains_enabled && !others_enabled {
+                        return Ok(None);
+                    }
+                }
+
+                repository.update_tx(tx, username, None, role, disabled).map(Some)
+            })
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(diesel::result::Error::NotFound))) => {
+                Err(Status::NotFound)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            // 有効なsuperadminがいなくなる
+            Ok(Ok(None)) => Err(Status::Conflict),
+            Ok(Ok(Some(admin))) => Ok(admin),
+        }
+    }
+
+    async fn update_password(&self, username: &str, password: &str) -> Result<Admin, Status> {
+        let username = username.to_string();
+        let password_hash = hash_password(password).await?;
+        let repository = self.admin_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.update(username, Some(password_hash), None, None)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(diesel::result::Error::NotFound))) => {
+                Err(Status::NotFound)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(admin)) => Ok(admin)
+        }
+    }
+}
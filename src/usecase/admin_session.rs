@@ -0,0 +1,241 @@
+use std::sync::Arc;
+use crate::adapters::repository::{RepositoryError, admin_session::AdminSessionRepository};
+use crate::infrastructure::models::AdminSession;
+use crate::utils::env::positive_from_env;
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use dotenv::dotenv;
+use rocket::{tokio::task, http::Status};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+/// ### SessionPolicy
+/// 管理者のセッションの有効期限の設定
+///
+/// idle            : 最後のリクエストからセッションが失効するまでの時間、リクエストのたびに延長する
+///
+/// max_lifetime    : ログインからセッションが失効するまでの最大時間、延長してもこの時間を超えない
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionPolicy {
+    pub idle: TimeDelta,
+    pub max_lifetime: TimeDelta,
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        SessionPolicy {
+            idle: TimeDelta::hours(1),
+            max_lifetime: TimeDelta::hours(12),
+        }
+    }
+}
+
+impl SessionPolicy {
+    /// ### from_env
+    /// ADMIN_SESSION_IDLE_MINUTES、ADMIN_SESSION_MAX_HOURSから設定を読み込む、設定されていない場合は既定値を使用する
+    pub fn from_env() -> Self {
+        dotenv().ok();
+        let default = SessionPolicy::default();
+
+        SessionPolicy {
+            idle: positive_from_env("ADMIN_SESSION_IDLE_MINUTES").map(TimeDelta::minutes).unwrap_or(default.idle),
+            max_lifetime: positive_from_env("ADMIN_SESSION_MAX_HOURS").map(TimeDelta::hours).unwrap_or(default.max_lifetime),
+        }
+    }
+
+    /// ### expires_at
+    /// created_atに開始したセッションにnowにアクセスした場合の有効期限を返す
+    pub fn expires_at(&self, created_at: NaiveDateTime, now: NaiveDateTime) -> NaiveDateTime {
+        (now + self.idle).min(created_at + self.max_lifetime)
+    }
+}
+
+#[derive(Clone)]
+pub struct AdminSessionUsecaseImpl {
+    pub admin_session_repository: Arc<dyn AdminSessionRepository>,
+    pub policy: SessionPolicy,
+}
+
+#[async_trait]
+pub trait AdminSessionUsecase: Sync + Send {
+    async fn start(&self, username: &str, ip_address: Option<String>) -> Result<AdminSession, Status>;
+    async fn refresh(&self, session_id: &Uuid) -> Result<AdminSession, Status>;
+    async fn get_by_id(&self, session_id: &Uuid) -> Result<AdminSession, Status>;
+    async fn get_active(&self, username: Option<String>) -> Result<Vec<AdminSession>, Status>;
+    async fn revoke(&self, session_id: &Uuid) -> Result<(), Status>;
+    async fn revoke_all(&self, username: &str, except: Option<&Uuid>) -> Result<usize, Status>;
+}
+
+impl AdminSessionUsecaseImpl {
+    pub fn new(admin_session_repository: Arc<dyn AdminSessionRepository>, policy: SessionPolicy) -> Self {
+        AdminSessionUsecaseImpl { admin_session_repository, policy }
+    }
+}
+
+#[async_trait]
+impl AdminSessionUsecase for AdminSessionUsecaseImpl {
+    // ログインした管理者のセッションを作成する
+    async fn start(&self, username: &str, ip_address: Option<String>) -> Result<AdminSession, Status> {
+        let username = username.to_string();
+        let now = Utc::now().naive_utc();
+        let expires_at = self.policy.expires_at(now, now);
+        let repository = self.admin_session_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.insert(username, ip_address, expires_at)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(session)) => Ok(session),
+        }
+    }
+
+    // セッションが有効であることを確認し、有効期限を延長する
+    //
+    // 存在しない場合は404、失効または有効期限切れの場合は401を返す
+    async fn refresh(&self, session_id: &Uuid) -> Result<AdminSession, Status> {
+        let mut session = self.get_by_id(session_id).await?;
+
+        let now = Utc::now().naive_utc();
+        if session.revoked_at.is_some() || session.expires_at <= now {
+            return Err(Status::Unauthorized);
+        }
+
+        let session_id = session.session_id;
+        let expires_at = self.policy.expires_at(session.created_at, now);
+        let repository = self.admin_session_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.touch(session_id, now, expires_at)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            // 確認した後に失効した
+            Ok(Ok(0)) => Err(Status::Unauthorized),
+            Ok(Ok(_)) => {
+                session.last_seen_at = now;
+                session.expires_at = expires_at;
+                Ok(session)
+            },
+        }
+    }
+
+    async fn get_by_id(&self, session_id: &Uuid) -> Result<AdminSession, Status> {
+        let session_id = *session_id;
+        let repository = self.admin_session_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.get_by_id(session_id)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(diesel::result::Error::NotFound))) => {
+                Err(Status::NotFound)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(session)) => Ok(session),
+        }
+    }
+
+    // 有効なセッションを最終アクセス時刻の新しい順に取得する、usernameを指定しない場合は全管理者のセッションを取得する
+    async fn get_active(&self, username: Option<String>) -> Result<Vec<AdminSession>, Status> {
+        let now = Utc::now().naive_utc();
+        let repository = self.admin_session_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.get_active(username, now)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(sessions)) => Ok(sessions),
+        }
+    }
+
+    // セッションを失効させる、存在しないまたは既に失効している場合は404を返す
+    async fn revoke(&self, session_id: &Uuid) -> Result<(), Status> {
+        let session_id = *session_id;
+        let repository = self.admin_session_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.revoke(session_id)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(0)) => Err(Status::NotFound),
+            Ok(Ok(_)) => Ok(()),
+        }
+    }
+
+    // 管理者の全てのセッションを失効させ、失効させた件数を返す、exceptを指定した場合はそのセッションを除く
+    async fn revoke_all(&self, username: &str, except: Option<&Uuid>) -> Result<usize, Status> {
+        let username = username.to_string();
+        let except = except.copied();
+        let repository = self.admin_session_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.revoke_by_username(username, except)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(revoked)) => Ok(revoked),
+        }
+    }
+}
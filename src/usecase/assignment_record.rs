@@ -1,40 +1,207 @@
 use std::sync::Arc;
-use crate::domain::assignment::AssignmentInfo;
-use crate::adapters::repository::{RepositoryError, assignment_record::AssignmentRecordRepository};
-use crate::infrastructure::models::{AssignmentRecord, StudentPair};
+use crate::domain::{assignment::{AssignmentInfo, RolloverAssignment}, locker::LockerStatus, student_pair::PairInfo};
+use crate::adapters::repository::{RepositoryError, UnitOfWork, assignment_record::AssignmentRecordRepository, locker::LockerRepository, student::StudentRepository, student_pair::StudentPairRepository};
+use crate::infrastructure::models::{AssignmentRecord, Locker, Student, StudentPair};
+use crate::utils::archive::{to_csv, to_zip};
 use async_trait::async_trait;
-use chrono::{Datelike, Local};
+use diesel::result::{DatabaseErrorKind, DatabaseErrorInformation, Error::DatabaseError};
 use rocket::{tokio::task, http::Status};
 use uuid::Uuid;
 
+/// 同じペアに同じ年度の有効な割り当てを1つに制限する一意インデックス
+const ACTIVE_PAIR_YEAR_INDEX: &str = "assignment_record_active_pair_year_idx";
+
+/// 割り当ての一意制約違反をステータスに変換する
+///
+/// ペアに既に割り当てがある場合は422、ロッカーに既に割り当てがある場合は409を返す
+fn unique_violation_status(info: &(dyn DatabaseErrorInformation + Send + Sync)) -> Status {
+    match info.constraint_name() {
+        Some(ACTIVE_PAIR_YEAR_INDEX) => Status::UnprocessableEntity,
+        _ => Status::Conflict,
+    }
+}
+
 pub struct AssignmentRecordUsecaseImpl {
     pub assignment_record_repository: Arc<dyn AssignmentRecordRepository>,
+    pub student_repository: Arc<dyn StudentRepository>,
+    pub student_pair_repository: Arc<dyn StudentPairRepository>,
+    pub locker_repository: Arc<dyn LockerRepository>,
+    pub unit_of_work: UnitOfWork,
+}
+
+/// ### YearRollover
+///
+/// 年度切り替えの結果
+///
+/// records         : 論理削除した(dry-runの場合は論理削除される)割り当て
+///
+/// pairs           : 対象年度のstudentpair
+///
+/// assignments     : 論理削除した割り当てとペアの学籍番号の一覧、ロッカー番号順
+///
+/// reset_lockers   : 割り当てがなくなり空きに戻したロッカーの数、故障中などのロッカーは含まない
+///
+/// archive         : 対象年度のアーカイブ(ZIP)、dry-runの場合はNone
+pub struct YearRollover {
+    pub records: Vec<AssignmentRecord>,
+    pub pairs: Vec<StudentPair>,
+    pub assignments: Vec<RolloverAssignment>,
+    pub reset_lockers: usize,
+    pub archive: Option<Vec<u8>>,
+}
+
+/// 論理削除する割り当てとペアの学籍番号の一覧を作成する
+fn rollover_assignments(records: &[AssignmentRecord], pairs: &[StudentPair]) -> Result<Vec<RolloverAssignment>, RepositoryError> {
+    let mut assignments = Vec::new();
+    for record in records {
+        let pair = pairs.iter()
+            .find(|pair| pair.pair_id == record.pair_id)
+            .ok_or(RepositoryError::DieselError(diesel::result::Error::NotFound))?;
+        assignments.push(RolloverAssignment {
+            locker_id: record.locker_id.clone(),
+            student_id1: pair.student_id1.clone(),
+            student_id2: pair.student_id2.clone(),
+        });
+    }
+    assignments.sort_by(|lt, rt| lt.locker_id.cmp(&rt.locker_id));
+
+    Ok(assignments)
+}
+
+/// 割り当てがなくなり空きに戻るロッカーの数を数える
+fn reset_lockers(records: &[AssignmentRecord], lockers: &[Locker]) -> usize {
+    lockers.iter()
+        .filter(|locker| locker.status == LockerStatus::Vacant)
+        .filter(|locker| records.iter().any(|record| record.locker_id == locker.locker_id))
+        .count()
+}
+
+/// 対象年度のstudent、studentpair、割り当て、ロッカーをまとめたアーカイブを作成する
+fn rollover_archive(students: &[Student], rollover: &YearRollover, lockers: &[Locker]) -> Result<Vec<u8>, Status> {
+    let students = students.iter()
+        .filter(|student| rollover.pairs.iter().any(|pair| pair.student_id1 == student.student_id || pair.student_id2 == student.student_id))
+        .collect::<Vec<_>>();
+
+    let files: Vec<(&str, Vec<u8>)> = vec![
+        ("students.csv", to_csv(&students)?),
+        ("student_pairs.csv", to_csv(&rollover.pairs)?),
+        ("assignment_records.csv", to_csv(&rollover.records)?),
+        ("lockers.csv", to_csv(lockers)?),
+        ("report.csv", to_csv(&rollover.assignments)?),
+    ];
+    to_zip(files)
 }
 
 #[async_trait]
 pub trait AssignmentRecordUsecase: Sync + Send {
-    async fn register(&self, student_pair: &StudentPair, assignment: &AssignmentInfo) -> Result<AssignmentRecord, Status>;
+    async fn register(&self, student_pair: &StudentPair, assignment: &AssignmentInfo, year: &i32) -> Result<AssignmentRecord, Status>;
+    async fn register_with_pair(&self, student_pair: &PairInfo, locker_id: &str, year: &i32) -> Result<AssignmentRecord, Status>;
+    async fn reassign(&self, prev_locker_id: &str, locker_id: &str, year: &i32, prev_status: &LockerStatus) -> Result<AssignmentRecord, Status>;
+    async fn cancel(&self, record: &AssignmentRecord) -> Result<AssignmentRecord, Status>;
+    async fn release_year(&self, year: &i32) -> Result<usize, Status>;
+    async fn rollover(&self, year: &i32, dry_run: bool) -> Result<YearRollover, Status>;
     async fn get_all(&self) -> Result<Vec<AssignmentRecord>, Status>;
     async fn get(&self, year: &i32, floor: Option<i8>, pair_id: &Uuid) -> Result<Vec<AssignmentRecord>, Status>;
-    async fn get_by_pair_id(&self, pair_id: &Uuid) -> Result<Option<AssignmentRecord>, Status>;
+    async fn get_by_pair_id_and_year(&self, pair_id: &Uuid, year: &i32) -> Result<Option<AssignmentRecord>, Status>;
 }
 
 impl AssignmentRecordUsecaseImpl {
-    pub fn new(assignment_record_repository: Arc<dyn AssignmentRecordRepository>) -> Self {
-        AssignmentRecordUsecaseImpl { assignment_record_repository }
+    pub fn new(assignment_record_repository: Arc<dyn AssignmentRecordRepository>, student_repository: Arc<dyn StudentRepository>, student_pair_repository: Arc<dyn StudentPairRepository>, locker_repository: Arc<dyn LockerRepository>, unit_of_work: UnitOfWork) -> Self {
+        AssignmentRecordUsecaseImpl { assignment_record_repository, student_repository, student_pair_repository, locker_repository, unit_of_work }
     }
 }
 
 #[async_trait]
 impl AssignmentRecordUsecase for AssignmentRecordUsecaseImpl {
-    async fn register(&self, student_pair: &StudentPair, assignment: &AssignmentInfo) -> Result<AssignmentRecord, Status> {
+    async fn register(&self, student_pair: &StudentPair, assignment: &AssignmentInfo, year: &i32) -> Result<AssignmentRecord, Status> {
         let pair_id = student_pair.pair_id;
         let locker_id = assignment.locker_id.clone();
-        let year = Local::now().year();
+        let year = *year;
+        let repository = self.assignment_record_repository.clone();
+
+        // ロッカーの空き確認、割り当て登録を1トランザクションで実行
+        match task::spawn_blocking(move || {
+             repository.assign(pair_id, locker_id, year)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(DatabaseError(DatabaseErrorKind::UniqueViolation, info)))) => {
+                Err(unique_violation_status(info.as_ref()))
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            // ロッカーが空きでない
+            Ok(Ok(None)) => Err(Status::Conflict),
+            Ok(Ok(Some(assignment))) => Ok(assignment),
+        }
+    }
+
+    // 管理者用、student、studentpairの登録とロッカーの割り当てを1トランザクションで実行
+    async fn register_with_pair(&self, student_pair: &PairInfo, locker_id: &str, year: &i32) -> Result<AssignmentRecord, Status> {
+        let year = *year;
+        let locker_id = locker_id.to_string();
+        let student_pair = student_pair.clone();
+        let unit_of_work = self.unit_of_work.clone();
+        let student_repository = self.student_repository.clone();
+        let student_pair_repository = self.student_pair_repository.clone();
+        let assignment_record_repository = self.assignment_record_repository.clone();
+
+        match task::spawn_blocking(move || {
+            unit_of_work.run(|tx| {
+                let main_user = student_pair.main_user;
+                let co_user = student_pair.co_user;
+
+                student_repository.insert_tx(tx, main_user.student_id.clone(), main_user.family_name, main_user.given_name)?;
+                student_repository.insert_tx(tx, co_user.student_id.clone(), co_user.family_name, co_user.given_name)?;
+                let pair = student_pair_repository.insert_tx(tx, main_user.student_id, co_user.student_id, year)?;
+
+                // ロッカーが空きでない場合は登録したstudentpairごとロールバックする
+                match assignment_record_repository.assign_tx(tx, pair.pair_id, locker_id, year)? {
+                    Some(record) => Ok(record),
+                    None => Err(RepositoryError::DieselError(diesel::result::Error::RollbackTransaction)),
+                }
+            })
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            // ロッカーが空きでない
+            Ok(Err(RepositoryError::DieselError(diesel::result::Error::RollbackTransaction))) => Err(Status::Conflict),
+            Ok(Err(RepositoryError::DieselError(DatabaseError(DatabaseErrorKind::UniqueViolation, info)))) => {
+                Err(unique_violation_status(info.as_ref()))
+            },
+            Ok(Err(RepositoryError::DieselError(diesel::result::Error::NotFound))) => Err(Status::NotFound),
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(record)) => Ok(record),
+        }
+    }
+
+    async fn reassign(&self, prev_locker_id: &str, locker_id: &str, year: &i32, prev_status: &LockerStatus) -> Result<AssignmentRecord, Status> {
+        let prev_locker_id = prev_locker_id.to_string();
+        let locker_id = locker_id.to_string();
+        let prev_status = *prev_status;
+        let year = *year;
         let repository = self.assignment_record_repository.clone();
 
+        // 割り当ての付け替え、付け替え元のステータス更新を1トランザクションで実行
         match task::spawn_blocking(move || {
-             repository.insert(pair_id, locker_id, year)
+            repository.reassign(prev_locker_id, locker_id, year, prev_status)
         }).await {
             Err(e) => {
                 eprintln!("Thread panic in spawn_blocking: {:?}", e);
@@ -44,11 +211,121 @@ impl AssignmentRecordUsecase for AssignmentRecordUsecaseImpl {
                 eprintln!("Connection Error: {:?}", e);
                 Err(Status::ServiceUnavailable)
             },
+            // 割り当てまたは付け替え先のロッカーが存在しない
+            Ok(Err(RepositoryError::DieselError(diesel::result::Error::NotFound))) => Err(Status::NotFound),
+            Ok(Err(RepositoryError::DieselError(DatabaseError(DatabaseErrorKind::UniqueViolation, _)))) => {
+                Err(Status::Conflict)
+            },
             Ok(Err(RepositoryError::DieselError(e))) => {
                 eprintln!("Repository Error: {:?}", e);
                 Err(Status::InternalServerError)
             },
-            Ok(Ok(assignment)) => Ok(assignment),
+            // 付け替え先のロッカーが空きでない
+            Ok(Ok(None)) => Err(Status::Conflict),
+            Ok(Ok(Some(record))) => Ok(record),
+        }
+    }
+
+    async fn cancel(&self, record: &AssignmentRecord) -> Result<AssignmentRecord, Status> {
+        let record_id = record.record_id;
+        let repository = self.assignment_record_repository.clone();
+
+        // 割り当ての論理削除
+        match task::spawn_blocking(move || {
+            repository.release(record_id)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            // 既に取り消し済み
+            Ok(Ok(None)) => Err(Status::NotFound),
+            Ok(Ok(Some(record))) => Ok(record),
+        }
+    }
+
+    // 指定年度の有効な割り当てをすべて論理削除し、ロッカーを空きに戻す
+    async fn release_year(&self, year: &i32) -> Result<usize, Status> {
+        let year = *year;
+        let repository = self.assignment_record_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.close_year(year)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(records)) => Ok(records.len()),
+        }
+    }
+
+    // 年度切り替え、指定年度の割り当ての論理削除とアーカイブの作成を1トランザクションで実行
+    // 対象の割り当てはロックしてから読み込み、アーカイブの作成に失敗した場合は論理削除をロールバックする
+    async fn rollover(&self, year: &i32, dry_run: bool) -> Result<YearRollover, Status> {
+        let year = *year;
+        let unit_of_work = self.unit_of_work.clone();
+        let assignment_record_repository = self.assignment_record_repository.clone();
+        let student_repository = self.student_repository.clone();
+        let student_pair_repository = self.student_pair_repository.clone();
+        let locker_repository = self.locker_repository.clone();
+
+        match task::spawn_blocking(move || -> Result<YearRollover, RepositoryError> {
+            unit_of_work.run(|tx| {
+                let records = assignment_record_repository.get_by_year_for_update_tx(tx, year)?;
+                let pairs = student_pair_repository.get_by_year_tx(tx, year)?;
+                let lockers = locker_repository.get_all_tx(tx)?;
+
+                // dry-runの場合は対象の取得のみ行う
+                if dry_run {
+                    let assignments = rollover_assignments(&records, &pairs)?;
+                    let reset_lockers = reset_lockers(&records, &lockers);
+                    return Ok(YearRollover { records, pairs, assignments, reset_lockers, archive: None });
+                }
+
+                let students = student_repository.get_all_tx(tx)?;
+                let records = assignment_record_repository.close_year_tx(tx, year)?;
+                let assignments = rollover_assignments(&records, &pairs)?;
+                let reset_lockers = reset_lockers(&records, &lockers);
+                let mut rollover = YearRollover { records, pairs, assignments, reset_lockers, archive: None };
+
+                match rollover_archive(&students, &rollover, &lockers) {
+                    Ok(archive) => rollover.archive = Some(archive),
+                    Err(_) => return Err(RepositoryError::DieselError(diesel::result::Error::RollbackTransaction)),
+                }
+
+                Ok(rollover)
+            })
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(result)) => Ok(result),
         }
     }
 
@@ -102,8 +379,8 @@ impl AssignmentRecordUsecase for AssignmentRecordUsecaseImpl {
         }
     }
 
-    async fn get_by_pair_id(&self, pair_id: &Uuid) -> Result<Option<AssignmentRecord>, Status> {
-        let year = Local::now().year();
+    async fn get_by_pair_id_and_year(&self, pair_id: &Uuid, year: &i32) -> Result<Option<AssignmentRecord>, Status> {
+        let year = *year;
         let pair_id = *pair_id;
         let repository = self.assignment_record_repository.clone();
 
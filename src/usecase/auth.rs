@@ -1,51 +1,110 @@
-use std::sync::Arc;
-use std::env;
-use crate::domain::{student::UserInfo, circle::OrganizationInfo};
-use crate::adapters::repository::{RepositoryError, auth::AuthRepository, circle_auth_info::CircleAuthInfoRepository, locker_auth_info::LockerAuthInfoRepository};
-use crate::infrastructure::models::{Auth, CircleAuthInfo, LockerAuthInfo};
-use crate::utils::token::generate_token;
-
-use dotenv::dotenv;
+use std::sync::{Arc, Mutex};
+use crate::domain::{student::UserInfo, student_pair::PairInfo, circle::OrganizationInfo};
+use crate::adapters::repository::{RepositoryError, UnitOfWork, auth::AuthRepository, circle_auth_info::CircleAuthInfoRepository, locker_auth_info::LockerAuthInfoRepository, student::StudentRepository, student_pair::StudentPairRepository};
+use crate::infrastructure::models::{Auth, CircleAuthInfo, LockerAuthInfo, NewLockerAuthInfo};
+use crate::utils::token::{generate_token, hash_token, verify_token_hash, TokenTtl};
+
 use uuid::Uuid;
-use lettre::message::header::ContentType;
-use lettre::{Message, SmtpTransport, Transport, transport::smtp::{authentication::Credentials, client::{TlsParameters, Tls}}};
 use rocket::{tokio::task, http::Status};
 use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// ### AuthSweepReport
+/// 放置された認証レコードの削除結果
+///
+/// ran_at              : 実行時刻
+///
+/// auth                : 削除したauthの件数
+///
+/// locker_auth_info    : 削除したlocker_auth_infoの件数
+///
+/// circle_auth_info    : 削除したcircle_auth_infoの件数
+#[derive(Clone)]
+pub struct AuthSweepReport {
+    pub ran_at: DateTime<Utc>,
+    pub auth: usize,
+    pub locker_auth_info: usize,
+    pub circle_auth_info: usize,
+}
 
+#[derive(Clone)]
 pub struct AuthUsecaseImpl {
      pub auth_repository: Arc<dyn AuthRepository>,
      pub locker_auth_info_repository: Arc<dyn LockerAuthInfoRepository>,
      pub circle_auth_info_repository: Arc<dyn CircleAuthInfoRepository>,
+     pub student_repository: Arc<dyn StudentRepository>,
+     pub student_pair_repository: Arc<dyn StudentPairRepository>,
+     pub unit_of_work: UnitOfWork,
+     pub token_ttl: TokenTtl,
+     pub last_sweep: Arc<Mutex<Option<AuthSweepReport>>>,
 }
 
 #[async_trait]
 pub trait AuthUsecase: Sync + Send {
     async fn locker_register(&self, main_user: &UserInfo, co_user: &UserInfo, phase: &str, is_same: bool) -> Result<Auth, Status>;
     async fn circle_register(&self, organization: &OrganizationInfo, phase: &str, is_same: bool) -> Result<Auth, Status>;
-    async fn mail_sender(&self, user_address: String, content: String, subject: &str) -> Result<(), Status>;
-    async fn mail_sender_local(&self, user_address: String, content: String, subject: &str) -> Result<(), Status>;
-    async fn token_check(&self, token: String, is_main: bool) -> Result<Auth, Status>;
+    async fn locker_cancel_register(&self, user: &UserInfo, partner: &UserInfo) -> Result<Auth, Status>;
+    async fn locker_co_auth(&self, auth_id: &Uuid, student_pair: &PairInfo, year: &i32) -> Result<Auth, Status>;
+    async fn token_check(&self, token: String, phase: &str, is_main: bool) -> Result<Auth, Status>;
+    async fn token_verify(&self, token: String, phase: &str, is_main: bool) -> Result<Auth, Status>;
+    async fn reissue_token(&self, auth_id: &Uuid, is_main: bool) -> Result<String, Status>;
     async fn get_locker_auth_info(&self, auth_id: &Uuid) -> Result<LockerAuthInfo, Status>;
     async fn get_circle_auth_info(&self, auth_id:&Uuid) -> Result<CircleAuthInfo, Status>;
     async fn update_phase(&self, auth_id: &Uuid, phase: String) -> Result<usize, Status>;
+    async fn locker_resend(&self, student_id: &str) -> Result<(Auth, LockerAuthInfo), Status>;
+    async fn circle_resend(&self, student_id: &str) -> Result<(Auth, CircleAuthInfo), Status>;
     async fn delete(&self, auth_id: &Uuid) -> Result<usize, Status>;
+    async fn sweep_stale(&self, max_age: TimeDelta) -> Result<AuthSweepReport, Status>;
+    fn last_sweep(&self) -> Option<AuthSweepReport>;
 }
 
 impl AuthUsecaseImpl {
-    pub fn new(auth_repository: Arc<dyn AuthRepository>, locker_auth_info_repository: Arc<dyn LockerAuthInfoRepository>, circle_auth_info_repository: Arc<dyn CircleAuthInfoRepository>) -> Self {
-        AuthUsecaseImpl { auth_repository, locker_auth_info_repository, circle_auth_info_repository}
+    pub fn new(auth_repository: Arc<dyn AuthRepository>, locker_auth_info_repository: Arc<dyn LockerAuthInfoRepository>, circle_auth_info_repository: Arc<dyn CircleAuthInfoRepository>, student_repository: Arc<dyn StudentRepository>, student_pair_repository: Arc<dyn StudentPairRepository>, unit_of_work: UnitOfWork, token_ttl: TokenTtl) -> Self {
+        AuthUsecaseImpl { auth_repository, locker_auth_info_repository, circle_auth_info_repository, student_repository, student_pair_repository, unit_of_work, token_ttl, last_sweep: Arc::new(Mutex::new(None)) }
+    }
+
+    // 前回の送信からresend_intervalが経過している場合のみtokenを再発行し、経過していなければ429を返す
+    // 経過の確認と更新を1つのUPDATEで行い、同時に再送信された場合も1回しか再発行しない
+    async fn resend_token(&self, auth_id: &Uuid, is_main: bool) -> Result<String, Status> {
+        let token = generate_token();
+        let token_hash = hash_token(&token);
+        let threshold = Utc::now().naive_utc() - self.token_ttl.resend_interval;
+        let auth_id = *auth_id;
+        let repository = self.auth_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.update_token_if_idle(auth_id, is_main, token_hash, threshold)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(0)) => Err(Status::TooManyRequests),
+            Ok(Ok(_)) => Ok(token),
+        }
     }
 }
 
 #[async_trait]
 impl AuthUsecase for AuthUsecaseImpl {
     // ロッカー用、tokenの生成、DBへの登録
+    // DBにはtokenのハッシュ値のみを保存し、返り値のAuthには発行したtokenを格納する
     async fn locker_register(&self, main_user: &UserInfo, co_user: &UserInfo, phase: &str, is_same: bool) -> Result<Auth, Status> {
         let main_token = generate_token();
         let mut co_token = generate_token();
         if is_same {
             co_token.clone_from(&main_token);
         }
+        let main_token_hash = hash_token(&main_token);
+        let co_token_hash = hash_token(&co_token);
         let phase = phase.to_string();
         let main_user = main_user.clone();
         let co_user = co_user.clone();
@@ -53,7 +112,7 @@ impl AuthUsecase for AuthUsecaseImpl {
         let locker_auth_info_repository = self.locker_auth_info_repository.clone();
 
         let auth = match task::spawn_blocking(move || {
-            auth_repository.insert(main_token, co_token, phase)
+            auth_repository.insert(main_token_hash, co_token_hash, phase)
         }).await {
             Err(e) => {
                 eprintln!("Thread panic in spawn_blocking: {:?}", e);
@@ -91,17 +150,20 @@ impl AuthUsecase for AuthUsecaseImpl {
                 eprintln!("Repository Error: {:?}", e);
                 return Err(Status::InternalServerError)
             },
-            Ok(_) => {return Ok(auth)},
+            Ok(_) => {return Ok(Auth { main_auth_token: main_token, co_auth_token: co_token, ..auth })},
         }
     }
 
     // 団体登録用、tokenの生成、DBへの登録
+    // DBにはtokenのハッシュ値のみを保存し、返り値のAuthには発行したtokenを格納する
     async fn circle_register(&self, organization: &OrganizationInfo, phase: &str, is_same: bool) -> Result<Auth, Status> {
         let main_token = generate_token();
         let mut co_token = generate_token();
         if is_same {
             co_token.clone_from(&main_token);
         }
+        let main_token_hash = hash_token(&main_token);
+        let co_token_hash = hash_token(&co_token);
         let phase = phase.to_string();
         let organization = organization.clone();
         let auth_repository = self.auth_repository.clone();
@@ -109,7 +171,7 @@ impl AuthUsecase for AuthUsecaseImpl {
 
 
         let auth = match task::spawn_blocking(move || {
-            auth_repository.insert(main_token, co_token, phase)
+            auth_repository.insert(main_token_hash, co_token_hash, phase)
         }).await {
             Err(e) => {
                 eprintln!("Thread panic in spawn_blocking: {:?}", e);
@@ -157,89 +219,135 @@ impl AuthUsecase for AuthUsecaseImpl {
                 eprintln!("Repository Error: {:?}", e);
                 return Err(Status::InternalServerError)
             },
-            Ok(_) => {return Ok(auth)},
+            Ok(_) => {return Ok(Auth { main_auth_token: main_token, co_auth_token: co_token, ..auth })},
+        }
+    }
+
+    // ロッカー用、登録取り消し用tokenの発行
+    // 取り消し中のレコードがある場合は新たに作成せずtokenを再発行し、resend_intervalが経過していなければ429を返す
+    async fn locker_cancel_register(&self, user: &UserInfo, partner: &UserInfo) -> Result<Auth, Status> {
+        let student_id = user.student_id.clone();
+        let repository = self.locker_auth_info_repository.clone();
+
+        let pending = match task::spawn_blocking(move || {
+            repository.get_cancel_by_student_id(student_id)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                return Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                return Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                return Err(Status::InternalServerError)
+            },
+            Ok(Ok(pending)) => pending,
+        };
+
+        match pending {
+            Some((auth, _)) => {
+                let token = self.resend_token(&auth.auth_id, true).await?;
+                Ok(Auth { main_auth_token: token, ..auth })
+            },
+            None => self.locker_register(user, partner, "cancel", true).await,
         }
     }
 
-    async fn mail_sender(&self, user_address: String, content: String, subject: &str) -> Result<(), Status> {
-        // 環境変数の読み取り
-        dotenv().ok();
-        let sender_address = env::var("SENDER_MAIL_ADDRESS").expect("SENDER_MAIL_ADDRESS must be set.");
-        let smtp_server = env::var("SMTP_SERVER").expect("SMTP_SERVER must be set.");
-
-        let email = Message::builder()
-            .from(
-                format!("Noreply <{}>", sender_address)
-                    .parse()
-                    .map_err(|_| Status::InternalServerError)?,
-            )
-            .to(format!("User <{}>", user_address)
-                .parse()
-                .map_err(|_| Status::InternalServerError)?)
-            .subject(subject)
-            .header(ContentType::TEXT_PLAIN)
-            .body(content)
-            .map_err(|_| Status::InternalServerError)?;
-
-        // SMTPサーバーに接続する
-        let mailer = SmtpTransport::builder_dangerous(smtp_server.as_str())
-            .port(25)
-            .build();
-
-        // メール送信
-        mailer.send(&email).map_err(|_| Status::InternalServerError)?;
-
-        Ok(())
+    // ロッカー用、共同利用者の認証完了時の登録処理
+    // couserの登録、studentpairの登録、認証完了用レコードの発行、認証レコードの削除を1つのトランザクションで行う
+    async fn locker_co_auth(&self, auth_id: &Uuid, student_pair: &PairInfo, year: &i32) -> Result<Auth, Status> {
+        let token = generate_token();
+        let token_hash = hash_token(&token);
+        let phase = String::from("auth_check");
+        let year = *year;
+        let prev_auth_id = *auth_id;
+        let student_pair = student_pair.clone();
+        let unit_of_work = self.unit_of_work.clone();
+        let student_repository = self.student_repository.clone();
+        let student_pair_repository = self.student_pair_repository.clone();
+        let auth_repository = self.auth_repository.clone();
+        let locker_auth_info_repository = self.locker_auth_info_repository.clone();
+
+        match task::spawn_blocking(move || {
+            unit_of_work.run(|tx| {
+                let main_user = student_pair.main_user;
+                let co_user = student_pair.co_user;
+
+                student_repository.insert_tx(tx, co_user.student_id.clone(), co_user.family_name.clone(), co_user.given_name.clone())?;
+                student_pair_repository.insert_tx(tx, main_user.student_id.clone(), co_user.student_id.clone(), year)?;
+
+                let auth = auth_repository.insert_tx(tx, token_hash.clone(), token_hash, phase)?;
+                locker_auth_info_repository.insert_tx(tx, &NewLockerAuthInfo {
+                    auth_id: &auth.auth_id,
+                    main_student_id: &main_user.student_id,
+                    main_family_name: &main_user.family_name,
+                    main_given_name: &main_user.given_name,
+                    co_student_id: &co_user.student_id,
+                    co_family_name: &co_user.family_name,
+                    co_given_name: &co_user.given_name,
+                })?;
+
+                locker_auth_info_repository.delete_tx(tx, prev_auth_id)?;
+                auth_repository.delete_tx(tx, prev_auth_id)?;
+
+                Ok(auth)
+            })
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(auth)) => Ok(Auth { main_auth_token: token.clone(), co_auth_token: token, ..auth }),
+        }
     }
 
-    async fn mail_sender_local(&self, user_address: String, content: String, subject: &str) -> Result<(), Status> {
-        // 環境変数の読み取り
-        dotenv().ok();
-        let sender_address = env::var("SENDER_MAIL_ADDRESS").expect("SENDER_MAIL_ADDRESS must be set.");
-        let appkey = env::var("MAIL_APP_KEY").expect("MAIL_APP_KEY must be set.");
-
-        let email = Message::builder()
-            .from(
-                format!("Developer <{}>", sender_address)
-                    .parse()
-                    .map_err(|_| Status::InternalServerError)?,
-            )
-            .to(format!("User <{}>", user_address)
-                .parse()
-                .map_err(|_| Status::InternalServerError)?)
-            .subject(subject)
-            .header(ContentType::TEXT_PLAIN)
-            .body(content)
-            .map_err(|_| Status::InternalServerError)?;
-
-        // SMTP認証情報
-        let creds = Credentials::new(sender_address.to_owned(), appkey.to_owned());
-
-        // TLSパラメータを生成
-        let tls_parameters = TlsParameters::builder("smtp.gmail.com".to_string())
-        .build()
-        .map_err(|_| Status::InternalServerError)?;
-
-        // Gmailにsmtp接続する
-        let mailer = SmtpTransport::relay("smtp.gmail.com")
-            .map_err(|_| Status::InternalServerError)?
-            .port(587)
-            .tls(Tls::Required(tls_parameters)) 
-            .credentials(creds)
-            .build();
-
-        // メール送信
-        mailer.send(&email).map_err(|_| Status::InternalServerError)?;
-
-        Ok(())
+    // tokenを検証し、使用済みにする
+    // phaseが一致しない場合はtokenを使用済みにせず400を返す
+    async fn token_check(&self, token: String, phase: &str, is_main: bool) -> Result<Auth, Status> {
+        let token_hash = hash_token(&token);
+        let auth = self.token_verify(token, phase, is_main).await?;
+
+        // tokenを使用済みにする、既に使用されていた場合は401を返す
+        let auth_id = auth.auth_id;
+        let repository = self.auth_repository.clone();
+        match task::spawn_blocking(move || {
+            repository.consume_token(auth_id, is_main, token_hash)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(0)) => Err(Status::Unauthorized),
+            Ok(Ok(_)) => Ok(auth),
+        }
     }
 
-    async fn token_check(&self, token: String, is_main: bool) -> Result<Auth, Status> {
-        let verify_token = token.clone();
+    // tokenを検証する、使用済みにはしないため有効期限まで繰り返し使用できる
+    async fn token_verify(&self, token: String, phase: &str, is_main: bool) -> Result<Auth, Status> {
+        let token_hash = hash_token(&token);
         let repository = self.auth_repository.clone();
 
         let auth = match task::spawn_blocking(move || {
-            repository.get_by_token(token)
+            repository.get_by_token(token_hash)
         }).await {
             Err(e) => {
                 eprintln!("Thread panic in spawn_blocking: {:?}", e);
@@ -249,20 +357,60 @@ impl AuthUsecase for AuthUsecaseImpl {
                 eprintln!("Connection Error: {:?}", e);
                 return Err(Status::ServiceUnavailable)
             },
+            // 存在しない、または使用済みのtoken
             Ok(Err(RepositoryError::DieselError(diesel::result::Error::NotFound))) => {
-                return Err(Status::InternalServerError)
+                return Err(Status::Unauthorized)
             },
             Ok(Err(RepositoryError::DieselError(e))) => {
                 eprintln!("Repository Error: {:?}", e);
-                return Err(Status::Unauthorized)
+                return Err(Status::InternalServerError)
             },
             Ok(Ok(auth)) => auth,
         };
 
-        if (is_main && auth.main_auth_token == verify_token) || (!is_main && auth.co_auth_token == verify_token) {
-            Ok(auth)
-        } else {
-            Err(Status::Unauthorized)
+        let stored_hash = if is_main { &auth.main_auth_token } else { &auth.co_auth_token };
+        if !verify_token_hash(&token, stored_hash) {
+            return Err(Status::Unauthorized)
+        }
+
+        if auth.phase != phase {
+            return Err(Status::BadRequest)
+        }
+
+        // phaseが更新されてから有効期限を過ぎたtokenは410を返す
+        if let Some(ttl) = self.token_ttl.get(&auth.phase) {
+            if auth.updated_at + ttl < Utc::now().naive_utc() {
+                return Err(Status::Gone)
+            }
+        }
+
+        Ok(auth)
+    }
+
+    // tokenを再発行し、有効期限の起点を現在時刻にする
+    async fn reissue_token(&self, auth_id: &Uuid, is_main: bool) -> Result<String, Status> {
+        let token = generate_token();
+        let token_hash = hash_token(&token);
+        let auth_id = *auth_id;
+        let repository = self.auth_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.update_token(auth_id, is_main, token_hash)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(0)) => Err(Status::NotFound),
+            Ok(Ok(_)) => Ok(token),
         }
     }
 
@@ -335,6 +483,78 @@ impl AuthUsecase for AuthUsecaseImpl {
         }
     }
 
+    // ロッカー用、認証中のレコードを取得し再送信するtokenを再発行する
+    async fn locker_resend(&self, student_id: &str) -> Result<(Auth, LockerAuthInfo), Status> {
+        let student_id = student_id.to_string();
+        let repository = self.locker_auth_info_repository.clone();
+
+        let (auth, auth_info) = match task::spawn_blocking(move || {
+            repository.get_pending_by_student_id(student_id)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                return Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                return Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                return Err(Status::InternalServerError)
+            },
+            Ok(Ok(None)) => return Err(Status::NotFound),
+            Ok(Ok(Some(pending))) => pending,
+        };
+
+        // 現在のphaseで使用するtokenを再発行する
+        let is_main = auth.phase != "co_auth";
+        let token = self.resend_token(&auth.auth_id, is_main).await?;
+        let auth = if is_main {
+            Auth { main_auth_token: token, ..auth }
+        } else {
+            Auth { co_auth_token: token, ..auth }
+        };
+
+        Ok((auth, auth_info))
+    }
+
+    // 団体登録用、認証中のレコードを取得し再送信するtokenを再発行する
+    async fn circle_resend(&self, student_id: &str) -> Result<(Auth, CircleAuthInfo), Status> {
+        let student_id = student_id.to_string();
+        let repository = self.circle_auth_info_repository.clone();
+
+        let (auth, auth_info) = match task::spawn_blocking(move || {
+            repository.get_pending_by_student_id(student_id)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                return Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                return Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                return Err(Status::InternalServerError)
+            },
+            Ok(Ok(None)) => return Err(Status::NotFound),
+            Ok(Ok(Some(pending))) => pending,
+        };
+
+        // 現在のphaseで使用するtokenを再発行する
+        let is_main = auth.phase != "co_auth";
+        let token = self.resend_token(&auth.auth_id, is_main).await?;
+        let auth = if is_main {
+            Auth { main_auth_token: token, ..auth }
+        } else {
+            Auth { co_auth_token: token, ..auth }
+        };
+
+        Ok((auth, auth_info))
+    }
+
     async  fn delete(&self, auth_id: &Uuid) -> Result<usize, Status> {
         let locker_auth_id = *auth_id;
         let circle_auth_id = *auth_id;
@@ -397,4 +617,49 @@ impl AuthUsecase for AuthUsecaseImpl {
             Ok(Ok(result)) => Ok(result),
         }
     }
-}
\ No newline at end of file
+
+    // 最終更新からmax_ageを過ぎた認証レコードを削除する
+    async fn sweep_stale(&self, max_age: TimeDelta) -> Result<AuthSweepReport, Status> {
+        let ran_at = Utc::now();
+        let threshold = (ran_at - max_age).naive_utc();
+        let unit_of_work = self.unit_of_work.clone();
+        let auth_repository = self.auth_repository.clone();
+        let locker_auth_info_repository = self.locker_auth_info_repository.clone();
+        let circle_auth_info_repository = self.circle_auth_info_repository.clone();
+
+        let report = match task::spawn_blocking(move || {
+            unit_of_work.run(|tx| {
+                // authを参照しているレコードから削除する
+                let locker_auth_info = locker_auth_info_repository.delete_stale_tx(tx, threshold)?;
+                let circle_auth_info = circle_auth_info_repository.delete_stale_tx(tx, threshold)?;
+                let auth = auth_repository.delete_stale_tx(tx, threshold)?;
+
+                Ok(AuthSweepReport { ran_at, auth, locker_auth_info, circle_auth_info })
+            })
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                return Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                return Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                return Err(Status::InternalServerError)
+            },
+            Ok(Ok(report)) => report,
+        };
+
+        if let Ok(mut last_sweep) = self.last_sweep.lock() {
+            *last_sweep = Some(report.clone());
+        }
+
+        Ok(report)
+    }
+
+    fn last_sweep(&self) -> Option<AuthSweepReport> {
+        self.last_sweep.lock().ok().and_then(|last_sweep| last_sweep.clone())
+    }
+}
@@ -1,25 +1,67 @@
 use std::sync::Arc;
-use crate::adapters::repository::{RepositoryError, locker::LockerRepository};
+use crate::domain::locker::{LockerChange, LockerImportDiff, LockerInfo, LockerStatus};
+use crate::adapters::repository::{RepositoryError, UnitOfWork, locker::LockerRepository};
 use crate::infrastructure::models::Locker;
 use async_trait::async_trait;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError};
 use rocket::{tokio::task, http::Status};
 
 pub struct LockerUsecaseImpl {
     pub locker_repository: Arc<dyn LockerRepository>,
+    pub unit_of_work: UnitOfWork,
 }
 
 #[async_trait]
 pub trait LockerUsecase: Sync + Send {
     async fn get_all(&self) -> Result<Vec<Locker>, Status>;
     async fn get_by_id(&self, locker_id: &str) -> Result<Locker, Status>;
-    async fn get_by_floor(&self, floor: &Option<i8>) -> Result<Vec<Locker>, Status>;
-    async fn update_status(&self, locker_id: &str, status: &str) -> Result<usize, Status>;
-    async fn reset_status(&self) -> Result<usize, Status>;
+    async fn get_by_floor(&self, floor: &Option<i8>, year: &i32) -> Result<Vec<Locker>, Status>;
+    async fn update_status(&self, locker_id: &str, status: &LockerStatus) -> Result<usize, Status>;
+    async fn search(&self, floor: &Option<i8>, location: &Option<String>, status: &Option<LockerStatus>, year: &i32) -> Result<Vec<Locker>, Status>;
+    async fn register(&self, locker_id: &str, location: &str, status: &LockerStatus) -> Result<Locker, Status>;
+    async fn update(&self, locker_id: &str, location: &Option<String>, status: &Option<LockerStatus>) -> Result<Locker, Status>;
+    async fn delete(&self, locker_id: &str) -> Result<usize, Status>;
+    async fn import(&self, lockers: &[LockerInfo], years: &[i32], dry_run: bool) -> Result<LockerImportDiff, Status>;
+}
+
+/// 既存のロッカーと一括登録データの差分を計算する
+///
+/// in_useのロッカーの状態を変更する行はupdatedに含めずin_useに含める
+fn import_diff(existing: &[Locker], lockers: &[LockerInfo], in_use: &[String]) -> LockerImportDiff {
+    let mut diff = LockerImportDiff {
+        created: Vec::new(),
+        updated: Vec::new(),
+        unchanged: 0,
+        in_use: Vec::new(),
+    };
+
+    for row in lockers {
+        match existing.iter().find(|l| l.locker_id == row.locker_id) {
+            None => diff.created.push(row.clone()),
+            Some(current) => {
+                if current.location == row.location && current.status == row.status {
+                    diff.unchanged += 1;
+                } else if current.status != row.status && in_use.contains(&row.locker_id) {
+                    diff.in_use.push(row.locker_id.clone());
+                } else {
+                    diff.updated.push(LockerChange {
+                        locker_id: row.locker_id.clone(),
+                        prev_location: current.location.clone(),
+                        prev_status: current.status,
+                        location: row.location.clone(),
+                        status: row.status,
+                    });
+                }
+            },
+        }
+    }
+
+    diff
 }
 
 impl LockerUsecaseImpl {
-    pub fn new(locker_repository: Arc<dyn LockerRepository>) -> Self {
-        LockerUsecaseImpl { locker_repository }
+    pub fn new(locker_repository: Arc<dyn LockerRepository>, unit_of_work: UnitOfWork) -> Self {
+        LockerUsecaseImpl { locker_repository, unit_of_work }
     }
 }
 
@@ -70,15 +112,17 @@ impl LockerUsecase for LockerUsecaseImpl {
         }
     }
 
-    async fn get_by_floor(&self, floor: &Option<i8>) -> Result<Vec<Locker>, Status> {
+    async fn get_by_floor(&self, floor: &Option<i8>, year: &i32) -> Result<Vec<Locker>, Status> {
         let floor_val = match floor {
             None => String::from(""),
             Some(x) => format!("{}", x),
         };
+        let year = *year;
         let repository = self.locker_repository.clone();
 
+        // 使用中かどうかは指定年度の割り当てから判定する
         match task::spawn_blocking(move || {
-            repository.get_by_floor(floor_val)
+            repository.get_by_floor_and_year(floor_val, year)
         }).await {
             Err(e) => {
                 eprintln!("Thread panic in spawn_blocking: {:?}", e);
@@ -96,9 +140,9 @@ impl LockerUsecase for LockerUsecaseImpl {
         }
     }
 
-    async  fn update_status(&self, locker_id: &str, status: &str) -> Result<usize, Status> {
+    async  fn update_status(&self, locker_id: &str, status: &LockerStatus) -> Result<usize, Status> {
         let locker_id = locker_id.to_string();
-        let status = status.to_string();
+        let status = *status;
         let repository = self.locker_repository.clone();
 
         match task::spawn_blocking(move || {
@@ -120,12 +164,19 @@ impl LockerUsecase for LockerUsecaseImpl {
         }
     }
 
-    async fn reset_status(&self) -> Result<usize, Status> {
+    async fn search(&self, floor: &Option<i8>, location: &Option<String>, status: &Option<LockerStatus>, year: &i32) -> Result<Vec<Locker>, Status> {
+        let floor_val = match floor {
+            None => String::from(""),
+            Some(x) => format!("{}", x),
+        };
+        let location = location.clone();
+        let status = *status;
+        let year = *year;
         let repository = self.locker_repository.clone();
 
-        // statusを更新
+        // 使用中かどうかは指定年度の割り当てから判定する
         match task::spawn_blocking(move || {
-            repository.update_status(String::from(""), String::from("occupied"), String::from("vacant"))
+            repository.search(floor_val, location, status, year)
         }).await {
             Err(e) => {
                 eprintln!("Thread panic in spawn_blocking: {:?}", e);
@@ -139,7 +190,129 @@ impl LockerUsecase for LockerUsecaseImpl {
                 eprintln!("Repository Error: {:?}", e);
                 Err(Status::InternalServerError)
             },
+            Ok(Ok(lockers)) => Ok(lockers),
+        }
+    }
+
+    async fn register(&self, locker_id: &str, location: &str, status: &LockerStatus) -> Result<Locker, Status> {
+        let locker_id = locker_id.to_string();
+        let location = location.to_string();
+        let status = *status;
+        let repository = self.locker_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.insert(locker_id, location, status)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            // 同じロッカー番号が既に存在する
+            Ok(Err(RepositoryError::DieselError(DatabaseError(DatabaseErrorKind::UniqueViolation, _)))) => {
+                Err(Status::Conflict)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(locker)) => Ok(locker),
+        }
+    }
+
+    async fn update(&self, locker_id: &str, location: &Option<String>, status: &Option<LockerStatus>) -> Result<Locker, Status> {
+        let locker_id = locker_id.to_string();
+        let location = location.clone();
+        let status = *status;
+        let repository = self.locker_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.update(locker_id, location, status)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(diesel::result::Error::NotFound))) => {
+                Err(Status::NotFound)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(locker)) => Ok(locker),
+        }
+    }
+
+    async fn delete(&self, locker_id: &str) -> Result<usize, Status> {
+        let locker_id = locker_id.to_string();
+        let repository = self.locker_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.delete(locker_id)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            // 割り当て履歴が存在するロッカーは削除できない
+            Ok(Err(RepositoryError::DieselError(DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)))) => {
+                Err(Status::Conflict)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(0)) => Err(Status::NotFound),
             Ok(Ok(result)) => Ok(result),
         }
     }
+
+    // 既存のロッカーをロックし、yearsのいずれかの年度に割り当てがあるロッカーの状態を変更する行がある場合は更新しない
+    // dry-runの場合は差分の計算のみ行い、DBは更新しない
+    async fn import(&self, lockers: &[LockerInfo], years: &[i32], dry_run: bool) -> Result<LockerImportDiff, Status> {
+        let lockers = lockers.to_vec();
+        let years = years.to_vec();
+        let repository = self.locker_repository.clone();
+        let unit_of_work = self.unit_of_work.clone();
+
+        match task::spawn_blocking(move || {
+            unit_of_work.run(|tx| {
+                let locker_ids: Vec<String> = lockers.iter().map(|l| l.locker_id.clone()).collect();
+                let existing = repository.get_for_update_tx(tx, locker_ids.clone())?;
+                let in_use = repository.get_in_use_tx(tx, locker_ids, years)?;
+                let diff = import_diff(&existing, &lockers, &in_use);
+
+                if !dry_run && diff.in_use.is_empty() {
+                    repository.upsert_all_tx(tx, lockers)?;
+                }
+                Ok(diff)
+            })
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(diff)) => Ok(diff),
+        }
+    }
 }
\ No newline at end of file
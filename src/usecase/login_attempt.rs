@@ -0,0 +1,193 @@
+use std::sync::Arc;
+use crate::adapters::repository::{RepositoryError, UnitOfWork, login_attempt::{LoginAttemptFilter, LoginAttemptRepository, LoginFailures}};
+use crate::domain::admin::LoginResult;
+use crate::infrastructure::models::LoginAttempt;
+use crate::utils::env::positive_from_env;
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use dotenv::dotenv;
+use rocket::{tokio::task, http::Status};
+use async_trait::async_trait;
+
+/// 管理画面に表示するログイン履歴の最大件数
+const LIST_LIMIT: i64 = 200;
+
+/// ### LoginThrottlePolicy
+/// ログインに連続して失敗した場合のロックアウトの設定
+///
+/// username_max_failures   : 同じユーザーネームでロックアウトするまでに失敗できる回数
+///
+/// ip_max_failures         : 同じIPアドレスからロックアウトするまでに失敗できる回数
+///
+/// base_lockout            : 1回目のロックアウトの時間、以降は失敗するごとに2倍にする
+///
+/// max_lockout             : ロックアウトの最大時間
+///
+/// window                  : 失敗回数を数える期間、この期間より前の失敗は数えない
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoginThrottlePolicy {
+    pub username_max_failures: i64,
+    pub ip_max_failures: i64,
+    pub base_lockout: TimeDelta,
+    pub max_lockout: TimeDelta,
+    pub window: TimeDelta,
+}
+
+impl Default for LoginThrottlePolicy {
+    fn default() -> Self {
+        LoginThrottlePolicy {
+            username_max_failures: 5,
+            ip_max_failures: 20,
+            base_lockout: TimeDelta::minutes(1),
+            max_lockout: TimeDelta::hours(1),
+            window: TimeDelta::hours(24),
+        }
+    }
+}
+
+impl LoginThrottlePolicy {
+    /// ### from_env
+    /// LOGIN_MAX_FAILURES_PER_USER、LOGIN_MAX_FAILURES_PER_IP、LOGIN_LOCKOUT_BASE_SECONDS、LOGIN_LOCKOUT_MAX_MINUTESから設定を読み込む、設定されていない場合は既定値を使用する
+    pub fn from_env() -> Self {
+        dotenv().ok();
+        let default = LoginThrottlePolicy::default();
+
+        LoginThrottlePolicy {
+            username_max_failures: positive_from_env("LOGIN_MAX_FAILURES_PER_USER").unwrap_or(default.username_max_failures),
+            ip_max_failures: positive_from_env("LOGIN_MAX_FAILURES_PER_IP").unwrap_or(default.ip_max_failures),
+            base_lockout: positive_from_env("LOGIN_LOCKOUT_BASE_SECONDS").map(TimeDelta::seconds).unwrap_or(default.base_lockout),
+            max_lockout: positive_from_env("LOGIN_LOCKOUT_MAX_MINUTES").map(TimeDelta::minutes).unwrap_or(default.max_lockout),
+            window: default.window,
+        }
+    }
+
+    /// ### locked_until
+    /// max_failures回まで失敗できる場合に、failuresの失敗によるロックアウトの終了時刻を返す、ロックアウトしない場合はNoneを返す
+    pub fn locked_until(&self, failures: LoginFailures, max_failures: i64) -> Option<NaiveDateTime> {
+        if failures.count < max_failures {
+            return None;
+        }
+        let exponent = (failures.count - max_failures).clamp(0, 16) as u32;
+        let lockout = (self.base_lockout * 2_i32.pow(exponent)).min(self.max_lockout);
+        failures.last_failed_at.map(|last_failed_at| last_failed_at + lockout)
+    }
+}
+
+#[derive(Clone)]
+pub struct LoginAttemptUsecaseImpl {
+    pub login_attempt_repository: Arc<dyn LoginAttemptRepository>,
+    pub unit_of_work: UnitOfWork,
+    pub policy: LoginThrottlePolicy,
+}
+
+#[async_trait]
+pub trait LoginAttemptUsecase: Sync + Send {
+    /// ログインの試行をパスワードの誤りとして記録する、ロックアウト中の場合はLockedとして記録し429を返す
+    async fn start(&self, username: &str, ip_address: Option<String>) -> Result<LoginAttempt, Status>;
+    /// startで記録した試行の結果を更新する
+    async fn finish(&self, attempt: &LoginAttempt, result: LoginResult) -> Result<(), Status>;
+    async fn search(&self, username: Option<String>, ip_address: Option<String>, results: Vec<LoginResult>) -> Result<Vec<LoginAttempt>, Status>;
+}
+
+impl LoginAttemptUsecaseImpl {
+    pub fn new(login_attempt_repository: Arc<dyn LoginAttemptRepository>, unit_of_work: UnitOfWork, policy: LoginThrottlePolicy) -> Self {
+        LoginAttemptUsecaseImpl { login_attempt_repository, unit_of_work, policy }
+    }
+}
+
+#[async_trait]
+impl LoginAttemptUsecase for LoginAttemptUsecaseImpl {
+    // ユーザーネームとIPアドレスの失敗回数の確認と試行の記録を、ユーザーネームとIPアドレスごとのロックを取得した1トランザクションで実行
+    // パスワードを照合する前に失敗として記録し、同時に送られた試行も失敗回数に数える
+    async fn start(&self, username: &str, ip_address: Option<String>) -> Result<LoginAttempt, Status> {
+        let username = username.to_string();
+        let now = Utc::now().naive_utc();
+        let since = now - self.policy.window;
+        let policy = self.policy;
+        let repository = self.login_attempt_repository.clone();
+        let unit_of_work = self.unit_of_work.clone();
+
+        let (attempt, locked) = match task::spawn_blocking(move || {
+            unit_of_work.run(|tx| {
+                repository.lock_tx(tx, username.clone(), ip_address.clone())?;
+
+                let by_username = repository.count_failures_tx(tx, LoginAttemptFilter::Username(username.clone()), since)?;
+                let by_ip_address = match &ip_address {
+                    Some(ip_address) => repository.count_failures_tx(tx, LoginAttemptFilter::IpAddress(ip_address.clone()), since)?,
+                    None => LoginFailures::default(),
+                };
+                let locked = policy.locked_until(by_username, policy.username_max_failures)
+                    .max(policy.locked_until(by_ip_address, policy.ip_max_failures))
+                    .is_some_and(|locked_until| locked_until > now);
+
+                let result = if locked { LoginResult::Locked } else { LoginResult::InvalidCredentials };
+                let attempt = repository.insert_tx(tx, username, ip_address, result)?;
+                Ok((attempt, locked))
+            })
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                return Err(Status::InternalServerError);
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                return Err(Status::ServiceUnavailable);
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                return Err(Status::InternalServerError);
+            },
+            Ok(Ok(result)) => result,
+        };
+
+        if locked {
+            return Err(Status::TooManyRequests);
+        }
+        Ok(attempt)
+    }
+
+    async fn finish(&self, attempt: &LoginAttempt, result: LoginResult) -> Result<(), Status> {
+        let attempt_id = attempt.attempt_id;
+        let repository = self.login_attempt_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.update_result(attempt_id, result)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(_)) => Ok(()),
+        }
+    }
+
+    // ログインの試行を新しい順に最大200件取得する
+    async fn search(&self, username: Option<String>, ip_address: Option<String>, results: Vec<LoginResult>) -> Result<Vec<LoginAttempt>, Status> {
+        let repository = self.login_attempt_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.search(username, ip_address, results, LIST_LIMIT)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(attempts)) => Ok(attempts),
+        }
+    }
+}
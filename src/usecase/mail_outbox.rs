@@ -0,0 +1,346 @@
+use std::sync::Arc;
+use crate::adapters::repository::{RepositoryError, mail_outbox::MailOutboxRepository};
+use crate::domain::mail::{MailAudit, MailStatus};
+use crate::infrastructure::{mailer::{MailContent, MailError, Mailer}, models::MailOutbox};
+use crate::utils::env::positive_from_env;
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use dotenv::dotenv;
+use rocket::{tokio::{task, sync::Notify}, http::Status};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+/// 管理画面に表示する送信待ちメール、送信履歴の最大件数
+const LIST_LIMIT: i64 = 200;
+
+/// ### MailRetryPolicy
+/// 送信に失敗したメールの再送設定
+///
+/// max_attempts    : 送信を試みる最大回数、超えた場合はfailedにする
+///
+/// base_delay      : 1回目の失敗後の待ち時間、以降は失敗するごとに2倍にする
+///
+/// lease           : ワーカーが取得したメールを他のワーカーが取得しない時間
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MailRetryPolicy {
+    pub max_attempts: i32,
+    pub base_delay: TimeDelta,
+    pub lease: TimeDelta,
+}
+
+impl Default for MailRetryPolicy {
+    fn default() -> Self {
+        MailRetryPolicy {
+            max_attempts: 8,
+            base_delay: TimeDelta::minutes(1),
+            lease: TimeDelta::minutes(5),
+        }
+    }
+}
+
+impl MailRetryPolicy {
+    /// ### from_env
+    /// MAIL_MAX_ATTEMPTS、MAIL_RETRY_BASE_SECONDSから設定を読み込む、設定されていない場合は既定値を使用する
+    pub fn from_env() -> Self {
+        dotenv().ok();
+        let default = MailRetryPolicy::default();
+
+        MailRetryPolicy {
+            max_attempts: positive_from_env("MAIL_MAX_ATTEMPTS").map(|value| value as i32).unwrap_or(default.max_attempts),
+            base_delay: positive_from_env("MAIL_RETRY_BASE_SECONDS").map(TimeDelta::seconds).unwrap_or(default.base_delay),
+            lease: default.lease,
+        }
+    }
+
+    /// ### next_attempt_at
+    /// attempts回失敗した後の次の送信時刻を返す、上限に達した場合はNoneを返す
+    pub fn next_attempt_at(&self, attempts: i32, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        let exponent = (attempts - 1).clamp(0, 16) as u32;
+        Some(now + self.base_delay * 2_i32.pow(exponent))
+    }
+}
+
+/// ### MailDeliveryReport
+/// 送信待ちメールの送信結果
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MailDeliveryReport {
+    pub sent: usize,
+    pub retrying: usize,
+    pub failed: usize,
+    /// leaseが切れたため結果を保存しなかった件数
+    pub expired: usize,
+}
+
+#[derive(Clone)]
+pub struct MailOutboxUsecaseImpl {
+    pub mail_outbox_repository: Arc<dyn MailOutboxRepository>,
+    pub retry_policy: MailRetryPolicy,
+    /// メールが追加されたことをワーカーに通知する
+    pub notify: Arc<Notify>,
+}
+
+#[async_trait]
+pub trait MailOutboxUsecase: Sync + Send {
+    async fn enqueue(&self, to_address: String, mail: MailContent, audit: MailAudit) -> Result<MailOutbox, Status>;
+    async fn deliver_due(&self, mailer: &dyn Mailer, limit: i64) -> Result<MailDeliveryReport, Status>;
+    async fn get_by_status(&self, statuses: Vec<MailStatus>) -> Result<Vec<MailOutbox>, Status>;
+    async fn search(&self, student_id: Option<String>, email: Option<String>) -> Result<Vec<MailOutbox>, Status>;
+    async fn retry(&self, mail_id: &Uuid) -> Result<(), Status>;
+    async fn retry_failed(&self) -> Result<usize, Status>;
+    async fn purge(&self, retention: TimeDelta) -> Result<usize, Status>;
+}
+
+impl MailOutboxUsecaseImpl {
+    pub fn new(mail_outbox_repository: Arc<dyn MailOutboxRepository>, retry_policy: MailRetryPolicy) -> Self {
+        MailOutboxUsecaseImpl { mail_outbox_repository, retry_policy, notify: Arc::new(Notify::new()) }
+    }
+
+    // 送信結果を保存する、失敗した場合は再送時刻を設定する
+    // leaseが切れて他のワーカーが取得した、または管理者が再送した場合は保存せずにNoneを返す
+    async fn record_result(&self, mail: &MailOutbox, result: Result<(), MailError>) -> Result<Option<MailStatus>, Status> {
+        let mail_id = mail.mail_id;
+        // claim_dueが返したnext_attempt_atはdbに保存されたlease_untilと一致する
+        let lease_until = mail.next_attempt_at;
+        let attempts = mail.attempts + 1;
+        let repository = self.mail_outbox_repository.clone();
+
+        let (status, update) = match result {
+            Ok(()) => (MailStatus::Sent, task::spawn_blocking(move || {
+                repository.mark_sent(mail_id, lease_until, attempts)
+            }).await),
+            Err(e) => {
+                eprintln!("Mail Error: {} ({})", e, mail_id);
+                let next_attempt_at = self.retry_policy.next_attempt_at(attempts, Utc::now().naive_utc());
+                let status = match next_attempt_at {
+                    Some(_) => MailStatus::Pending,
+                    None => MailStatus::Failed,
+                };
+                (status, task::spawn_blocking(move || {
+                    repository.mark_failed(mail_id, lease_until, attempts, next_attempt_at, e.to_string())
+                }).await)
+            },
+        };
+
+        match update {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(0)) => {
+                eprintln!("Mail lease expired before the result was recorded ({})", mail_id);
+                Ok(None)
+            },
+            Ok(Ok(_)) => Ok(Some(status)),
+        }
+    }
+}
+
+#[async_trait]
+impl MailOutboxUsecase for MailOutboxUsecaseImpl {
+    // メールを送信待ちとして保存し、ワーカーに通知する
+    async fn enqueue(&self, to_address: String, mail: MailContent, audit: MailAudit) -> Result<MailOutbox, Status> {
+        let repository = self.mail_outbox_repository.clone();
+
+        let result = match task::spawn_blocking(move || {
+            repository.insert(to_address, mail.subject, mail.text, mail.html, audit)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(mail)) => Ok(mail),
+        };
+
+        if result.is_ok() {
+            self.notify.notify_one();
+        }
+        result
+    }
+
+    // 送信時刻を過ぎたメールを最大limit件送信する
+    async fn deliver_due(&self, mailer: &dyn Mailer, limit: i64) -> Result<MailDeliveryReport, Status> {
+        let now = Utc::now().naive_utc();
+        let lease_until = now + self.retry_policy.lease;
+        let repository = self.mail_outbox_repository.clone();
+
+        let mails = match task::spawn_blocking(move || {
+            repository.claim_due(now, lease_until, limit)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                return Err(Status::InternalServerError);
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                return Err(Status::ServiceUnavailable);
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                return Err(Status::InternalServerError);
+            },
+            Ok(Ok(mails)) => mails,
+        };
+
+        let mut report = MailDeliveryReport::default();
+        for mail in mails {
+            let content = MailContent {
+                subject: mail.subject.clone(),
+                text: mail.body_text.clone(),
+                html: mail.body_html.clone(),
+            };
+            let result = mailer.send(mail.to_address.clone(), content).await;
+
+            match self.record_result(&mail, result).await? {
+                Some(MailStatus::Sent) => report.sent += 1,
+                Some(MailStatus::Pending) => report.retrying += 1,
+                Some(MailStatus::Failed) => report.failed += 1,
+                None => report.expired += 1,
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn get_by_status(&self, statuses: Vec<MailStatus>) -> Result<Vec<MailOutbox>, Status> {
+        let repository = self.mail_outbox_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.get_by_status(statuses, LIST_LIMIT)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(mails)) => Ok(mails),
+        }
+    }
+
+    async fn search(&self, student_id: Option<String>, email: Option<String>) -> Result<Vec<MailOutbox>, Status> {
+        let repository = self.mail_outbox_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.search(student_id, email, LIST_LIMIT)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(mails)) => Ok(mails),
+        }
+    }
+
+    // 存在しない、送信済み、または本文を削除したメールはNotFoundを返す
+    async fn retry(&self, mail_id: &Uuid) -> Result<(), Status> {
+        let mail_id = *mail_id;
+        let repository = self.mail_outbox_repository.clone();
+
+        let result = match task::spawn_blocking(move || {
+            repository.retry(mail_id)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(0)) => Err(Status::NotFound),
+            Ok(Ok(_)) => Ok(()),
+        };
+
+        if result.is_ok() {
+            self.notify.notify_one();
+        }
+        result
+    }
+
+    async fn retry_failed(&self) -> Result<usize, Status> {
+        let repository = self.mail_outbox_repository.clone();
+
+        let result = match task::spawn_blocking(move || {
+            repository.retry_failed()
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(count)) => Ok(count),
+        };
+
+        if result.is_ok() {
+            self.notify.notify_one();
+        }
+        result
+    }
+
+    // 作成からretentionが経過した送信済み、送信に失敗したメールの本文を削除する、宛先などの送信履歴は残す
+    async fn purge(&self, retention: TimeDelta) -> Result<usize, Status> {
+        let threshold = Utc::now().naive_utc() - retention;
+        let repository = self.mail_outbox_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.clear_bodies_before(threshold)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(count)) => Ok(count),
+        }
+    }
+}
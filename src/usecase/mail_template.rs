@@ -0,0 +1,148 @@
+use std::sync::Arc;
+use crate::adapters::repository::{RepositoryError, mail_template::MailTemplateRepository};
+use crate::infrastructure::{mail_template::MailTemplates, mailer::MailContent, models::MailTemplate};
+use rocket::{tokio::task, http::Status};
+use async_trait::async_trait;
+use serde_json::Value;
+
+pub struct MailTemplateUsecaseImpl {
+    pub mail_template_repository: Arc<dyn MailTemplateRepository>,
+    pub templates: Arc<MailTemplates>,
+}
+
+#[async_trait]
+pub trait MailTemplateUsecase: Sync + Send {
+    async fn render(&self, name: &str, context: Value) -> Result<MailContent, Status>;
+    async fn get_all(&self) -> Result<Vec<MailTemplate>, Status>;
+    async fn get_by_name(&self, name: &str) -> Result<Option<MailTemplate>, Status>;
+    async fn register(&self, name: &str, subject: &str, body_text: &str, body_html: Option<&str>) -> Result<MailTemplate, Status>;
+    async fn delete(&self, name: &str) -> Result<(), Status>;
+}
+
+impl MailTemplateUsecaseImpl {
+    pub fn new(mail_template_repository: Arc<dyn MailTemplateRepository>, templates: Arc<MailTemplates>) -> Self {
+        MailTemplateUsecaseImpl { mail_template_repository, templates }
+    }
+}
+
+#[async_trait]
+impl MailTemplateUsecase for MailTemplateUsecaseImpl {
+    // 管理画面から編集されたテンプレートがある場合はそちらを使用して描画する
+    async fn render(&self, name: &str, context: Value) -> Result<MailContent, Status> {
+        let overridden = self.get_by_name(name).await?;
+
+        self.templates.render(name, context, overridden.as_ref()).map_err(|e| {
+            eprintln!("Template Error: {:?}", e);
+            Status::InternalServerError
+        })
+    }
+
+    async fn get_all(&self) -> Result<Vec<MailTemplate>, Status> {
+        let locale = self.templates.locale.clone();
+        let repository = self.mail_template_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.get_all(locale)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(templates)) => Ok(templates),
+        }
+    }
+
+    async fn get_by_name(&self, name: &str) -> Result<Option<MailTemplate>, Status> {
+        let name = name.to_string();
+        let locale = self.templates.locale.clone();
+        let repository = self.mail_template_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.get_by_name(name, locale)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(template)) => Ok(template),
+        }
+    }
+
+    // テンプレートファイルが存在しない名前はNotFound、構文エラーや使用できない変数はBadRequestを返す
+    async fn register(&self, name: &str, subject: &str, body_text: &str, body_html: Option<&str>) -> Result<MailTemplate, Status> {
+        if !self.templates.names().iter().any(|template| template == name) {
+            return Err(Status::NotFound);
+        }
+        if let Err(e) = MailTemplates::validate(name, subject, body_text, body_html) {
+            eprintln!("Template Error: {:?}", e);
+            return Err(Status::BadRequest);
+        }
+
+        let name = name.to_string();
+        let locale = self.templates.locale.clone();
+        let subject = subject.to_string();
+        let body_text = body_text.to_string();
+        let body_html = body_html.map(|html| html.to_string());
+        let repository = self.mail_template_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.upsert(name, locale, subject, body_text, body_html)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(template)) => Ok(template),
+        }
+    }
+
+    // 編集されたテンプレートを削除してテンプレートファイルに戻す
+    async fn delete(&self, name: &str) -> Result<(), Status> {
+        let name = name.to_string();
+        let locale = self.templates.locale.clone();
+        let repository = self.mail_template_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.delete(name, locale)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(0)) => Err(Status::NotFound),
+            Ok(Ok(_)) => Ok(()),
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
-use crate::domain::circle::OrganizationInfo;
-use crate::adapters::repository::{RepositoryError, registration::RegistrationRepository};
-use crate::infrastructure::models::Registration;
+use crate::domain::circle::{OrganizationInfo, AcceptanceStatus, AuthenticationStatus, FormConfirmationStatus, RegistrationCompleteStatus};
+use crate::adapters::repository::{RepositoryError, UnitOfWork, registration::RegistrationRepository, organization::OrganizationRepository, representatives::RepresentativesRepository};
+use crate::infrastructure::models::{Registration, NewRegistration};
 use async_trait::async_trait;
 use rocket::{tokio::task, http::Status};
 use chrono::{Datelike, Local};
@@ -9,19 +9,24 @@ use chrono::{Datelike, Local};
 
 pub struct RegistrationUsecaseImpl {
     pub registration_repository: Arc<dyn RegistrationRepository>,
+    pub organization_repository: Arc<dyn OrganizationRepository>,
+    pub representatives_repository: Arc<dyn RepresentativesRepository>,
+    pub unit_of_work: UnitOfWork,
 }
 
 #[async_trait]
 pub trait RegistrationUsecase: Sync + Send {
     async fn register(&self, organization: &OrganizationInfo, organization_id: &i32) -> Result<Registration, Status>;
     async fn update_student(&self, organization_id: &i32, main_student_id: &str, co_student_id: &str) -> Result<Registration, Status>;
-    async fn update_status(&self, organization_id: &i32, status_acceptance: &str, status_authentication: &str, status_form_confirmation: &str, status_registration_complete: &str) -> Result<Registration, Status>;
+    async fn register_with_organization(&self, organization: &OrganizationInfo) -> Result<Registration, Status>;
+    async fn update_with_organization(&self, organization_id: &i32, organization: &OrganizationInfo) -> Result<(), Status>;
+    async fn update_status(&self, organization_id: &i32, status_acceptance: &AcceptanceStatus, status_authentication: &AuthenticationStatus, status_form_confirmation: &FormConfirmationStatus, status_registration_complete: &RegistrationCompleteStatus) -> Result<Registration, Status>;
     async fn get_all(&self) -> Result<Vec<Registration>, Status>;
 }
 
 impl RegistrationUsecaseImpl {
-    pub fn new(registration_repository: Arc<dyn RegistrationRepository>) -> Self {
-        RegistrationUsecaseImpl { registration_repository }
+    pub fn new(registration_repository: Arc<dyn RegistrationRepository>, organization_repository: Arc<dyn OrganizationRepository>, representatives_repository: Arc<dyn RepresentativesRepository>, unit_of_work: UnitOfWork) -> Self {
+        RegistrationUsecaseImpl { registration_repository, organization_repository, representatives_repository, unit_of_work }
     }
 }
 
@@ -30,10 +35,10 @@ impl RegistrationUsecase for RegistrationUsecaseImpl {
     async fn register(&self, organization: &OrganizationInfo, organization_id: &i32) -> Result<Registration, Status> {
         // 団体情報の登録
         let year = Local::now().year();
-        let init_status_acpt = String::from("pending");
-        let init_status_auth = String::from("not_authenticated");
-        let init_status_form = String::from("not_confirmed");
-        let init_status_rgst = String::from("incomplete");
+        let init_status_acpt = AcceptanceStatus::Pending;
+        let init_status_auth = AuthenticationStatus::NotAuthenticated;
+        let init_status_form = FormConfirmationStatus::NotConfirmed;
+        let init_status_rgst = RegistrationCompleteStatus::Incomplete;
         let organization = organization.clone();
         let organization_id = *organization_id;
         let repository = self.registration_repository.clone();
@@ -94,12 +99,106 @@ impl RegistrationUsecase for RegistrationUsecaseImpl {
         }
     }
 
-    async fn update_status(&self, organization_id: &i32, status_acceptance: &str, status_authentication: &str, status_form_confirmation: &str, status_registration_complete: &str) -> Result<Registration, Status> {
+    async fn register_with_organization(&self, organization: &OrganizationInfo) -> Result<Registration, Status> {
+        // 副代表者、団体、団体登録情報を1つのトランザクションで登録
+        let year = Local::now().year();
+        let organization = organization.clone();
+        let unit_of_work = self.unit_of_work.clone();
+        let representatives_repository = self.representatives_repository.clone();
+        let organization_repository = self.organization_repository.clone();
+        let registration_repository = self.registration_repository.clone();
+
+        match task::spawn_blocking(move || {
+            unit_of_work.run(|tx| {
+                let co_user = organization.co_user;
+                representatives_repository.insert_tx(tx, co_user.student_id.clone(), co_user.family_name, co_user.given_name, co_user.email, co_user.phone_number)?;
+
+                let organization_id = organization_repository.insert_tx(tx,
+                                                                        organization.organization.organization_name,
+                                                                        organization.organization.organization_ruby,
+                                                                        organization.organization.organization_email
+                                                                        )?.organization_id;
+
+                registration_repository.insert_tx(tx, &NewRegistration {
+                    organization_id: &organization_id,
+                    year: &year,
+                    main_student_id: &organization.main_user.student_id,
+                    co_student_id: &co_user.student_id,
+                    status_acceptance: &AcceptanceStatus::Pending,
+                    status_authentication: &AuthenticationStatus::NotAuthenticated,
+                    status_form_confirmation: &FormConfirmationStatus::NotConfirmed,
+                    status_registration_complete: &RegistrationCompleteStatus::Incomplete,
+                    b_doc: &organization.b_doc,
+                    c_doc: &organization.c_doc,
+                    d_doc: &organization.d_doc,
+                })
+            })
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(registration)) => Ok(registration),
+        }
+    }
+
+    async fn update_with_organization(&self, organization_id: &i32, organization: &OrganizationInfo) -> Result<(), Status> {
+        // 副代表者の登録、団体メールアドレスと代表者・副代表者の更新を1つのトランザクションで実行
+        let organization_id = *organization_id;
+        let organization = organization.clone();
+        let unit_of_work = self.unit_of_work.clone();
+        let representatives_repository = self.representatives_repository.clone();
+        let organization_repository = self.organization_repository.clone();
+        let registration_repository = self.registration_repository.clone();
+
+        match task::spawn_blocking(move || {
+            unit_of_work.run(|tx| {
+                let main_user = organization.main_user;
+                let co_user = organization.co_user;
+                let organization_email = organization.organization.organization_email;
+                representatives_repository.insert_tx(tx, co_user.student_id.clone(), co_user.family_name, co_user.given_name, co_user.email, co_user.phone_number)?;
+
+                if !organization_email.is_empty() {
+                    organization_repository.update_email_by_id_tx(tx, organization_id, organization_email)?;
+                }
+
+                if !main_user.student_id.is_empty() && !co_user.student_id.is_empty() {
+                    registration_repository.update_student_by_id_tx(tx, organization_id, main_user.student_id, co_user.student_id)?;
+                }
+
+                Ok(())
+            })
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(())) => Ok(()),
+        }
+    }
+
+    async fn update_status(&self, organization_id: &i32, status_acceptance: &AcceptanceStatus, status_authentication: &AuthenticationStatus, status_form_confirmation: &FormConfirmationStatus, status_registration_complete: &RegistrationCompleteStatus) -> Result<Registration, Status> {
         let organization_id = *organization_id;
-        let status_acceptance = status_acceptance.to_string();
-        let status_authentication = status_authentication.to_string();
-        let status_form_confirmation = status_form_confirmation.to_string();
-        let status_registration_complete = status_registration_complete.to_string();
+        let status_acceptance = *status_acceptance;
+        let status_authentication = *status_authentication;
+        let status_form_confirmation = *status_form_confirmation;
+        let status_registration_complete = *status_registration_complete;
         let repository = self.registration_repository.clone();
 
         // 団体のステータス更新
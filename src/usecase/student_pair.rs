@@ -15,8 +15,11 @@ pub trait StudentPairUsecase: Sync + Send {
     async fn register(&self, student_pair: &PairInfo) -> Result<StudentPair, Status>;
     async fn get_all(&self) -> Result<Vec<StudentPair>, Status>;
     async fn get_by_id(&self, student_id: &str) -> Result<Option<StudentPair>, Status>;
+    async fn get_by_id_and_year(&self, student_id: &str, year: &i32) -> Result<Option<StudentPair>, Status>;
     async fn get_by_main_id(&self, student_id: &str) -> Result<StudentPair, Status>;
+    async fn get_by_main_id_and_year(&self, student_id: &str, year: &i32) -> Result<StudentPair, Status>;
     async fn get_by_pair_id(&self, pair_id: &uuid::Uuid) -> Result<StudentPair, Status>;
+    async fn get_by_pair_id_and_year(&self, pair_id: &uuid::Uuid, year: &i32) -> Result<StudentPair, Status>;
 }
 
 impl StudentPairUsecaseImpl {
@@ -74,8 +77,12 @@ impl StudentPairUsecase for StudentPairUsecaseImpl {
     }
 
     async fn get_by_id(&self, student_id: &str) -> Result<Option<StudentPair>, Status> {
+        self.get_by_id_and_year(student_id, &Local::now().year()).await
+    }
+
+    async fn get_by_id_and_year(&self, student_id: &str, year: &i32) -> Result<Option<StudentPair>, Status> {
         let student_id = student_id.to_string();
-        let year = Local::now().year();
+        let year = *year;
         let repository = self.student_pair_repository.clone();
 
         match task::spawn_blocking(move || {
@@ -101,8 +108,12 @@ impl StudentPairUsecase for StudentPairUsecaseImpl {
     }
 
     async fn get_by_main_id(&self, student_id: &str) -> Result<StudentPair, Status> {
+        self.get_by_main_id_and_year(student_id, &Local::now().year()).await
+    }
+
+    async fn get_by_main_id_and_year(&self, student_id: &str, year: &i32) -> Result<StudentPair, Status> {
         let student_id = student_id.to_string();
-        let year = Local::now().year();
+        let year = *year;
         let repository = self.student_pair_repository.clone();
 
         match task::spawn_blocking(move || {
@@ -125,8 +136,12 @@ impl StudentPairUsecase for StudentPairUsecaseImpl {
     }
 
     async fn get_by_pair_id(&self, pair_id: &uuid::Uuid) -> Result<StudentPair, Status> {
+        self.get_by_pair_id_and_year(pair_id, &Local::now().year()).await
+    }
+
+    async fn get_by_pair_id_and_year(&self, pair_id: &uuid::Uuid, year: &i32) -> Result<StudentPair, Status> {
         let pair_id = *pair_id;
-        let year = Local::now().year();
+        let year = *year;
         let repository = self.student_pair_repository.clone();
 
         match task::spawn_blocking(move || {
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use crate::adapters::repository::{RepositoryError, time::TimeRepository};
 use crate::infrastructure::models::Time;
-use chrono::NaiveDateTime;
+use chrono::{Datelike, Local, NaiveDateTime, Utc};
 use rocket::{tokio::task, http::Status};
 use async_trait::async_trait;
 
@@ -11,9 +11,11 @@ pub struct TimeUsecaseImpl {
 
 #[async_trait]
 pub trait TimeUsecase: Sync + Send {
-    async fn register(&self, name: &str, start_time: &NaiveDateTime, end_time: &NaiveDateTime) -> Result<Time, Status>;
+    async fn register(&self, name: &str, start_time: &NaiveDateTime, end_time: &NaiveDateTime, year: &Option<i32>) -> Result<Time, Status>;
     async fn get_all(&self) -> Result<Vec<Time>, Status>;
     async fn get_by_name(&self, name: &str) -> Result<Time, Status>;
+    async fn is_open(&self, name: &str) -> Result<Option<bool>, Status>;
+    async fn year(&self, name: &str) -> Result<i32, Status>;
 }
 
 impl TimeUsecaseImpl {
@@ -24,14 +26,15 @@ impl TimeUsecaseImpl {
 
 #[async_trait]
 impl TimeUsecase for TimeUsecaseImpl {
-    async fn register(&self, name: &str, start_time: &NaiveDateTime, end_time: &NaiveDateTime) -> Result<Time, Status> {
+    async fn register(&self, name: &str, start_time: &NaiveDateTime, end_time: &NaiveDateTime, year: &Option<i32>) -> Result<Time, Status> {
         let name = name.to_string();
         let start_time = *start_time;
         let end_time = *end_time;
+        let year = *year;
         let repository = self.time_repository.clone();
 
         match task::spawn_blocking(move || {
-            repository.insert(name, start_time, end_time)
+            repository.insert(name, start_time, end_time, year)
         }).await {
             Err(e) => {
                 eprintln!("Thread panic in spawn_blocking: {:?}", e);
@@ -96,4 +99,55 @@ impl TimeUsecase for TimeUsecaseImpl {
             Ok(Ok(time)) => Ok(time),
         }
     }
+
+    // 現在時刻が期間内か確認する、期間が設定されていない場合はNoneを返す
+    async fn is_open(&self, name: &str) -> Result<Option<bool>, Status> {
+        let name = name.to_string();
+        let now = Utc::now().naive_utc();
+        let repository = self.time_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.get_by_name(name)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(diesel::result::Error::NotFound))) => Ok(None),
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(time)) => Ok(Some(time.start_time <= now && now < time.end_time)),
+        }
+    }
+
+    // 期間の対象年度を取得する、期間または年度が設定されていない場合は今年度を返す
+    async fn year(&self, name: &str) -> Result<i32, Status> {
+        let name = name.to_string();
+        let repository = self.time_repository.clone();
+
+        match task::spawn_blocking(move || {
+            repository.get_by_name(name)
+        }).await {
+            Err(e) => {
+                eprintln!("Thread panic in spawn_blocking: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Err(RepositoryError::ConnectionError(e))) => {
+                eprintln!("Connection Error: {:?}", e);
+                Err(Status::ServiceUnavailable)
+            },
+            Ok(Err(RepositoryError::DieselError(diesel::result::Error::NotFound))) => Ok(Local::now().year()),
+            Ok(Err(RepositoryError::DieselError(e))) => {
+                eprintln!("Repository Error: {:?}", e);
+                Err(Status::InternalServerError)
+            },
+            Ok(Ok(time)) => Ok(time.year.unwrap_or_else(|| Local::now().year())),
+        }
+    }
 }
\ No newline at end of file
@@ -2,4 +2,8 @@ pub mod token;
 pub mod jwt;
 pub mod oauth_authentication;
 pub mod verify_password;
-pub mod verify_recaptcha;
\ No newline at end of file
+pub mod verify_recaptcha;
+pub mod archive;
+pub mod student_identity;
+pub mod password_policy;
+pub mod env;
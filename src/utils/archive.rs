@@ -0,0 +1,38 @@
+use std::io::{Cursor, Write};
+use csv::Writer;
+use rocket::http::Status;
+use serde::Serialize;
+use zip::{write::{FileOptions, ExtendedFileOptions}, CompressionMethod, ZipWriter};
+
+/// ### to_csv
+/// レコードの一覧をCSVに変換する
+///
+/// rows    : CSVに変換するレコード
+pub fn to_csv<T: Serialize>(rows: &[T]) -> Result<Vec<u8>, Status> {
+    let mut wtr = Writer::from_writer(vec![]);
+    for row in rows {
+        wtr.serialize(row).map_err(|_| Status::InternalServerError)?;
+    }
+    wtr.flush().map_err(|_| Status::InternalServerError)?;
+    wtr.into_inner().map_err(|_| Status::InternalServerError)
+}
+
+/// ### to_zip
+/// ファイル名と内容の組からZIPアーカイブをメモリ上で作成する
+///
+/// files   : (ファイル名, 内容)の一覧
+pub fn to_zip(files: Vec<(&str, Vec<u8>)>) -> Result<Vec<u8>, Status> {
+    let mut zip_buffer = Cursor::new(Vec::new());
+    let options: FileOptions<ExtendedFileOptions> = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    {
+        let mut zip_writer = ZipWriter::new(&mut zip_buffer);
+        for (filename, data) in files {
+            zip_writer.start_file(filename, options.clone())
+                .map_err(|_| Status::InternalServerError)?;
+            zip_writer.write_all(&data)
+                .map_err(|_| Status::InternalServerError)?;
+        }
+        zip_writer.finish().map_err(|_| Status::InternalServerError)?;
+    }
+    Ok(zip_buffer.into_inner())
+}
@@ -0,0 +1,9 @@
+use std::env;
+
+/// ### positive_from_env
+/// 環境変数keyを正の整数として読み込む、設定されていない、整数でない、または0以下の場合はNoneを返す
+pub fn positive_from_env(key: &str) -> Option<i64> {
+    env::var(key).ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|value| *value > 0)
+}
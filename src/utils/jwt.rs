@@ -3,6 +3,8 @@ use chrono::{Utc, TimeDelta};
 use serde::{Deserialize, Serialize};
 use dotenv::dotenv;
 use std::env;
+use uuid::Uuid;
+use crate::domain::admin::AdminRole;
 
 /// ### Claims
 /// JWTペイロードに指定する構造体
@@ -12,11 +14,17 @@ use std::env;
 /// expire      : tokenの持続時間
 ///
 /// issued at   : tokenの発行時刻
+///
+/// role        : tokenの持ち主の権限
+///
+/// jwt id      : tokenを発行したセッションのid、admin_sessionテーブルで失効していないか確認する
 #[derive(Serialize, Deserialize)]
 pub struct Claims{
     pub sub: String,
     pub exp: usize,
     pub iat: usize,
+    pub role: AdminRole,
+    pub jti: Uuid,
 }
 
 /// ### encode_jwt
@@ -24,10 +32,14 @@ pub struct Claims{
 ///
 /// username    : jwtの持ち主
 ///
+/// role        : jwtの持ち主の権限
+///
+/// session_id  : jwtを発行したセッションのid
+///
 /// exp         : jwtの持続時間
 ///
 /// key         : jwtの鍵
-pub fn encode_jwt(username: &str, exp: TimeDelta, key: &String) -> String {
+pub fn encode_jwt(username: &str, role: AdminRole, session_id: Uuid, exp: TimeDelta, key: &String) -> String {
 
     // headerの宣言
     let header = Header{
@@ -48,6 +60,8 @@ pub fn encode_jwt(username: &str, exp: TimeDelta, key: &String) -> String {
         sub: username.to_string(),
         exp: (now + exp).timestamp() as usize,
         iat: now.timestamp() as usize,
+        role,
+        jti: session_id,
     };
 
     // jwtを発行
@@ -55,12 +69,13 @@ pub fn encode_jwt(username: &str, exp: TimeDelta, key: &String) -> String {
 }
 
 /// ### decode_jwt
-/// JWTを検証する
+/// JWTを検証する、jtiのないjwtは受け付けない
 ///
 /// jwt     : 検証するjwt
 pub fn decode_jwt(jwt: &str) -> Option<Claims> {
 
-    let validation = Validation::default();
+    let mut validation = Validation::default();
+    validation.set_required_spec_claims(&["exp", "sub", "jti"]);
 
     dotenv().ok();
     let secret = env::var("TOKEN_KEY").expect("token key must be set");
@@ -0,0 +1,62 @@
+use thiserror::Error;
+
+/// 管理者パスワードの最小文字数
+pub const PASSWORD_MIN_LENGTH: usize = 12;
+/// 管理者パスワードの最大文字数、ハッシュ計算の負荷を抑えるため上限を設ける
+pub const PASSWORD_MAX_LENGTH: usize = 128;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PasswordPolicyError {
+    #[error("password must be at least {PASSWORD_MIN_LENGTH} characters")]
+    TooShort,
+
+    #[error("password must be at most {PASSWORD_MAX_LENGTH} characters")]
+    TooLong,
+
+    #[error("password must not contain whitespace or control characters")]
+    InvalidCharacter,
+
+    #[error("password must contain at least 3 of uppercase, lowercase, digit and symbol")]
+    TooSimple,
+
+    #[error("password must not contain the username")]
+    ContainsUsername,
+}
+
+/// ### validate_password
+/// 管理者パスワードがパスワードポリシーを満たしているか確認する
+///
+/// 12文字以上128文字以下、空白と制御文字を含まない、英大文字・英小文字・数字・記号のうち3種類以上を含む、ユーザーネームを含まない
+///
+/// username    : パスワードを設定する管理者
+///
+/// password    : 検証するパスワード
+pub fn validate_password(username: &str, password: &str) -> Result<(), PasswordPolicyError> {
+    let length = password.chars().count();
+    if length < PASSWORD_MIN_LENGTH {
+        return Err(PasswordPolicyError::TooShort);
+    }
+    if length > PASSWORD_MAX_LENGTH {
+        return Err(PasswordPolicyError::TooLong);
+    }
+
+    if password.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(PasswordPolicyError::InvalidCharacter);
+    }
+
+    let classes = [
+        password.chars().any(|c| c.is_ascii_uppercase()),
+        password.chars().any(|c| c.is_ascii_lowercase()),
+        password.chars().any(|c| c.is_ascii_digit()),
+        password.chars().any(|c| !c.is_ascii_alphanumeric()),
+    ];
+    if classes.iter().filter(|&&class| class).count() < 3 {
+        return Err(PasswordPolicyError::TooSimple);
+    }
+
+    if !username.is_empty() && password.to_lowercase().contains(&username.to_lowercase()) {
+        return Err(PasswordPolicyError::ContainsUsername);
+    }
+
+    Ok(())
+}
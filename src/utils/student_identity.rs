@@ -0,0 +1,141 @@
+use std::{env, fs, io};
+use csv::{ReaderBuilder, Trim};
+use dotenv::dotenv;
+use regex::Regex;
+use thiserror::Error;
+
+/// 既定の学生用メールアドレスのドメイン
+pub const DEFAULT_MAIL_DOMAIN: &str = "ed.tus.ac.jp";
+/// 既定のロッカー登録で使用できる学籍番号
+pub const DEFAULT_LOCKER_PATTERN: &str = r"^(15\d{5}|[48][1-6]\d{5})$";
+/// 既定の団体登録で使用できる学籍番号
+pub const DEFAULT_CIRCLE_PATTERN: &str = r"^[1-46-9][1-9AB]\d{5}$";
+
+#[derive(Debug, Error)]
+pub enum StudentIdentityError {
+    #[error("PatternError: {0}")]
+    PatternError(#[from] regex::Error),
+
+    #[error("AffiliationError: {0}")]
+    AffiliationError(#[from] csv::Error),
+
+    #[error("IoError: {0}")]
+    IoError(#[from] io::Error),
+}
+
+/// ### StudentIdFlow
+/// 学籍番号を使用する手続き、手続きごとに使用できる学籍番号が異なる
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StudentIdFlow {
+    /// ロッカー登録
+    Locker,
+    /// 団体登録
+    Circle,
+}
+
+/// ### StudentAffiliation
+/// 学籍番号の先頭から判定した学部、学科
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StudentAffiliation {
+    pub prefix: String,
+    pub faculty: String,
+    pub department: String,
+}
+
+/// ### StudentIdentity
+/// 学籍番号の形式と学生用メールアドレスの設定
+///
+/// mail_domain     : 学籍番号から作成するメールアドレスのドメイン
+///
+/// locker_pattern  : ロッカー登録で使用できる学籍番号
+///
+/// circle_pattern  : 団体登録で使用できる学籍番号
+///
+/// affiliations    : 学籍番号の先頭と学部、学科の対応、設定されていない場合は空
+#[derive(Clone, Debug)]
+pub struct StudentIdentity {
+    pub mail_domain: String,
+    locker_pattern: Regex,
+    circle_pattern: Regex,
+    affiliations: Vec<StudentAffiliation>,
+}
+
+impl Default for StudentIdentity {
+    fn default() -> Self {
+        StudentIdentity::new(DEFAULT_MAIL_DOMAIN, DEFAULT_LOCKER_PATTERN, DEFAULT_CIRCLE_PATTERN, Vec::new()).unwrap()
+    }
+}
+
+impl StudentIdentity {
+    pub fn new(mail_domain: &str, locker_pattern: &str, circle_pattern: &str, affiliations: Vec<StudentAffiliation>) -> Result<Self, StudentIdentityError> {
+        Ok(StudentIdentity {
+            mail_domain: mail_domain.to_string(),
+            locker_pattern: Regex::new(locker_pattern)?,
+            circle_pattern: Regex::new(circle_pattern)?,
+            affiliations,
+        })
+    }
+
+    /// ### from_env
+    /// STUDENT_MAIL_DOMAIN、LOCKER_STUDENT_ID_PATTERN、CIRCLE_STUDENT_ID_PATTERNから設定を読み込む、設定されていない場合は既定値を使用する
+    ///
+    /// STUDENT_AFFILIATION_FILEが設定されている場合は学籍番号の先頭,学部,学科のCSVを読み込む
+    pub fn from_env() -> Self {
+        dotenv().ok();
+        let mail_domain = env::var("STUDENT_MAIL_DOMAIN").unwrap_or_else(|_| String::from(DEFAULT_MAIL_DOMAIN));
+        let locker_pattern = env::var("LOCKER_STUDENT_ID_PATTERN").unwrap_or_else(|_| String::from(DEFAULT_LOCKER_PATTERN));
+        let circle_pattern = env::var("CIRCLE_STUDENT_ID_PATTERN").unwrap_or_else(|_| String::from(DEFAULT_CIRCLE_PATTERN));
+        let affiliations = match env::var("STUDENT_AFFILIATION_FILE") {
+            Ok(path) => {
+                let csv = fs::read_to_string(path).expect("Failed to read student affiliation file");
+                StudentIdentity::parse_affiliations(&csv).expect("Failed to parse student affiliation file")
+            },
+            Err(_) => Vec::new(),
+        };
+
+        StudentIdentity::new(&mail_domain, &locker_pattern, &circle_pattern, affiliations).expect("Failed to compile student id pattern")
+    }
+
+    /// ### parse_affiliations
+    /// 学籍番号の先頭,学部,学科の形式のCSVを読み込む
+    pub fn parse_affiliations(csv: &str) -> Result<Vec<StudentAffiliation>, StudentIdentityError> {
+        let mut reader = ReaderBuilder::new()
+            .has_headers(false)
+            .trim(Trim::All)
+            .from_reader(csv.as_bytes());
+
+        let mut affiliations = Vec::new();
+        for record in reader.records() {
+            let record = record?;
+            affiliations.push(StudentAffiliation {
+                prefix: record.get(0).unwrap_or_default().to_string(),
+                faculty: record.get(1).unwrap_or_default().to_string(),
+                department: record.get(2).unwrap_or_default().to_string(),
+            });
+        }
+        Ok(affiliations)
+    }
+
+    /// ### is_valid
+    /// 手続きで使用できる学籍番号か確認する
+    pub fn is_valid(&self, flow: StudentIdFlow, student_id: &str) -> bool {
+        match flow {
+            StudentIdFlow::Locker => self.locker_pattern.is_match(student_id),
+            StudentIdFlow::Circle => self.circle_pattern.is_match(student_id),
+        }
+    }
+
+    /// ### email
+    /// 学籍番号から学生用メールアドレスを作成する
+    pub fn email(&self, student_id: &str) -> String {
+        format!("{}@{}", student_id, self.mail_domain)
+    }
+
+    /// ### affiliation
+    /// 学籍番号の先頭から学部、学科を判定する、複数該当する場合は最も長く一致したものを返す
+    pub fn affiliation(&self, student_id: &str) -> Option<&StudentAffiliation> {
+        self.affiliations.iter()
+            .filter(|affiliation| !affiliation.prefix.is_empty() && student_id.starts_with(affiliation.prefix.as_str()))
+            .max_by_key(|affiliation| affiliation.prefix.len())
+    }
+}
@@ -1,9 +1,100 @@
-use rand::{distributions::Alphanumeric, Rng};
+use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
+use sha2::{Digest, Sha256};
+use chrono::TimeDelta;
+use crate::utils::env::positive_from_env;
 
 /// ### generate_token
-/// [A-Za-z0-9]のランダムな16文字のトークンを作成する
+/// OSの乱数生成器を使用して[A-Za-z0-9]のランダムな32文字のトークンを作成する
 pub fn generate_token() -> String{
-    let mut rng = rand::thread_rng();
-    let token: String = (0..16).map(|_| rng.sample(Alphanumeric) as char).collect();
+    let token: String = OsRng.sample_iter(&Alphanumeric).take(32).map(char::from).collect();
     token
-}
\ No newline at end of file
+}
+
+/// ### hash_token
+/// トークンをSHA-256でハッシュ化し、16進数の文字列で返す
+///
+/// DBにはハッシュ化したトークンのみを保存する
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// ### verify_token_hash
+/// トークンとハッシュ値を定数時間で比較する
+pub fn verify_token_hash(token: &str, token_hash: &str) -> bool {
+    let hash = hash_token(token);
+    if hash.len() != token_hash.len() {
+        return false;
+    }
+    hash.bytes()
+        .zip(token_hash.bytes())
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// ### TokenTtl
+/// phaseごとのメール認証tokenの有効期限
+///
+/// main_auth       : 代表者の認証用token
+///
+/// co_auth         : 共同利用者の認証用token
+///
+/// auth_check      : 登録確認用token
+///
+/// cancel          : 登録取り消し用token
+///
+/// resend_interval : 認証メールを再送信できる間隔
+#[derive(Clone, Copy)]
+pub struct TokenTtl {
+    pub main_auth: TimeDelta,
+    pub co_auth: TimeDelta,
+    pub auth_check: TimeDelta,
+    pub cancel: TimeDelta,
+    pub resend_interval: TimeDelta,
+}
+
+impl Default for TokenTtl {
+    fn default() -> Self {
+        TokenTtl {
+            main_auth: TimeDelta::hours(1),
+            co_auth: TimeDelta::hours(24),
+            auth_check: TimeDelta::hours(1),
+            cancel: TimeDelta::hours(1),
+            resend_interval: TimeDelta::minutes(5),
+        }
+    }
+}
+
+impl TokenTtl {
+    /// ### from_env
+    /// 環境変数から有効期限(分)を読み込む、設定されていない場合は既定値を使用する
+    pub fn from_env() -> Self {
+        let default = TokenTtl::default();
+        TokenTtl {
+            main_auth: ttl_from_env("MAIN_AUTH_TOKEN_TTL_MINUTES", default.main_auth),
+            co_auth: ttl_from_env("CO_AUTH_TOKEN_TTL_MINUTES", default.co_auth),
+            auth_check: ttl_from_env("AUTH_CHECK_TOKEN_TTL_MINUTES", default.auth_check),
+            cancel: ttl_from_env("CANCEL_TOKEN_TTL_MINUTES", default.cancel),
+            resend_interval: ttl_from_env("AUTH_RESEND_INTERVAL_MINUTES", default.resend_interval),
+        }
+    }
+
+    /// ### get
+    /// phaseに対応する有効期限を取得する、有効期限のないphaseの場合はNoneを返す
+    pub fn get(&self, phase: &str) -> Option<TimeDelta> {
+        match phase {
+            "main_auth" => Some(self.main_auth),
+            "co_auth" => Some(self.co_auth),
+            "auth_check" => Some(self.auth_check),
+            "cancel" => Some(self.cancel),
+            _ => None,
+        }
+    }
+}
+
+fn ttl_from_env(key: &str, default: TimeDelta) -> TimeDelta {
+    positive_from_env(key)
+        .map(TimeDelta::minutes)
+        .unwrap_or(default)
+}
@@ -1,5 +1,6 @@
-use argon2::password_hash;
-use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use std::sync::OnceLock;
+use argon2::password_hash::{self, SaltString};
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 
 /// ### verify_password_hash
 /// パスワードをハッシュ値と比較検証する
@@ -10,4 +11,30 @@ use argon2::{Argon2, PasswordHash, PasswordVerifier};
 pub fn verify_password_hash(password: String, expected_password_hash: String) -> Result<(), password_hash::Error> {
     let expected_password_hash = PasswordHash::new(expected_password_hash.as_str())?;
     Argon2::default().verify_password(password.as_bytes(), &expected_password_hash)
-}
\ No newline at end of file
+}
+
+/// ### compute_password_hash
+/// パスワードのハッシュ値をArgon2idで計算する
+///
+/// 登録済みの管理者のハッシュ値と同じArgon2の既定のパラメータを使用する
+///
+/// password    : ハッシュ値を計算するパスワード
+pub fn compute_password_hash(password: String) -> Result<String, password_hash::Error> {
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    let password_hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string();
+
+    Ok(password_hash)
+}
+
+/// ### dummy_password_hash
+/// 存在しないユーザーネームでログインした場合に照合するハッシュ値
+///
+/// 存在するユーザーネームと同じ時間をかけてパスワードを照合し、応答時間からユーザーネームを推測されないようにする
+pub fn dummy_password_hash() -> &'static str {
+    static DUMMY_PASSWORD_HASH: OnceLock<String> = OnceLock::new();
+    DUMMY_PASSWORD_HASH.get_or_init(|| {
+        compute_password_hash(String::from("dummy password")).expect("failed to compute dummy password hash")
+    })
+}
@@ -0,0 +1,311 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::{router::rocket, admin::{register_admin, staff_cookie, session_cookie, delete_admin}};
+use rocket::local::asynchronous::Client;
+use rocket::http::{Status, ContentType};
+use tus_yuurikai_system::adapters::{controller::{self, locker}, httpmodels::{AdminCreateRequest, AdminListResponse, AdminPasswordChangeRequest, AdminPasswordResetRequest, AdminUpdateRequest, LoginFormRequest}};
+use tus_yuurikai_system::domain::admin::AdminRole;
+use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use tus_yuurikai_system::usecase::admin::AdminUsecase;
+use tus_yuurikai_system::utils::password_policy::{validate_password, PasswordPolicyError};
+
+// 正常系＝パスワードポリシーを満たすパスワードのみ設定できる
+#[test]
+fn password_policy() {
+
+    // Act
+    let valid = validate_password("user000", "Locker-Admin2025");
+    let too_short = validate_password("user000", "Abc-123");
+    let too_long = validate_password("user000", &"Aa1-".repeat(33));
+    let whitespace = validate_password("user000", "Locker Admin2025");
+    let too_simple = validate_password("user000", "lockeradmin2025");
+    let contains_username = validate_password("user000", "User000-Locker");
+
+    // Assert
+    assert_eq!(valid, Ok(()));
+    assert_eq!(too_short, Err(PasswordPolicyError::TooShort));
+    assert_eq!(too_long, Err(PasswordPolicyError::TooLong));
+    assert_eq!(whitespace, Err(PasswordPolicyError::InvalidCharacter));
+    assert_eq!(too_simple, Err(PasswordPolicyError::TooSimple));
+    assert_eq!(contains_username, Err(PasswordPolicyError::ContainsUsername));
+}
+
+// 正常系＝superadminは管理者の登録、一覧取得、無効化、パスワードの再設定ができる
+#[rocket::async_test]
+#[ignore]
+async fn manage_admin() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    let superadmin = staff_cookie(&client, "test_superadmin", AdminRole::Superadmin).await;
+
+    // Act
+    let created = client.post(uri!("/api/admin", controller::admin_create))
+        .header(ContentType::JSON)
+        .json(&AdminCreateRequest{
+            username: String::from("test_staff"),
+            password: String::from("Locker-Admin2025"),
+            role: String::from("locker_staff"),
+        })
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let list = client.get(uri!("/api/admin", controller::admin_list))
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let login = client.post(uri!("/api", locker::login))
+        .header(ContentType::JSON)
+        .json(&LoginFormRequest{
+            username: String::from("test_staff"),
+            password: String::from("Locker-Admin2025"),
+        })
+        .dispatch().await;
+    let reset = client.post(uri!("/api/admin", controller::admin_password_reset(username = "test_staff")))
+        .header(ContentType::JSON)
+        .json(&AdminPasswordResetRequest{
+            password: String::from("Circle-Admin2026"),
+        })
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let disabled = client.patch(uri!("/api/admin", controller::admin_update(username = "test_staff")))
+        .header(ContentType::JSON)
+        .json(&AdminUpdateRequest{
+            role: None,
+            disabled: Some(true),
+        })
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let login_disabled = client.post(uri!("/api", locker::login))
+        .header(ContentType::JSON)
+        .json(&LoginFormRequest{
+            username: String::from("test_staff"),
+            password: String::from("Circle-Admin2026"),
+        })
+        .dispatch().await;
+    let admin = app.admin.get_by_name("test_staff").await.unwrap();
+
+    delete_admin(&client, "test_staff").await;
+    delete_admin(&client, "test_superadmin").await;
+
+    // Assert
+    assert_eq!(created.status(), Status::Created);
+
+    assert_eq!(list.status(), Status::Ok);
+    let list = list.into_json::<AdminListResponse>().await.unwrap();
+    let staff = list.data.iter().find(|admin| admin.username == "test_staff").unwrap();
+    assert_eq!(staff.role, AdminRole::LockerStaff);
+    assert!(!staff.disabled);
+
+    assert_eq!(login.status(), Status::Created);
+    assert_eq!(reset.status(), Status::Ok);
+    assert_eq!(disabled.status(), Status::Ok);
+    assert_eq!(login_disabled.status(), Status::Forbidden);
+    assert_eq!(login_disabled.cookies().get("token"), None);
+    assert!(admin.disabled);
+}
+
+// 正常系＝パスワードを再設定、または無効化した管理者のセッションは失効する
+#[rocket::async_test]
+#[ignore]
+async fn revoke_sessions() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let superadmin = staff_cookie(&client, "test_superadmin", AdminRole::Superadmin).await;
+    let staff = staff_cookie(&client, "test_staff", AdminRole::CircleStaff).await;
+
+    // Act
+    let reset = client.post(uri!("/api/admin", controller::admin_password_reset(username = "test_staff")))
+        .header(ContentType::JSON)
+        .json(&AdminPasswordResetRequest{
+            password: String::from("Circle-Admin2026"),
+        })
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let after_reset = client.get("/api/admin/circle/list")
+        .cookie(staff)
+        .dispatch().await;
+    let staff = session_cookie(&client, "test_staff", AdminRole::CircleStaff).await;
+    let disabled = client.patch(uri!("/api/admin", controller::admin_update(username = "test_staff")))
+        .header(ContentType::JSON)
+        .json(&AdminUpdateRequest{
+            role: None,
+            disabled: Some(true),
+        })
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let after_disabled = client.get("/api/admin/circle/list")
+        .cookie(staff)
+        .dispatch().await;
+
+    delete_admin(&client, "test_staff").await;
+    delete_admin(&client, "test_superadmin").await;
+
+    // Assert
+    assert_eq!(reset.status(), Status::Ok);
+    assert_eq!(after_reset.status(), Status::Unauthorized);
+    assert_eq!(disabled.status(), Status::Ok);
+    assert_eq!(after_disabled.status(), Status::Unauthorized);
+}
+
+// 正常系＝管理者は自身のパスワードを変更でき、現在のセッション以外のセッションは失効する
+#[rocket::async_test]
+#[ignore]
+async fn change_own_password() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    app.admin.register("test_staff", "Locker-Admin2025", &AdminRole::CircleStaff).await.unwrap();
+    let staff = session_cookie(&client, "test_staff", AdminRole::CircleStaff).await;
+    let other = session_cookie(&client, "test_staff", AdminRole::CircleStaff).await;
+
+    // Act
+    let wrong_password = client.put(uri!("/api/admin", controller::admin_password_change))
+        .header(ContentType::JSON)
+        .json(&AdminPasswordChangeRequest{
+            current_password: String::from("Wrong-Password1"),
+            new_password: String::from("Circle-Admin2026"),
+        })
+        .cookie(staff.clone())
+        .dispatch().await;
+    let changed = client.put(uri!("/api/admin", controller::admin_password_change))
+        .header(ContentType::JSON)
+        .json(&AdminPasswordChangeRequest{
+            current_password: String::from("Locker-Admin2025"),
+            new_password: String::from("Circle-Admin2026"),
+        })
+        .cookie(staff.clone())
+        .dispatch().await;
+    let by_current = client.get("/api/admin/circle/list")
+        .cookie(staff)
+        .dispatch().await;
+    let by_other = client.get("/api/admin/circle/list")
+        .cookie(other)
+        .dispatch().await;
+    let login = client.post(uri!("/api", locker::login))
+        .header(ContentType::JSON)
+        .json(&LoginFormRequest{
+            username: String::from("test_staff"),
+            password: String::from("Circle-Admin2026"),
+        })
+        .dispatch().await;
+
+    delete_admin(&client, "test_staff").await;
+
+    // Assert
+    assert_eq!(wrong_password.status(), Status::BadRequest);
+    assert_eq!(changed.status(), Status::Ok);
+    assert_eq!(by_current.status(), Status::Ok);
+    assert_eq!(by_other.status(), Status::Unauthorized);
+    assert_eq!(login.status(), Status::Created);
+}
+
+// 異常系＝ポリシーを満たさないパスワード、重複する管理者、自身の無効化、superadmin以外からのリクエストは拒否される
+#[rocket::async_test]
+#[ignore]
+async fn invalid_request() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    app.admin.register("test_staff", "Locker-Admin2025", &AdminRole::LockerStaff).await.unwrap();
+    let staff = session_cookie(&client, "test_staff", AdminRole::LockerStaff).await;
+    let superadmin = staff_cookie(&client, "test_superadmin", AdminRole::Superadmin).await;
+
+    // Act
+    let weak_password = client.post(uri!("/api/admin", controller::admin_create))
+        .header(ContentType::JSON)
+        .json(&AdminCreateRequest{
+            username: String::from("test_weak"),
+            password: String::from("0000"),
+            role: String::from("locker_staff"),
+        })
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let duplicated = client.post(uri!("/api/admin", controller::admin_create))
+        .header(ContentType::JSON)
+        .json(&AdminCreateRequest{
+            username: String::from("test_staff"),
+            password: String::from("Locker-Admin2025"),
+            role: String::from("circle_staff"),
+        })
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let disable_self = client.patch(uri!("/api/admin", controller::admin_update(username = "test_superadmin")))
+        .header(ContentType::JSON)
+        .json(&AdminUpdateRequest{
+            role: None,
+            disabled: Some(true),
+        })
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let not_found = client.post(uri!("/api/admin", controller::admin_password_reset(username = "test_unknown")))
+        .header(ContentType::JSON)
+        .json(&AdminPasswordResetRequest{
+            password: String::from("Circle-Admin2026"),
+        })
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let forbidden = client.get(uri!("/api/admin", controller::admin_list))
+        .cookie(staff)
+        .dispatch().await;
+
+    delete_admin(&client, "test_staff").await;
+    delete_admin(&client, "test_superadmin").await;
+
+    // Assert
+    assert_eq!(weak_password.status(), Status::BadRequest);
+    assert_eq!(weak_password.into_string().await.unwrap(), "password must be at least 12 characters");
+    assert_eq!(duplicated.status(), Status::Conflict);
+    assert_eq!(disable_self.status(), Status::BadRequest);
+    assert_eq!(not_found.status(), Status::NotFound);
+    assert_eq!(forbidden.status(), Status::Forbidden);
+}
+
+// 異常系＝有効なsuperadminがいなくなる権限の変更と無効化は、同時に行われた場合も拒否される
+#[rocket::async_test]
+#[ignore]
+async fn last_superadmin() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app = client.rocket().state::<App>().unwrap();
+    register_admin(&client, "test_superadmin_a", AdminRole::Superadmin).await;
+    register_admin(&client, "test_superadmin_b", AdminRole::Superadmin).await;
+
+    // テスト用以外の有効なsuperadminを一時的に無効化する
+    let others: Vec<String> = app.admin.get_all().await.unwrap().into_iter()
+        .filter(|admin| admin.role == AdminRole::Superadmin && !admin.disabled && !admin.username.starts_with("test_superadmin_"))
+        .map(|admin| admin.username)
+        .collect();
+    for username in &others {
+        app.admin.update(username, &None, &Some(true)).await.unwrap();
+    }
+
+    // Act
+    let (disable_a, disable_b) = rocket::tokio::join!(
+        app.admin.update("test_superadmin_a", &None, &Some(true)),
+        app.admin.update("test_superadmin_b", &None, &Some(true)),
+    );
+    let remaining = if disable_a.is_ok() { "test_superadmin_b" } else { "test_superadmin_a" };
+    let demoted = app.admin.update(remaining, &Some(AdminRole::LockerStaff), &None).await;
+
+    for username in &others {
+        app.admin.update(username, &None, &Some(false)).await.unwrap();
+    }
+    delete_admin(&client, "test_superadmin_a").await;
+    delete_admin(&client, "test_superadmin_b").await;
+
+    // Assert
+    assert!(disable_a.is_ok() != disable_b.is_ok());
+    assert_eq!(disable_a.err().or(disable_b.err()), Some(Status::Conflict));
+    assert_eq!(demoted.err(), Some(Status::Conflict));
+}
@@ -0,0 +1,155 @@
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use std::env;
+use utils::{router::rocket, admin::{register_admin, staff_cookie, token_cookie, delete_admin}};
+use rocket::local::asynchronous::Client;
+use rocket::http::{Status, Cookie};
+use rocket::tokio::task;
+use dotenv::dotenv;
+use tus_yuurikai_system::domain::admin::AdminRole;
+use tus_yuurikai_system::infrastructure::router::App;
+use tus_yuurikai_system::utils::jwt::{encode_jwt, decode_jwt};
+use chrono::Duration;
+use uuid::Uuid;
+
+// 正常系＝superadminは全ての操作を行え、それ以外は自身の権限の操作のみ行える
+#[test]
+fn role_permits() {
+
+    // Act
+    let superadmin = [AdminRole::Superadmin, AdminRole::LockerStaff, AdminRole::CircleStaff].map(|required| AdminRole::Superadmin.permits(required));
+    let locker_staff = [AdminRole::Superadmin, AdminRole::LockerStaff, AdminRole::CircleStaff].map(|required| AdminRole::LockerStaff.permits(required));
+    let circle_staff = [AdminRole::Superadmin, AdminRole::LockerStaff, AdminRole::CircleStaff].map(|required| AdminRole::CircleStaff.permits(required));
+
+    // Assert
+    assert_eq!(superadmin, [true, true, true]);
+    assert_eq!(locker_staff, [false, true, false]);
+    assert_eq!(circle_staff, [false, false, true]);
+}
+
+// 正常系＝jwtに管理者の権限とセッションのidが含まれる
+#[test]
+fn role_in_jwt() {
+
+    // Arrange
+    dotenv().ok();
+    let key = env::var("TOKEN_KEY").expect("token key must be set");
+
+    // Act
+    let session_id = Uuid::from_u128(1);
+    let token = encode_jwt("test_admin", AdminRole::LockerStaff, session_id, Duration::hours(1), &key);
+    let claims = decode_jwt(&token).unwrap();
+
+    // Assert
+    assert_eq!(claims.sub, "test_admin");
+    assert_eq!(claims.role, AdminRole::LockerStaff);
+    assert_eq!(claims.jti, session_id);
+}
+
+// 異常系＝jwtがない、不正、期限切れ、または管理者が存在しないリクエストは401で拒否される
+#[rocket::async_test]
+#[ignore]
+async fn unauthorized() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    delete_admin(&client, "test_deleted").await;
+
+    // Act
+    let no_token = client.get("/api/admin/mail-outbox")
+        .dispatch().await;
+    let invalid_token = client.get("/api/admin/mail-outbox")
+        .cookie(Cookie::new("token", "invalid"))
+        .dispatch().await;
+    let expired_token = client.get("/api/admin/mail-outbox")
+        .cookie(token_cookie("test_expired", AdminRole::Superadmin, Duration::minutes(-2)))
+        .dispatch().await;
+    let deleted_admin = client.get("/api/admin/mail-outbox")
+        .cookie(token_cookie("test_deleted", AdminRole::Superadmin, Duration::hours(1)))
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(no_token.status(), Status::Unauthorized);
+    assert_eq!(no_token.into_string().await.unwrap(), "request is unauthorized");
+    assert_eq!(invalid_token.status(), Status::Unauthorized);
+    assert_eq!(invalid_token.into_string().await.unwrap(), "request token is not valid");
+    assert_eq!(expired_token.status(), Status::Unauthorized);
+    assert_eq!(deleted_admin.status(), Status::Unauthorized);
+    assert_eq!(deleted_admin.into_string().await.unwrap(), "request token is not valid");
+}
+
+// 異常系＝権限のない管理機能へのリクエストは403で拒否される
+#[rocket::async_test]
+#[ignore]
+async fn forbidden_role() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let locker_staff = staff_cookie(&client, "test_locker_staff", AdminRole::LockerStaff).await;
+    let circle_staff = staff_cookie(&client, "test_circle_staff", AdminRole::CircleStaff).await;
+    let superadmin = staff_cookie(&client, "test_superadmin", AdminRole::Superadmin).await;
+
+    // Act
+    let circle_by_locker_staff = client.get("/api/admin/circle/list")
+        .cookie(locker_staff.clone())
+        .dispatch().await;
+    let locker_by_circle_staff = client.get("/api/admin/locker/lockers")
+        .cookie(circle_staff)
+        .dispatch().await;
+    let mail_by_locker_staff = client.get("/api/admin/mail-outbox")
+        .cookie(locker_staff)
+        .dispatch().await;
+    let mail_by_superadmin = client.get("/api/admin/mail-outbox")
+        .cookie(superadmin)
+        .dispatch().await;
+
+    delete_admin(&client, "test_locker_staff").await;
+    delete_admin(&client, "test_circle_staff").await;
+    delete_admin(&client, "test_superadmin").await;
+
+    // Assert
+    assert_eq!(circle_by_locker_staff.status(), Status::Forbidden);
+    assert_eq!(circle_by_locker_staff.into_string().await.unwrap(), "request is forbidden");
+    assert_eq!(locker_by_circle_staff.status(), Status::Forbidden);
+    assert_eq!(mail_by_locker_staff.status(), Status::Forbidden);
+    assert_eq!(mail_by_superadmin.status(), Status::Ok);
+}
+
+// 異常系＝権限の変更と無効化は発行済みのjwtにも反映される
+#[rocket::async_test]
+#[ignore]
+async fn role_from_db() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app = client.rocket().state::<App>().unwrap();
+    // superadminとして発行したjwtを保持したまま、locker_staffに変更する
+    let cookie = staff_cookie(&client, "test_demoted", AdminRole::Superadmin).await;
+    register_admin(&client, "test_demoted", AdminRole::LockerStaff).await;
+
+    // Act
+    let demoted = client.get("/api/admin/mail-outbox")
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let allowed = client.get("/api/admin/locker/lockers")
+        .cookie(cookie.clone())
+        .dispatch().await;
+
+    let repository = app.admin.admin_repository.clone();
+    task::spawn_blocking(move || {
+        repository.update(String::from("test_demoted"), None, None, Some(true))
+    }).await.unwrap().unwrap();
+    let disabled = client.get("/api/admin/locker/lockers")
+        .cookie(cookie)
+        .dispatch().await;
+
+    delete_admin(&client, "test_demoted").await;
+
+    // Assert
+    assert_eq!(demoted.status(), Status::Forbidden);
+    assert_eq!(allowed.status(), Status::Ok);
+    assert_eq!(disabled.status(), Status::Forbidden);
+    assert_eq!(disabled.into_string().await.unwrap(), "admin is disabled");
+}
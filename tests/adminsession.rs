@@ -0,0 +1,217 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use std::env;
+use utils::{router::rocket, admin::{register_admin, session_cookie, staff_cookie, delete_admin}};
+use rocket::local::asynchronous::Client;
+use rocket::http::{Status, Cookie};
+use dotenv::dotenv;
+use tus_yuurikai_system::adapters::{controller::{self, locker}, httpmodels::{AdminSessionListResponse, AdminSessionRevokeResponse}};
+use tus_yuurikai_system::domain::admin::AdminRole;
+use tus_yuurikai_system::infrastructure::router::App;
+use tus_yuurikai_system::usecase::admin_session::{AdminSessionUsecase, SessionPolicy};
+use tus_yuurikai_system::utils::jwt::encode_jwt;
+use chrono::{NaiveDate, TimeDelta, Duration};
+
+// 正常系＝セッションの有効期限はアクセスするごとに延長され、ログインからの最大時間を超えない
+#[test]
+fn session_policy() {
+
+    // Arrange
+    let policy = SessionPolicy{
+        idle: TimeDelta::hours(1),
+        max_lifetime: TimeDelta::hours(12),
+    };
+    let created_at = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+
+    // Act
+    let start = policy.expires_at(created_at, created_at);
+    let sliding = policy.expires_at(created_at, created_at + TimeDelta::hours(3));
+    let capped = policy.expires_at(created_at, created_at + TimeDelta::minutes(690));
+
+    // Assert
+    assert_eq!(start, created_at + TimeDelta::hours(1));
+    assert_eq!(sliding, created_at + TimeDelta::hours(4));
+    assert_eq!(capped, created_at + TimeDelta::hours(12));
+}
+
+// 正常系＝自身の有効なセッションを確認し、他のセッションを失効させることができる
+#[rocket::async_test]
+#[ignore]
+async fn list_and_revoke() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let current = staff_cookie(&client, "test_session", AdminRole::LockerStaff).await;
+    let other = session_cookie(&client, "test_session", AdminRole::LockerStaff).await;
+
+    // Act
+    let list = client.get("/api/admin/sessions")
+        .cookie(current.clone())
+        .dispatch().await;
+    let list = list.into_json::<AdminSessionListResponse>().await.unwrap();
+    let other_id = list.data.iter().find(|session| !session.current).unwrap().session_id.clone();
+
+    let revoked = client.delete(uri!("/api/admin", controller::admin_session_revoke(session_id = other_id.clone())))
+        .cookie(current.clone())
+        .dispatch().await;
+    let revoked_again = client.delete(uri!("/api/admin", controller::admin_session_revoke(session_id = other_id)))
+        .cookie(current.clone())
+        .dispatch().await;
+    let by_revoked = client.get("/api/admin/locker/lockers")
+        .cookie(other)
+        .dispatch().await;
+    let by_current = client.get("/api/admin/locker/lockers")
+        .cookie(current.clone())
+        .dispatch().await;
+    let remaining = client.get("/api/admin/sessions")
+        .cookie(current)
+        .dispatch().await;
+
+    delete_admin(&client, "test_session").await;
+
+    // Assert
+    assert_eq!(list.data.len(), 2);
+    assert!(list.data.iter().all(|session| session.username == "test_session"));
+    assert_eq!(list.data.iter().filter(|session| session.current).count(), 1);
+
+    assert_eq!(revoked.status(), Status::Ok);
+    assert_eq!(revoked_again.status(), Status::NotFound);
+    assert_eq!(by_revoked.status(), Status::Unauthorized);
+    assert_eq!(by_revoked.into_string().await.unwrap(), "session has expired or been revoked");
+    assert_eq!(by_current.status(), Status::Ok);
+    assert_eq!(remaining.into_json::<AdminSessionListResponse>().await.unwrap().data.len(), 1);
+}
+
+// 正常系＝superadminは他の管理者の全てのセッションを失効させることができる
+#[rocket::async_test]
+#[ignore]
+async fn revoke_all() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let superadmin = staff_cookie(&client, "test_superadmin", AdminRole::Superadmin).await;
+    let staff = staff_cookie(&client, "test_staff", AdminRole::CircleStaff).await;
+    session_cookie(&client, "test_staff", AdminRole::CircleStaff).await;
+
+    // Act
+    let list = client.get("/api/admin/sessions?username=test_staff")
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let revoked = client.delete("/api/admin/sessions?username=test_staff")
+        .cookie(superadmin.clone())
+        .dispatch().await;
+    let by_staff = client.get("/api/admin/circle/list")
+        .cookie(staff)
+        .dispatch().await;
+
+    delete_admin(&client, "test_superadmin").await;
+    delete_admin(&client, "test_staff").await;
+
+    // Assert
+    assert_eq!(list.into_json::<AdminSessionListResponse>().await.unwrap().data.len(), 2);
+    assert_eq!(revoked.status(), Status::Ok);
+    assert_eq!(revoked.into_json::<AdminSessionRevokeResponse>().await.unwrap().revoked, 2);
+    assert_eq!(by_staff.status(), Status::Unauthorized);
+}
+
+// 正常系＝ログアウトしたセッションのjwtは使用できない
+#[rocket::async_test]
+#[ignore]
+async fn logout_revokes_session() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = staff_cookie(&client, "test_logout", AdminRole::LockerStaff).await;
+
+    // Act
+    let logout = client.post(uri!("/api", locker::logout))
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let after_logout = client.get("/api/admin/locker/lockers")
+        .cookie(cookie)
+        .dispatch().await;
+
+    delete_admin(&client, "test_logout").await;
+
+    // Assert
+    assert_eq!(logout.status(), Status::Ok);
+    assert_eq!(after_logout.status(), Status::Unauthorized);
+}
+
+// 正常系＝有効期限が近いjwtはセッションが有効であれば再発行される
+#[rocket::async_test]
+#[ignore]
+async fn refresh_token() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app = client.rocket().state::<App>().unwrap();
+    register_admin(&client, "test_refresh", AdminRole::LockerStaff).await;
+    let session = app.admin_session.start("test_refresh", None).await.unwrap();
+
+    dotenv().ok();
+    let key = env::var("TOKEN_KEY").expect("token key must be set");
+    let expiring = encode_jwt("test_refresh", AdminRole::LockerStaff, session.session_id, Duration::minutes(1), &key);
+    let fresh = encode_jwt("test_refresh", AdminRole::LockerStaff, session.session_id, app.admin_session.policy.idle, &key);
+
+    // Act
+    let by_expiring = client.get("/api/admin/locker/lockers")
+        .cookie(Cookie::new("token", expiring))
+        .dispatch().await;
+    let by_fresh = client.get("/api/admin/locker/lockers")
+        .cookie(Cookie::new("token", fresh))
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(by_expiring.status(), Status::Ok);
+    assert!(by_expiring.cookies().get("token").is_some());
+    assert_eq!(by_fresh.status(), Status::Ok);
+    assert!(by_fresh.cookies().get("token").is_none());
+
+    delete_admin(&client, "test_refresh").await;
+}
+
+// 異常系＝superadmin以外は他の管理者のセッションを操作できない
+#[rocket::async_test]
+#[ignore]
+async fn forbidden_other_admin() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let staff = staff_cookie(&client, "test_staff_a", AdminRole::LockerStaff).await;
+    staff_cookie(&client, "test_staff_b", AdminRole::LockerStaff).await;
+    let superadmin = staff_cookie(&client, "test_superadmin_a", AdminRole::Superadmin).await;
+
+    let list = client.get("/api/admin/sessions?username=test_staff_b")
+        .cookie(superadmin)
+        .dispatch().await;
+    let other_id = list.into_json::<AdminSessionListResponse>().await.unwrap().data[0].session_id.clone();
+
+    // Act
+    let list_other = client.get("/api/admin/sessions?username=test_staff_b")
+        .cookie(staff.clone())
+        .dispatch().await;
+    let revoke_other = client.delete(uri!("/api/admin", controller::admin_session_revoke(session_id = other_id)))
+        .cookie(staff.clone())
+        .dispatch().await;
+    let revoke_all_other = client.delete("/api/admin/sessions?username=test_staff_b")
+        .cookie(staff.clone())
+        .dispatch().await;
+    let invalid_id = client.delete(uri!("/api/admin", controller::admin_session_revoke(session_id = "not-uuid")))
+        .cookie(staff)
+        .dispatch().await;
+
+    delete_admin(&client, "test_staff_a").await;
+    delete_admin(&client, "test_staff_b").await;
+    delete_admin(&client, "test_superadmin_a").await;
+
+    // Assert
+    assert_eq!(list_other.status(), Status::Forbidden);
+    assert_eq!(revoke_other.status(), Status::Forbidden);
+    assert_eq!(revoke_all_other.status(), Status::Forbidden);
+    assert_eq!(invalid_id.status(), Status::BadRequest);
+}
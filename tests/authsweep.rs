@@ -0,0 +1,96 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::{router::rocket, setup::setup_db, admin::admin_cookie};
+use rocket::local::asynchronous::Client;
+use rocket::http::Status;
+use tus_yuurikai_system::adapters::{controller, httpmodels::AuthSweepStatusResponse};
+use tus_yuurikai_system::domain::student::UserInfo;
+use tus_yuurikai_system::usecase::auth::AuthUsecase;
+use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use chrono::TimeDelta;
+
+// 認証待ちのレコードを作成する
+async fn setup_pending_auth(app: &App) {
+    let mainuser = &UserInfo{
+            student_id: String::from("4622999"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    let couser = &UserInfo{
+            student_id: String::from("4622000"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    match app.auth.locker_register(mainuser, couser, "main_auth", false).await{
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err)},
+    };
+}
+
+// 正常系＝max_ageを過ぎたレコードのみ削除される
+#[rocket::async_test]
+#[ignore]
+async fn sweep() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    setup_pending_auth(&app).await;
+
+    // Act
+    let fresh = app.auth.sweep_stale(TimeDelta::hours(1)).await.unwrap();
+    let stale = app.auth.sweep_stale(TimeDelta::zero()).await.unwrap();
+
+    // Assert
+    assert_eq!(fresh.auth, 0);
+    assert_eq!(fresh.locker_auth_info, 0);
+    assert_eq!(stale.auth, 1);
+    assert_eq!(stale.locker_auth_info, 1);
+    assert_eq!(stale.circle_auth_info, 0);
+    assert_eq!(app.auth.last_sweep().unwrap().ran_at, stale.ran_at);
+}
+
+// 正常系＝一度も実行されていない場合はlastRunがnull
+#[rocket::async_test]
+#[ignore]
+async fn status_not_run() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+
+    // Act
+    let response = client.get(uri!("/api/admin", controller::auth_sweep_status))
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.into_json::<AuthSweepStatusResponse>().await.unwrap();
+    assert!(body.last_run.is_none());
+    assert_eq!(body.deleted_auth, 0);
+}
+
+// 異常系＝JWTがない
+#[rocket::async_test]
+#[ignore]
+async fn status_unauthorized() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+
+    // Act
+    let response = client.get(uri!("/api/admin", controller::auth_sweep_status))
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Unauthorized);
+}
@@ -0,0 +1,157 @@
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::setup::setup_db;
+use rocket::http::Status;
+use tus_yuurikai_system::domain::student::UserInfo;
+use tus_yuurikai_system::usecase::auth::AuthUsecase;
+use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use tus_yuurikai_system::utils::token::{generate_token, hash_token, verify_token_hash};
+
+fn users() -> (UserInfo, UserInfo) {
+    let mainuser = UserInfo{
+            student_id: String::from("4622999"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    let couser = UserInfo{
+            student_id: String::from("4622000"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    (mainuser, couser)
+}
+
+// 正常系＝32文字の英数字のtokenが生成され、ハッシュ値と照合できる
+#[test]
+fn generate_and_verify() {
+
+    // Act
+    let token = generate_token();
+    let token_hash = hash_token(&token);
+
+    // Assert
+    assert_eq!(token.len(), 32);
+    assert!(token.chars().all(|c| c.is_ascii_alphanumeric()));
+    assert_eq!(token_hash.len(), 64);
+    assert!(verify_token_hash(&token, &token_hash));
+    assert!(!verify_token_hash(&generate_token(), &token_hash));
+    assert!(!verify_token_hash(&token, ""));
+}
+
+// 正常系＝DBにはtokenのハッシュ値のみが保存される
+#[rocket::async_test]
+#[ignore]
+async fn stored_as_hash() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    let (mainuser, couser) = users();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let auth = match app.auth.locker_register(&mainuser, &couser, "main_auth", false).await{
+        Ok(auth) => auth,
+        Err(err) => {panic!("{}", err)},
+    };
+
+    // Act
+    let by_plain = app.auth.auth_repository.get_by_token(auth.main_auth_token.clone());
+    let by_hash = app.auth.auth_repository.get_by_token(hash_token(&auth.main_auth_token));
+
+    // Assert
+    assert!(by_plain.is_err());
+    let stored = by_hash.unwrap();
+    assert_eq!(stored.main_auth_token, hash_token(&auth.main_auth_token));
+    assert_eq!(stored.co_auth_token, hash_token(&auth.co_auth_token));
+}
+
+// 異常系＝一度使用したtokenは再利用できない
+#[rocket::async_test]
+#[ignore]
+async fn single_use() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    let (mainuser, couser) = users();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let token = match app.auth.locker_register(&mainuser, &couser, "cancel", true).await{
+        Ok(auth) => auth.main_auth_token,
+        Err(err) => {panic!("{}", err)},
+    };
+
+    // Act
+    let first = app.auth.token_check(token.clone(), "cancel", true).await;
+    let second = app.auth.token_check(token.clone(), "cancel", true).await;
+    // 同じtokenを共有している場合は両方が無効化される
+    let shared = app.auth.token_check(token, "cancel", false).await;
+
+    // Assert
+    assert!(first.is_ok());
+    assert_eq!(second.err(), Some(Status::Unauthorized));
+    assert_eq!(shared.err(), Some(Status::Unauthorized));
+}
+
+// 異常系＝phaseが一致しないtokenは使用済みにならない
+#[rocket::async_test]
+#[ignore]
+async fn phase_mismatch() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    let (mainuser, couser) = users();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let token = match app.auth.locker_register(&mainuser, &couser, "main_auth", false).await{
+        Ok(auth) => auth.main_auth_token,
+        Err(err) => {panic!("{}", err)},
+    };
+
+    // Act
+    let mismatch = app.auth.token_check(token.clone(), "cancel", true).await;
+    let matched = app.auth.token_check(token, "main_auth", true).await;
+
+    // Assert
+    assert_eq!(mismatch.err(), Some(Status::BadRequest));
+    assert!(matched.is_ok());
+}
+
+// 正常系＝auth_checkのtokenは検証しても使用済みにならず、繰り返し確認できる
+#[rocket::async_test]
+#[ignore]
+async fn verify_not_consumed() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    let (mainuser, couser) = users();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let token = match app.auth.locker_register(&mainuser, &couser, "auth_check", false).await{
+        Ok(auth) => auth.main_auth_token,
+        Err(err) => {panic!("{}", err)},
+    };
+
+    // Act
+    let first = app.auth.token_verify(token.clone(), "auth_check", true).await;
+    let second = app.auth.token_verify(token.clone(), "auth_check", true).await;
+    let mismatch = app.auth.token_verify(token, "cancel", true).await;
+
+    // Assert
+    assert!(first.is_ok());
+    assert!(second.is_ok());
+    assert_eq!(first.unwrap().auth_id, second.unwrap().auth_id);
+    assert_eq!(mismatch.err(), Some(Status::BadRequest));
+}
@@ -8,6 +8,9 @@ use rocket::local::asynchronous::Client;
 use rocket::{tokio::task, http::Status};
 use tus_yuurikai_system::adapters::httpmodels::{LockerStatus, LockerStatusResponse};
 use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use tus_yuurikai_system::domain::{locker, student::UserInfo, student_pair::PairInfo};
+use tus_yuurikai_system::usecase::assignment_record::AssignmentRecordUsecase;
+use chrono::{Datelike, Local};
 
 // 正常系
 #[rocket::async_test]
@@ -93,4 +96,48 @@ pub async fn floor_is_not_requested() {
     assert_eq!(response.into_json::<LockerStatusResponse>().await.unwrap(), expected_result);
 
     setup_db(&app).await;
-}
\ No newline at end of file
+}
+
+// 正常系=使用中かどうかは年度ごとに判定する
+#[rocket::async_test]
+pub async fn year_scoped() {
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    setup_db(&app).await;
+
+    let year = Local::now().year();
+    let student_pair = PairInfo{
+        main_user: UserInfo{
+            student_id: String::from("4622999"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        },
+        co_user: UserInfo{
+            student_id: String::from("4622000"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        },
+    };
+    match app.assignment_record.register_with_pair(&student_pair, "2001", &year).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+
+    // Act
+    let current_response = client.get(format!("/api/locker/availability?floor=2&year={}", year)).dispatch().await;
+    let next_response = client.get(format!("/api/locker/availability?floor=2&year={}", year + 1)).dispatch().await;
+
+    // Assert
+    assert_eq!(current_response.status(), Status::Ok);
+    let current = current_response.into_json::<LockerStatusResponse>().await.unwrap();
+    assert_eq!(current.data.iter().find(|locker| locker.locker_id == "2001").unwrap().status, locker::LockerStatus::Occupied);
+
+    assert_eq!(next_response.status(), Status::Ok);
+    let next = next_response.into_json::<LockerStatusResponse>().await.unwrap();
+    assert_eq!(next.data.iter().find(|locker| locker.locker_id == "2001").unwrap().status, locker::LockerStatus::Vacant);
+
+    setup_db(&app).await;
+}
@@ -0,0 +1,92 @@
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::{router::rocket, setup::setup_db};
+use rocket::local::asynchronous::Client;
+use rocket::http::Status;
+use chrono::{Duration, Utc};
+use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use tus_yuurikai_system::usecase::time::TimeUsecase;
+
+// 正常系＝受付期間が設定されていない場合はガードを通過する
+#[rocket::async_test]
+#[ignore]
+async fn not_configured() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    // Act
+    let response = client.post("/api/circle/main-auth?token=invalid-token")
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+// 異常系＝受付期間外
+#[rocket::async_test]
+#[ignore]
+async fn closed() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let start_time = (Utc::now() - Duration::days(14)).naive_utc();
+    let end_time = (Utc::now() - Duration::days(7)).naive_utc();
+    match app.time.register("access_restrictions", &start_time, &end_time, &None).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+
+    // Act
+    let response = client.post("/api/circle/main-auth?token=0000000000000000")
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Forbidden);
+
+    setup_db(&app).await;
+}
+
+// 正常系＝受付期間内はガードを通過する
+#[rocket::async_test]
+#[ignore]
+async fn open() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let start_time = (Utc::now() - Duration::days(1)).naive_utc();
+    let end_time = (Utc::now() + Duration::days(1)).naive_utc();
+    match app.time.register("access_restrictions", &start_time, &end_time, &None).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+
+    // Act
+    let response = client.post("/api/circle/main-auth?token=invalid-token")
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::BadRequest);
+
+    setup_db(&app).await;
+}
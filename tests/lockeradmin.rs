@@ -0,0 +1,602 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::{router::rocket, setup::{setup_db, locker_status}, admin::admin_cookie};
+use rocket::local::asynchronous::Client;
+use rocket::{tokio::task, http::{Status, ContentType}};
+use tus_yuurikai_system::adapters::{controller::locker, httpmodels::{LockerCreateRequest, LockerUpdateRequest, LockerListResponse, LockerImportResponse, LockerAssignmentRequest, LockerReassignmentRequest}};
+use tus_yuurikai_system::infrastructure::{router::{App, AppOption}, mailer::MemoryMailer};
+use tus_yuurikai_system::domain::{locker::LockerStatus, student::UserInfo, student_pair::PairInfo};
+use tus_yuurikai_system::usecase::{assignment_record::AssignmentRecordUsecase, locker::LockerUsecase, mail_outbox::MailOutboxUsecase, student_pair::StudentPairUsecase};
+use chrono::{Datelike, Local};
+
+// テスト用のロッカーを削除する
+async fn cleanup(app: &App) {
+    let locker_repository = app.locker.locker_repository.clone();
+    match task::spawn_blocking(move || {
+        locker_repository.delete(String::from("6999"))
+    }).await {
+        Ok(Ok(_)) => {},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err)
+    }
+}
+
+// 正常系＝状態で絞り込み
+#[rocket::async_test]
+#[ignore]
+async fn list_filter_by_status() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let locker_repository = app.locker.locker_repository.clone();
+    match task::spawn_blocking(move || {
+        locker_repository.update_status_by_id(String::from("2001"), LockerStatus::Maintenance)
+    }).await {
+        Ok(Ok(_)) => {},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err)
+    }
+
+    // Act
+    let response = client.get(uri!("/api/admin/locker", locker::locker_list(Some(2), None::<String>, Some("maintenance"), None::<i32>)))
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.into_json::<LockerListResponse>().await.unwrap();
+    assert_eq!(body.data.len(), 1);
+    assert_eq!(body.data[0].locker_id, "2001");
+    assert_eq!(body.data[0].status, LockerStatus::Maintenance);
+
+    setup_db(&app).await;
+}
+
+// 正常系＝使用中は割り当てから判定して絞り込み
+#[rocket::async_test]
+#[ignore]
+async fn list_filter_by_occupied() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    match app.assignment_record.register_with_pair(&pair_info(), "2001", &Local::now().year()).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+
+    // Act
+    let occupied_response = client.get(uri!("/api/admin/locker", locker::locker_list(Some(2), None::<String>, Some("occupied"), None::<i32>)))
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let next_year_response = client.get(uri!("/api/admin/locker", locker::locker_list(Some(2), None::<String>, Some("occupied"), Some(Local::now().year() + 1))))
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(occupied_response.status(), Status::Ok);
+    let body = occupied_response.into_json::<LockerListResponse>().await.unwrap();
+    assert_eq!(body.data.len(), 1);
+    assert_eq!(body.data[0].locker_id, "2001");
+
+    assert_eq!(next_year_response.status(), Status::Ok);
+    let body = next_year_response.into_json::<LockerListResponse>().await.unwrap();
+    assert!(body.data.is_empty());
+
+    setup_db(&app).await;
+}
+
+// 正常系＝登録、更新、削除
+#[rocket::async_test]
+#[ignore]
+async fn create_update_delete() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+    cleanup(&app).await;
+
+    let create_request = LockerCreateRequest{
+        locker_id: String::from("6999"),
+        location: String::from("講義棟"),
+        status: String::from("vacant"),
+    };
+    let update_request = LockerUpdateRequest{
+        location: None,
+        status: Some(String::from("broken")),
+    };
+
+    // Act
+    let create_response = client.post(uri!("/api/admin/locker", locker::locker_create))
+        .header(ContentType::JSON)
+        .json(&create_request)
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let update_response = client.patch(uri!("/api/admin/locker", locker::locker_update("6999")))
+        .header(ContentType::JSON)
+        .json(&update_request)
+        .cookie(cookie.clone())
+        .dispatch().await;
+
+    let locker_repository = app.locker.locker_repository.clone();
+    let status = match task::spawn_blocking(move || {
+        locker_repository.get_by_id(String::from("6999"))
+    }).await {
+        Ok(Ok(locker)) => {locker.status},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err)
+    };
+
+    let delete_response = client.delete(uri!("/api/admin/locker", locker::locker_delete("6999")))
+        .cookie(cookie.clone())
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(create_response.status(), Status::Created);
+    assert_eq!(update_response.status(), Status::Ok);
+    assert_eq!(status, LockerStatus::Broken);
+    assert_eq!(delete_response.status(), Status::Ok);
+}
+
+// 正常系＝一括登録のdry-runではDBを更新しない
+#[rocket::async_test]
+#[ignore]
+async fn import_dry_run() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+    cleanup(&app).await;
+
+    let csv = String::from("\"2001\",\"講義棟\",\"maintenance\"\n\"6999\",\"講義棟\",\"vacant\"\n");
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::locker_import(dry_run = Some(true))))
+        .header(ContentType::CSV)
+        .body(csv)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.into_json::<LockerImportResponse>().await.unwrap();
+    assert!(body.dry_run);
+    assert_eq!(body.created.len(), 1);
+    assert_eq!(body.created[0].locker_id, "6999");
+    assert_eq!(body.updated.len(), 1);
+    assert_eq!(body.updated[0].status, LockerStatus::Maintenance);
+
+    let locker_repository = app.locker.locker_repository.clone();
+    let status = match task::spawn_blocking(move || {
+        locker_repository.get_by_id(String::from("2001"))
+    }).await {
+        Ok(Ok(locker)) => {locker.status},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err)
+    };
+    assert_eq!(status, LockerStatus::Vacant);
+}
+
+// 正常系＝一括登録、dry_runを省略した場合はDBを更新する
+#[rocket::async_test]
+#[ignore]
+async fn import() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+    cleanup(&app).await;
+
+    let csv = String::from("\"6999\",\"講義棟\",\"vacant\"\n");
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::locker_import(dry_run = _)))
+        .header(ContentType::CSV)
+        .body(csv)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Ok);
+
+    let locker_repository = app.locker.locker_repository.clone();
+    let status = match task::spawn_blocking(move || {
+        locker_repository.get_by_id(String::from("6999"))
+    }).await {
+        Ok(Ok(locker)) => {locker.status},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err)
+    };
+    assert_eq!(status, LockerStatus::Vacant);
+
+    cleanup(&app).await;
+}
+
+// 異常系＝不正なロッカー番号を含む
+#[rocket::async_test]
+#[ignore]
+async fn import_invalid_locker_id() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+
+    let csv = String::from("\"2001\",\"講義棟\",\"vacant\"\n\"7001\",\"講義棟\",\"vacant\"\n");
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::locker_import(dry_run = Some(false))))
+        .header(ContentType::CSV)
+        .body(csv)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::BadRequest);
+    assert_eq!(response.into_string().await.unwrap(), "line 2: invalid locker_id");
+}
+
+// 割り当て用のペアを作成する
+fn pair_info() -> PairInfo {
+    PairInfo{
+        main_user: UserInfo{
+            student_id: String::from("4622999"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        },
+        co_user: UserInfo{
+            student_id: String::from("4622000"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        },
+    }
+}
+
+// 正常系＝メール認証を経ずに割り当て
+#[rocket::async_test]
+#[ignore]
+async fn assignment_create() {
+
+    // Arrange
+    let mailer = MemoryMailer::new();
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let request = LockerAssignmentRequest{
+        data: pair_info(),
+        locker_id: String::from("2001"),
+    };
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::assignment_create))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Created);
+
+    let student_pair = app.student_pair.get_by_id("4622999").await.unwrap().unwrap();
+    let record = app.assignment_record.get_by_pair_id_and_year(&student_pair.pair_id, &Local::now().year()).await.unwrap().unwrap();
+    assert_eq!(record.locker_id, "2001");
+    assert_eq!(locker_status(&app, "2001").await, LockerStatus::Occupied);
+
+    // 2人に登録完了のメールが送信される
+    let report = app.mail_outbox.deliver_due(&mailer, 10).await.unwrap();
+    assert_eq!(report.sent, 2);
+    let sent = mailer.sent();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0].to, "4622999@ed.tus.ac.jp");
+    assert_eq!(sent[1].to, "4622000@ed.tus.ac.jp");
+    assert!(sent[0].content.contains("ロッカー番号：2001"));
+
+    setup_db(&app).await;
+}
+
+// 異常系＝空きでないロッカーには割り当てず、ペアも登録しない
+#[rocket::async_test]
+#[ignore]
+async fn assignment_create_not_vacant() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let locker_repository = app.locker.locker_repository.clone();
+    match task::spawn_blocking(move || {
+        locker_repository.update_status_by_id(String::from("2001"), LockerStatus::Broken)
+    }).await {
+        Ok(Ok(_)) => {},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err)
+    }
+
+    let request = LockerAssignmentRequest{
+        data: pair_info(),
+        locker_id: String::from("2001"),
+    };
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::assignment_create))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Conflict);
+    assert!(app.student_pair.get_by_id("4622999").await.unwrap().is_none());
+
+    setup_db(&app).await;
+}
+
+// 正常系＝別のロッカーへ付け替え
+#[rocket::async_test]
+#[ignore]
+async fn assignment_update() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    match app.assignment_record.register_with_pair(&pair_info(), "2001", &Local::now().year()).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+
+    let request = LockerReassignmentRequest{
+        locker_id: String::from("2002"),
+        prev_status: Some(String::from("broken")),
+    };
+
+    // Act
+    let response = client.patch(uri!("/api/admin/locker", locker::assignment_update("2001")))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Ok);
+
+    let student_pair = app.student_pair.get_by_id("4622999").await.unwrap().unwrap();
+    let record = app.assignment_record.get_by_pair_id_and_year(&student_pair.pair_id, &Local::now().year()).await.unwrap().unwrap();
+    assert_eq!(record.locker_id, "2002");
+    assert_eq!(app.locker.get_by_id("2001").await.unwrap().status, LockerStatus::Broken);
+    assert_eq!(locker_status(&app, "2002").await, LockerStatus::Occupied);
+
+    setup_db(&app).await;
+}
+
+// 異常系＝付け替え元に割り当てがない
+#[rocket::async_test]
+#[ignore]
+async fn assignment_update_not_found() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let request = LockerReassignmentRequest{
+        locker_id: String::from("2002"),
+        prev_status: None,
+    };
+
+    // Act
+    let response = client.patch(uri!("/api/admin/locker", locker::assignment_update("2001")))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(locker_status(&app, "2002").await, LockerStatus::Vacant);
+}
+
+// 異常系＝使用中には手動で変更できない
+#[rocket::async_test]
+#[ignore]
+async fn update_to_occupied() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+
+    let request = LockerUpdateRequest{
+        location: None,
+        status: Some(String::from("occupied")),
+    };
+
+    // Act
+    let response = client.patch(uri!("/api/admin/locker", locker::locker_update("2001")))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+// 異常系＝今年度の割り当てがあるロッカーの状態は変更できない
+#[rocket::async_test]
+#[ignore]
+async fn update_in_use() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    match app.assignment_record.register_with_pair(&pair_info(), "2001", &Local::now().year()).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+
+    let request = LockerUpdateRequest{
+        location: None,
+        status: Some(String::from("maintenance")),
+    };
+
+    // Act
+    let response = client.patch(uri!("/api/admin/locker", locker::locker_update("2001")))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Conflict);
+    assert_eq!(response.into_string().await.unwrap(), "locker is in use");
+    assert_eq!(locker_status(&app, "2001").await, LockerStatus::Occupied);
+
+    setup_db(&app).await;
+}
+
+// 正常系＝一括登録のdry-runでは割り当てがあるロッカーの状態変更をinUseとして返す
+#[rocket::async_test]
+#[ignore]
+async fn import_dry_run_in_use() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    match app.assignment_record.register_with_pair(&pair_info(), "2001", &Local::now().year()).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+
+    let csv = String::from("\"2001\",\"講義棟\",\"maintenance\"\n\"2002\",\"講義棟\",\"maintenance\"\n");
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::locker_import(dry_run = Some(true))))
+        .header(ContentType::CSV)
+        .body(csv)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.into_json::<LockerImportResponse>().await.unwrap();
+    assert_eq!(body.in_use, vec![String::from("2001")]);
+    assert_eq!(body.updated.len(), 1);
+    assert_eq!(body.updated[0].locker_id, "2002");
+
+    setup_db(&app).await;
+}
+
+// 異常系＝一括登録で割り当てがあるロッカーの状態は変更できない
+#[rocket::async_test]
+#[ignore]
+async fn import_in_use() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    match app.assignment_record.register_with_pair(&pair_info(), "2001", &Local::now().year()).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+
+    let csv = String::from("\"2001\",\"講義棟\",\"maintenance\"\n\"2002\",\"講義棟\",\"maintenance\"\n");
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::locker_import(dry_run = Some(false))))
+        .header(ContentType::CSV)
+        .body(csv)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Conflict);
+    assert_eq!(response.into_string().await.unwrap(), "locker is in use: 2001");
+    assert_eq!(locker_status(&app, "2001").await, LockerStatus::Occupied);
+    assert_eq!(locker_status(&app, "2002").await, LockerStatus::Vacant);
+
+    setup_db(&app).await;
+}
+
+// 異常系＝jwtがない
+#[rocket::async_test]
+#[ignore]
+async fn unauthorized() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+
+    // Act
+    let response = client.get(uri!("/api/admin/locker", locker::locker_list(None::<i8>, None::<String>, None::<String>, None::<i32>)))
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Unauthorized);
+}
@@ -0,0 +1,203 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::{router::rocket, setup::{setup_db, locker_status}};
+use rocket::local::asynchronous::Client;
+use rocket::http::Status;
+use tus_yuurikai_system::adapters::controller::locker;
+use tus_yuurikai_system::domain::{assignment::AssignmentInfo, student_pair::PairInfo, student::UserInfo, locker::LockerStatus};
+use tus_yuurikai_system::usecase::{student_pair::StudentPairUsecase, student::StudentUsecase, auth::AuthUsecase, assignment_record::AssignmentRecordUsecase};
+use tus_yuurikai_system::infrastructure::{router::{App, AppOption}, models::StudentPair};
+use tus_yuurikai_system::utils::token::TokenTtl;
+use chrono::{Datelike, Local, TimeDelta};
+
+// ロッカーが割り当て済みのペアを作成する
+async fn setup_assignment(app: &App, mainuser: &UserInfo, couser: &UserInfo) -> StudentPair {
+    let studentpair = &PairInfo{
+        main_user: mainuser.clone(),
+        co_user: couser.clone()
+    };
+
+    // student2人をdbに保存
+    match app.student.register(mainuser).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+    match app.student.register(couser).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+    // studentpairをdbに保存
+    let student_pair = match app.student_pair.register(studentpair).await {
+        Ok(student_pair) => student_pair,
+        Err(err) => {panic!("{}", err);},
+    };
+
+    // ロッカーを割り当て
+    let assignment = &AssignmentInfo{
+        student_id: mainuser.student_id.clone(),
+        locker_id: String::from("2001"),
+    };
+    match app.assignment_record.register(&student_pair, assignment, &Local::now().year()).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+
+    student_pair
+}
+
+// 正常系
+#[rocket::async_test]
+#[ignore]
+async fn normal() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    let mainuser = &UserInfo{
+            student_id: String::from("4622999"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    let couser = &UserInfo{
+            student_id: String::from("4622000"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let student_pair = setup_assignment(&app, mainuser, couser).await;
+
+    // 取り消し用のレコードを保存
+    let token = match app.auth.locker_register(mainuser, couser, &String::from("cancel"), true).await{
+        Ok(auth) => auth.main_auth_token,
+        Err(err) => {panic!("{}", err)},
+    };
+
+    // Act
+    let response = client.get(uri!("/api/locker", locker::cancel(token)))
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_string().await.unwrap(), "success cancel assignment");
+
+    // 割り当てが論理削除されている
+    assert!(app.assignment_record.get_by_pair_id_and_year(&student_pair.pair_id, &Local::now().year()).await.unwrap().is_none());
+
+    // ロッカーが空きに戻っている
+    assert_eq!(locker_status(&app, "2001").await, LockerStatus::Vacant);
+
+    setup_db(&app).await;
+}
+
+// 異常系＝phaseが一致しない
+#[rocket::async_test]
+#[ignore]
+async fn phase_mismatch() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    let mainuser = &UserInfo{
+            student_id: String::from("4622999"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    let couser = &UserInfo{
+            student_id: String::from("4622000"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let student_pair = setup_assignment(&app, mainuser, couser).await;
+
+    // 登録用のレコードを保存
+    let token = match app.auth.locker_register(mainuser, couser, &String::from("auth_check"), true).await{
+        Ok(auth) => auth.main_auth_token,
+        Err(err) => {panic!("{}", err)},
+    };
+
+    // Act
+    let response = client.get(uri!("/api/locker", locker::cancel(token)))
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::BadRequest);
+
+    // 割り当ては残っている
+    assert!(app.assignment_record.get_by_pair_id_and_year(&student_pair.pair_id, &Local::now().year()).await.unwrap().is_some());
+    assert_eq!(locker_status(&app, "2001").await, LockerStatus::Occupied);
+
+    setup_db(&app).await;
+}
+
+// 異常系＝取り消し中のレコードがある場合は新たに作成せず、再送信の間隔が経過していなければ429を返す
+#[rocket::async_test]
+#[ignore]
+async fn rate_limited() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let mut app = App::new(app_option);
+
+    let mainuser = &UserInfo{
+            student_id: String::from("4622999"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    let couser = &UserInfo{
+            student_id: String::from("4622000"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let first = app.auth.locker_cancel_register(mainuser, couser).await.unwrap();
+
+    // Act
+    let limited = app.auth.locker_cancel_register(mainuser, couser).await;
+    app.auth.token_ttl = TokenTtl{
+        resend_interval: TimeDelta::zero(),
+        ..TokenTtl::default()
+    };
+    let reissued = app.auth.locker_cancel_register(mainuser, couser).await.unwrap();
+
+    // Assert
+    assert_eq!(limited.err(), Some(Status::TooManyRequests));
+    assert_eq!(reissued.auth_id, first.auth_id);
+    assert_ne!(reissued.main_auth_token, first.main_auth_token);
+
+    setup_db(&app).await;
+}
+
+// 異常系＝tokenの形式が不正
+#[rocket::async_test]
+#[ignore]
+async fn invalid_token() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let token = String::from("invalid-token");
+
+    // Act
+    let response = client.get(uri!("/api/locker", locker::cancel(token)))
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::BadRequest);
+}
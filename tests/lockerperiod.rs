@@ -0,0 +1,192 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::{router::rocket, setup::setup_db, admin::admin_cookie};
+use rocket::local::asynchronous::Client;
+use rocket::http::{Status, ContentType};
+use tus_yuurikai_system::adapters::{controller::locker, httpmodels::{LockerPeriodSetting, LockerResisterRequest, LockerAssignmentRequest}};
+use tus_yuurikai_system::domain::{assignment::AssignmentInfo, locker::LockerStatus, student::UserInfo, student_pair::PairInfo};
+use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use tus_yuurikai_system::usecase::locker::LockerUsecase;
+use chrono::{Datelike, Duration, Local, SecondsFormat, Utc};
+
+// 正常系＝設定した受付期間を取得できる
+#[rocket::async_test]
+#[ignore]
+async fn setting() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let request = LockerPeriodSetting{
+        start: String::from("2025-04-01T00:00:00.000Z"),
+        end: String::from("2025-04-15T00:00:00.000Z"),
+        year: None,
+    };
+
+    // Act
+    let post_response = client.post(uri!("/api/admin/locker", locker::period_setting_post))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+    let get_response = client.get(uri!("/api/locker", locker::period_setting_get))
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(post_response.status(), Status::Created);
+    assert_eq!(get_response.status(), Status::Ok);
+    let body = get_response.into_json::<LockerPeriodSetting>().await.unwrap();
+    assert_eq!(body.start, "2025-04-01T00:00:00.000Z");
+    assert_eq!(body.end, "2025-04-15T00:00:00.000Z");
+
+    setup_db(&app).await;
+}
+
+// 異常系＝受付期間外
+#[rocket::async_test]
+#[ignore]
+async fn closed() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let setting = LockerPeriodSetting{
+        start: String::from("2024-04-01T00:00:00.000Z"),
+        end: String::from("2024-04-15T00:00:00.000Z"),
+        year: None,
+    };
+    client.post(uri!("/api/admin/locker", locker::period_setting_post))
+        .header(ContentType::JSON)
+        .json(&setting)
+        .cookie(cookie)
+        .dispatch().await;
+
+    let request = LockerResisterRequest{
+        data: AssignmentInfo{
+            student_id: String::from("4622999"),
+            locker_id: String::from("2001"),
+        },
+        auth_id: String::from("00000000-0000-0000-0000-000000000000"),
+    };
+
+    // Act
+    let response = client.post(uri!("/api/locker", locker::locker_register))
+        .header(ContentType::JSON)
+        .json(&request)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Forbidden);
+    assert_eq!(response.into_string().await.unwrap(), "locker registration is closed");
+
+    setup_db(&app).await;
+}
+
+// 異常系＝開始時刻が終了時刻より後
+#[rocket::async_test]
+#[ignore]
+async fn invalid_period() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+
+    let request = LockerPeriodSetting{
+        start: String::from("2025-04-15T00:00:00.000Z"),
+        end: String::from("2025-04-01T00:00:00.000Z"),
+        year: None,
+    };
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::period_setting_post))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+// 正常系＝受付期間に設定した年度で割り当てる
+#[rocket::async_test]
+#[ignore]
+async fn registration_year() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    // 来年度の登録を受け付ける
+    let year = Local::now().year() + 1;
+    let setting = LockerPeriodSetting{
+        start: (Utc::now() - Duration::days(1)).to_rfc3339_opts(SecondsFormat::Millis, true),
+        end: (Utc::now() + Duration::days(1)).to_rfc3339_opts(SecondsFormat::Millis, true),
+        year: Some(year),
+    };
+    let post_response = client.post(uri!("/api/admin/locker", locker::period_setting_post))
+        .header(ContentType::JSON)
+        .json(&setting)
+        .cookie(cookie.clone())
+        .dispatch().await;
+    assert_eq!(post_response.status(), Status::Created);
+
+    let request = LockerAssignmentRequest{
+        data: PairInfo{
+            main_user: UserInfo{
+                student_id: String::from("4622999"),
+                family_name: String::from("テスト太郎"),
+                given_name: String::from("てすと太郎")
+            },
+            co_user: UserInfo{
+                student_id: String::from("4622000"),
+                family_name: String::from("テスト太郎"),
+                given_name: String::from("てすと太郎")
+            },
+        },
+        locker_id: String::from("2001"),
+    };
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::assignment_create))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Created);
+
+    let get_response = client.get(uri!("/api/locker", locker::period_setting_get))
+        .dispatch().await;
+    assert_eq!(get_response.into_json::<LockerPeriodSetting>().await.unwrap().year, Some(year));
+
+    // 来年度は使用中、今年度は空きのまま
+    let next = app.locker.get_by_floor(&Some(2), &year).await.unwrap();
+    assert_eq!(next.iter().find(|locker| locker.locker_id == "2001").unwrap().status, LockerStatus::Occupied);
+    let current = app.locker.get_by_floor(&Some(2), &(year - 1)).await.unwrap();
+    assert_eq!(current.iter().find(|locker| locker.locker_id == "2001").unwrap().status, LockerStatus::Vacant);
+
+    setup_db(&app).await;
+}
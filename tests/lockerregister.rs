@@ -8,9 +8,10 @@ use utils::{router::rocket, setup::setup_db};
 use rocket::local::asynchronous::Client;
 use rocket::{tokio::task, http::{Status, ContentType}};
 use tus_yuurikai_system::adapters::{controller::locker, httpmodels::LockerResisterRequest};
-use tus_yuurikai_system::domain::{assignment::AssignmentInfo, student_pair::PairInfo, student::UserInfo};
-use tus_yuurikai_system::usecase::{student_pair::StudentPairUsecase, student::StudentUsecase, auth::AuthUsecase};
+use tus_yuurikai_system::domain::{assignment::AssignmentInfo, student_pair::PairInfo, student::UserInfo, locker::LockerStatus};
+use tus_yuurikai_system::usecase::{student_pair::StudentPairUsecase, student::StudentUsecase, auth::AuthUsecase, assignment_record::AssignmentRecordUsecase};
 use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use chrono::{Datelike, Local};
 
 // 正常系
 #[rocket::async_test]
@@ -361,11 +362,11 @@ async fn locker_status_unavailable() {
         auth_id: auth_id.to_string(),
     };
 
-    // 該当lockerのstatusをunavailableに変更
+    // 該当lockerのstatusをout-of-workに変更
     let data = request.data.clone();
     let locker_repository = app.locker.locker_repository.clone();
     match task::spawn_blocking(move || {
-        locker_repository.update_status_by_id(data.locker_id, String::from("unavailable"))
+        locker_repository.update_status_by_id(data.locker_id, LockerStatus::OutOfWork)
     }).await {
         Ok(Ok(_)) => {},
         Ok(Err(err)) => panic!("{}", err),
@@ -460,4 +461,169 @@ async fn same_pair_arleady_registered() {
     assert_eq!(response.into_string().await.unwrap(), "same pair already exists");
 
     setup_db(&app).await;
-}
\ No newline at end of file
+}
+
+// 正常系:同一ロッカーへの同時リクエストは1件のみ成功する
+#[rocket::async_test]
+#[ignore]
+async fn concurrent_requests_for_same_locker() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    const N: usize = 5;
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let mut requests = Vec::new();
+    for i in 0..N {
+        let mainuser = &UserInfo{
+                student_id: format!("46229{:02}", i),
+                family_name: String::from("テスト太郎"),
+                given_name: String::from("てすと太郎")
+            };
+        let couser = &UserInfo{
+                student_id: format!("46120{:02}", i),
+                family_name: String::from("テスト太郎"),
+                given_name: String::from("てすと太郎")
+            };
+
+        let studentpair = &PairInfo{
+            main_user: mainuser.clone(),
+            co_user: couser.clone()
+        };
+
+        // student2人をdbに保存
+        match app.student.register(mainuser).await {
+            Ok(_) => {},
+            Err(err) => {panic!("{}", err);},
+        };
+        match app.student.register(couser).await {
+            Ok(_) => {},
+            Err(err) => {panic!("{}", err);},
+        };
+        // studentpairをdbに保存
+        match app.student_pair.register(studentpair).await {
+            Ok(_) => {},
+            Err(err) => {panic!("{}", err);},
+        };
+
+        // 認証完了用のレコードを保存
+        let auth_id = match app.auth.locker_register(mainuser, couser, &String::from("auth_check"), true).await{
+            Ok(auth) => auth.auth_id,
+            Err(err) => {panic!("{}", err)},
+        };
+
+        requests.push(LockerResisterRequest{
+            data: AssignmentInfo{
+                student_id: mainuser.student_id.clone(),
+                locker_id: String::from("2001"),
+            },
+            auth_id: auth_id.to_string(),
+        });
+    }
+
+    // Act
+    let responses = rocket::futures::future::join_all(requests.iter().map(|request| {
+        client.post(uri!("/api/locker", locker::locker_register))
+            .header(ContentType::JSON)
+            .json(request)
+            .dispatch()
+    })).await;
+
+    // Assert
+    let mut created = 0;
+    for response in responses {
+        if response.status() == Status::Created {
+            created += 1;
+        } else {
+            assert_eq!(response.status(), Status::BadRequest);
+            assert_eq!(response.into_string().await.unwrap(), "This locker is not vacant");
+        }
+    }
+    assert_eq!(created, 1);
+
+    let assignment_record_repository = app.assignment_record.assignment_record_repository.clone();
+    let records = match task::spawn_blocking(move || {
+        assignment_record_repository.get_all()
+    }).await {
+        Ok(Ok(records)) => records,
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err),
+    };
+    assert_eq!(records.iter().filter(|record| record.locker_id == "2001").count(), 1);
+
+    setup_db(&app).await;
+}
+
+// 異常系:同じペアに別のロッカーを割り当てようとすると、ペアの一意制約違反として区別される
+#[rocket::async_test]
+#[ignore]
+async fn pair_already_has_locker() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    let mainuser = &UserInfo{
+            student_id: String::from("4622999"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    let couser = &UserInfo{
+            student_id: String::from("4622000"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    let studentpair = &PairInfo{
+        main_user: mainuser.clone(),
+        co_user: couser.clone()
+    };
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    // student2人をdbに保存
+    match app.student.register(mainuser).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+    match app.student.register(couser).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+    // studentpairをdbに保存
+    let user_pair = match app.student_pair.register(studentpair).await {
+        Ok(student_pair) => student_pair,
+        Err(err) => {panic!("{}", err);},
+    };
+
+    // 先に2001を割り当てる
+    let assignment = AssignmentInfo{
+        student_id: String::from("4622999"),
+        locker_id: String::from("2001"),
+    };
+    match app.assignment_record.register(&user_pair, &assignment, &Local::now().year()).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+
+    let assignment = AssignmentInfo{
+        student_id: String::from("4622999"),
+        locker_id: String::from("2002"),
+    };
+
+    // Act
+    let result = app.assignment_record.register(&user_pair, &assignment, &Local::now().year()).await;
+
+    // Assert
+    match result {
+        Ok(_) => panic!("pair must not have two lockers"),
+        Err(status) => assert_eq!(status, Status::UnprocessableEntity),
+    }
+
+    setup_db(&app).await;
+}
@@ -0,0 +1,216 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::{router::rocket, setup::setup_db, admin::admin_cookie};
+use rocket::local::asynchronous::Client;
+use rocket::tokio;
+use rocket::http::{Status, ContentType};
+use tus_yuurikai_system::adapters::{controller::locker, httpmodels::{LockerPeriodSetting, LockerResendRequest}};
+use tus_yuurikai_system::domain::student::UserInfo;
+use tus_yuurikai_system::usecase::auth::AuthUsecase;
+use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use tus_yuurikai_system::utils::token::TokenTtl;
+use chrono::TimeDelta;
+
+fn users() -> (UserInfo, UserInfo) {
+    let mainuser = UserInfo{
+            student_id: String::from("4622999"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    let couser = UserInfo{
+            student_id: String::from("4622000"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    (mainuser, couser)
+}
+
+// 正常系＝共同利用者の学籍番号からも認証中のレコードを取得でき、tokenが再発行される
+#[rocket::async_test]
+#[ignore]
+async fn normal() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let mut app = App::new(app_option);
+    app.auth.token_ttl = TokenTtl{
+        resend_interval: TimeDelta::zero(),
+        ..TokenTtl::default()
+    };
+    let (mainuser, couser) = users();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let auth = match app.auth.locker_register(&mainuser, &couser, "main_auth", false).await{
+        Ok(auth) => auth,
+        Err(err) => {panic!("{}", err)},
+    };
+
+    // Act
+    let result = app.auth.locker_resend(&couser.student_id).await;
+
+    // Assert
+    let (pending, auth_info) = result.unwrap();
+    assert_eq!(pending.auth_id, auth.auth_id);
+    assert_eq!(pending.phase, "main_auth");
+    assert_eq!(auth_info.main_student_id, mainuser.student_id);
+    assert_ne!(pending.main_auth_token, auth.main_auth_token);
+    // 再発行前のtokenは使用できない
+    assert_eq!(app.auth.token_check(auth.main_auth_token, "main_auth", true).await.err(), Some(Status::Unauthorized));
+    let refreshed = app.auth.token_check(pending.main_auth_token, "main_auth", true).await.unwrap();
+    assert!(refreshed.updated_at > auth.updated_at);
+}
+
+// 異常系＝認証中のレコードがない
+#[rocket::async_test]
+#[ignore]
+async fn not_found() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    // Act
+    let result = app.auth.locker_resend("4622999").await;
+
+    // Assert
+    assert_eq!(result.err(), Some(Status::NotFound));
+}
+
+// 異常系＝前回の送信から再送信の間隔が経過していない
+#[rocket::async_test]
+#[ignore]
+async fn rate_limited() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    let (mainuser, couser) = users();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    match app.auth.locker_register(&mainuser, &couser, "main_auth", false).await{
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err)},
+    };
+
+    // Act
+    let result = app.auth.locker_resend(&mainuser.student_id).await;
+
+    // Assert
+    assert_eq!(result.err(), Some(Status::TooManyRequests));
+}
+
+// 異常系＝同時に再送信された場合は1回のみ再発行される
+#[rocket::async_test]
+#[ignore]
+async fn concurrent() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let mut app = App::new(app_option);
+    app.auth.token_ttl = TokenTtl{
+        resend_interval: TimeDelta::seconds(1),
+        ..TokenTtl::default()
+    };
+    let (mainuser, couser) = users();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    match app.auth.locker_register(&mainuser, &couser, "main_auth", false).await{
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err)},
+    };
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+
+    // Act
+    let (first, second) = tokio::join!(
+        app.auth.locker_resend(&mainuser.student_id),
+        app.auth.locker_resend(&couser.student_id),
+    );
+
+    // Assert
+    let results = [first.err(), second.err()];
+    assert_eq!(results.iter().filter(|err| err.is_none()).count(), 1);
+    assert!(results.contains(&Some(Status::TooManyRequests)));
+}
+
+// 異常系＝学籍番号の形式が不正
+#[rocket::async_test]
+#[ignore]
+async fn invalid_student_id() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let request = LockerResendRequest{
+        student_id: String::from("0000000"),
+        recaptcha_token: String::from("token"),
+    };
+
+    // Act
+    let response = client.post(uri!("/api/locker", locker::locker_resend))
+        .header(ContentType::JSON)
+        .json(&request)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+// 異常系＝受付期間外は再送信できない
+#[rocket::async_test]
+#[ignore]
+async fn closed() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    let (mainuser, couser) = users();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    match app.auth.locker_register(&mainuser, &couser, "main_auth", false).await{
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err)},
+    };
+
+    let setting = LockerPeriodSetting{
+        start: String::from("2024-04-01T00:00:00.000Z"),
+        end: String::from("2024-04-15T00:00:00.000Z"),
+        year: None,
+    };
+    client.post(uri!("/api/admin/locker", locker::period_setting_post))
+        .header(ContentType::JSON)
+        .json(&setting)
+        .cookie(cookie)
+        .dispatch().await;
+
+    let request = LockerResendRequest{
+        student_id: mainuser.student_id.clone(),
+        recaptcha_token: String::from("token"),
+    };
+
+    // Act
+    let response = client.post(uri!("/api/locker", locker::locker_resend))
+        .header(ContentType::JSON)
+        .json(&request)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Forbidden);
+
+    setup_db(&app).await;
+}
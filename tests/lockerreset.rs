@@ -5,15 +5,32 @@ extern crate tus_yuurikai_system;
 mod utils;
 
 use std::env;
-use utils::{router::rocket, setup::setup_db};
+use utils::{router::rocket, setup::{setup_db, locker_status}, admin::{admin_cookie, token_cookie}};
 use rocket::local::asynchronous::Client;
-use rocket::{tokio::task, http::{Status, ContentType, Cookie}};
+use rocket::{tokio::task, http::{Status, ContentType}};
 use dotenv::dotenv;
 use tus_yuurikai_system::adapters::{controller::locker, httpmodels::LockerResetRequest};
-use tus_yuurikai_system::utils::jwt::encode_jwt;
 use tus_yuurikai_system::infrastructure::router::{App, AppOption};
-use chrono::Duration;
-
+use tus_yuurikai_system::domain::{admin::AdminRole, locker::LockerStatus, student::UserInfo, student_pair::PairInfo};
+use tus_yuurikai_system::usecase::assignment_record::AssignmentRecordUsecase;
+use chrono::{Datelike, Duration, Local};
+
+
+// 割り当て用のペアを作成する
+fn pair_info(main_id: &str, co_id: &str) -> PairInfo {
+    PairInfo{
+        main_user: UserInfo{
+            student_id: String::from(main_id),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        },
+        co_user: UserInfo{
+            student_id: String::from(co_id),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        },
+    }
+}
 
 // 正常系
 #[rocket::async_test]
@@ -35,26 +52,16 @@ async fn normal() {
     };
 
     // jwtをCookieに保存
-    let username = String::from("test_admin");
-    let key = env::var("TOKEN_KEY").expect("token key must be set");
-    let token = encode_jwt(&username, Duration::hours(1), &key);
-    let cookie = Cookie::build(("token", token))
-        .path("/")
-        .secure(true)
-        .http_only(true);
+    let cookie = admin_cookie(&client).await;
 
-    let locker_repository = app.locker.locker_repository.clone();
-    match task::spawn_blocking(move || {
-        locker_repository.update_status_by_id(String::from("2001"), String::from("occupied"))
-    }).await {
-        Ok(Ok(_)) => {},
-        Ok(Err(err)) => panic!("{}", err),
-        Err(err) => panic!("{}", err)
+    match app.assignment_record.register_with_pair(&pair_info("4622999", "4622000"), "2001", &Local::now().year()).await {
+        Ok(_) => {},
+        Err(err) => panic!("{}", err),
     }
 
     let locker_repository = app.locker.locker_repository.clone();
     match task::spawn_blocking(move || {
-        locker_repository.update_status_by_id(String::from("2002"), String::from("out-of-work"))
+        locker_repository.update_status_by_id(String::from("2002"), LockerStatus::OutOfWork)
     }).await {
         Ok(Ok(_)) => {},
         Ok(Err(err)) => panic!("{}", err),
@@ -69,43 +76,17 @@ async fn normal() {
         .dispatch().await;
 
     // Assert
-    let locker_repository = app.locker.locker_repository.clone();
-    let status = match task::spawn_blocking(move || {
-        locker_repository.get_by_id(String::from("2001"))
-    }).await {
-        Ok(Ok(locker)) => {locker.status},
-        Ok(Err(err)) => panic!("{}", err),
-        Err(err) => panic!("{}", err)
-    };
-    assert_eq!(status, String::from("vacant"));
-
-    let locker_repository = app.locker.locker_repository.clone();
-    let status = match task::spawn_blocking(move || {
-        locker_repository.get_by_id(String::from("2002"))
-    }).await {
-        Ok(Ok(locker)) => {locker.status},
-        Ok(Err(err)) => panic!("{}", err),
-        Err(err) => panic!("{}", err)
-    };
-    let locker_repository = app.locker.locker_repository.clone();
-    assert_eq!(status, String::from("out-of-work"));
-
-    let status = match task::spawn_blocking(move || {
-        locker_repository.get_by_id(String::from("2003"))
-    }).await {
-        Ok(Ok(locker)) => {locker.status},
-        Ok(Err(err)) => panic!("{}", err),
-        Err(err) => panic!("{}", err)
-    };
-    assert_eq!(status, String::from("vacant"));
-
     assert_eq!(response.status(), Status::Ok);
     assert_eq!(response.into_string().await.unwrap(), "successfully reset locker");
 
+    assert_eq!(locker_status(&app, "2001").await, LockerStatus::Vacant);
+    assert_eq!(locker_status(&app, "2002").await, LockerStatus::OutOfWork);
+    assert_eq!(locker_status(&app, "2003").await, LockerStatus::Vacant);
+
     setup_db(&app).await;
 }
 
-// 正常系:モンキーテスト
+// 正常系:故障中のロッカーは空きに戻さない
 #[rocket::async_test]
 #[ignore]
 async fn out_of_work_locker_exists() {
@@ -125,27 +106,20 @@ async fn out_of_work_locker_exists() {
     };
 
     // jwtをCookieに保存
-    let username = String::from("test_admin");
-    let key = env::var("TOKEN_KEY").expect("token key must be set");
-    let token = encode_jwt(&username, Duration::hours(1), &key);
-    let cookie = Cookie::build(("token", token))
-        .path("/")
-        .secure(true)
-        .http_only(true);
-
-    const VCT: &str = "vacant";
-    const OCP: &str = "occupied";
-    const OOW: &str = "out-of-work";
-
-    let target_locker = vec!["2001", "3100", "4013", "5031", "6003"];
-    let target_status = vec![OCP, OOW, VCT, OCP, OOW];
-
-    for _i in 0..target_locker.len() {
+    let cookie = admin_cookie(&client).await;
+
+    // 2001、5031は割り当て、3100、6003は故障中にする
+    let assigned = [("2001", "4622999", "4622000"), ("5031", "4622998", "4622001")];
+    for (locker_id, main_id, co_id) in assigned {
+        match app.assignment_record.register_with_pair(&pair_info(main_id, co_id), locker_id, &Local::now().year()).await {
+            Ok(_) => {},
+            Err(err) => panic!("{}", err),
+        }
+    }
+    for locker_id in ["3100", "6003"] {
         let locker_repository = app.locker.locker_repository.clone();
-        let locker = target_locker.clone();
-        let status = target_status.clone();
         match task::spawn_blocking(move || {
-            locker_repository.update_status_by_id(String::from(locker[_i]), String::from(status[_i]))
+            locker_repository.update_status_by_id(String::from(locker_id), LockerStatus::OutOfWork)
         }).await {
             Ok(Ok(_)) => {},
             Ok(Err(err)) => panic!("{}", err),
@@ -161,27 +135,16 @@ async fn out_of_work_locker_exists() {
         .dispatch().await;
 
     // Assert
-
-    for _i in 0..target_locker.len() {
-        let locker_repository = app.locker.locker_repository.clone();
-        let locker = target_locker.clone();
-        let status = match task::spawn_blocking(move || {
-            locker_repository.get_by_id(String::from(locker[_i]))
-        }).await {
-            Ok(Ok(locker)) => {locker.status},
-            Ok(Err(err)) => panic!("{}", err),
-            Err(err) => panic!("{}", err),
-        };
-        if target_status[_i] == OOW {
-            assert_eq!(status, String::from(OOW));
-        } else {
-            assert_eq!(status, String::from(VCT));
-        }
-    }
-
     assert_eq!(response.status(), Status::Ok);
     assert_eq!(response.into_string().await.unwrap(), "successfully reset locker");
 
+    for locker_id in ["2001", "4013", "5031"] {
+        assert_eq!(locker_status(&app, locker_id).await, LockerStatus::Vacant);
+    }
+    for locker_id in ["3100", "6003"] {
+        assert_eq!(locker_status(&app, locker_id).await, LockerStatus::OutOfWork);
+    }
+
     setup_db(&app).await;
 }
 
@@ -203,17 +166,11 @@ async fn request_password_is_not_valid() {
     };
 
     // jwtをCookieに保存
-    let username = String::from("test_admin");
-    let key = env::var("TOKEN_KEY").expect("token key must be set");
-    let token = encode_jwt(&username, Duration::hours(1), &key);
-    let cookie = Cookie::build(("token", token))
-        .path("/")
-        .secure(true)
-        .http_only(true);
+    let cookie = admin_cookie(&client).await;
 
     let locker_repository = app.locker.locker_repository.clone();
     match task::spawn_blocking(move || {
-        locker_repository.update_status_by_id(String::from("2001"), String::from("occupied"))
+        locker_repository.update_status_by_id(String::from("2001"), LockerStatus::Occupied)
     }).await {
         Ok(Ok(_)) => {},
         Ok(Err(err)) => panic!("{}", err),
@@ -283,16 +240,11 @@ async fn jwt_is_not_valid() {
 
     // jwtをCookieに保存
     let username = env::var("ADMIN_USER_NAME").expect("admin username must be set");
-    let key = env::var("TOKEN_KEY").expect("token key must be set");
-    let token = encode_jwt(&username, Duration::minutes(-2), &key);
-    let cookie = Cookie::build(("token", token))
-        .path("/")
-        .secure(true)
-        .http_only(true);
+    let cookie = token_cookie(&username, AdminRole::Superadmin, Duration::minutes(-2));
 
     let locker_repository = app.locker.locker_repository.clone();
     match task::spawn_blocking(move || {
-        locker_repository.update_status_by_id(String::from("2001"), String::from("occupied"))
+        locker_repository.update_status_by_id(String::from("2001"), LockerStatus::Occupied)
     }).await {
         Ok(Ok(_)) => {},
         Ok(Err(err)) => panic!("{}", err),
@@ -0,0 +1,149 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use std::env;
+use utils::{router::rocket, setup::setup_db, admin::admin_cookie};
+use rocket::local::asynchronous::Client;
+use rocket::http::{Status, ContentType};
+use dotenv::dotenv;
+use tus_yuurikai_system::adapters::{controller::locker, httpmodels::{LockerRolloverRequest, LockerRolloverResponse}};
+use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use tus_yuurikai_system::domain::{student::UserInfo, student_pair::PairInfo};
+use tus_yuurikai_system::usecase::assignment_record::AssignmentRecordUsecase;
+use chrono::{Datelike, Local};
+
+// ロッカーが割り当て済みのペアを作成する
+async fn setup_assignment(app: &App) {
+    let student_pair = PairInfo{
+        main_user: UserInfo{
+            student_id: String::from("4622999"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        },
+        co_user: UserInfo{
+            student_id: String::from("4622000"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        },
+    };
+    match app.assignment_record.register_with_pair(&student_pair, "2001", &Local::now().year()).await {
+        Ok(_) => {},
+        Err(err) => {panic!("{}", err);},
+    };
+}
+
+// 正常系
+#[rocket::async_test]
+#[ignore]
+async fn normal() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+    setup_assignment(&app).await;
+
+    let request = LockerRolloverRequest{
+        password: env::var("LOCKER_RESET_PASSWORD").expect("locker reset password must be set"),
+        year: Local::now().year(),
+        dry_run: false,
+    };
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::rollover))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.into_json::<LockerRolloverResponse>().await.unwrap();
+    assert!(!body.dry_run);
+    assert_eq!(body.closed_assignments, 1);
+    assert_eq!(body.archived_pairs, 1);
+    assert_eq!(body.reset_lockers, 1);
+    assert_eq!(body.assignments[0].locker_id, "2001");
+    assert!(body.zip_data.is_some());
+
+    // 割り当てが論理削除されている
+    assert!(app.assignment_record.get_all().await.unwrap().iter().all(|record| record.deleted_at.is_some()));
+
+    setup_db(&app).await;
+}
+
+// 正常系＝dry-runではDBを更新しない
+#[rocket::async_test]
+#[ignore]
+async fn dry_run() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+    setup_assignment(&app).await;
+
+    let request = LockerRolloverRequest{
+        password: env::var("LOCKER_RESET_PASSWORD").expect("locker reset password must be set"),
+        year: Local::now().year(),
+        dry_run: true,
+    };
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::rollover))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.into_json::<LockerRolloverResponse>().await.unwrap();
+    assert!(body.dry_run);
+    assert_eq!(body.closed_assignments, 1);
+    assert_eq!(body.reset_lockers, 1);
+    assert!(body.zip_data.is_none());
+
+    // 割り当ては残っている
+    assert!(app.assignment_record.get_all().await.unwrap().iter().all(|record| record.deleted_at.is_none()));
+
+    setup_db(&app).await;
+}
+
+// 異常系＝翌年度は締め切れない
+#[rocket::async_test]
+#[ignore]
+async fn future_year() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+
+    dotenv().ok();
+    let request = LockerRolloverRequest{
+        password: env::var("LOCKER_RESET_PASSWORD").expect("locker reset password must be set"),
+        year: Local::now().year() + 1,
+        dry_run: true,
+    };
+
+    // Act
+    let response = client.post(uri!("/api/admin/locker", locker::rollover))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(response.status(), Status::BadRequest);
+}
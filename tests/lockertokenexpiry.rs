@@ -0,0 +1,114 @@
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::setup::setup_db;
+use rocket::http::Status;
+use tus_yuurikai_system::domain::student::UserInfo;
+use tus_yuurikai_system::usecase::auth::AuthUsecase;
+use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use tus_yuurikai_system::utils::token::TokenTtl;
+use chrono::TimeDelta;
+
+fn users() -> (UserInfo, UserInfo) {
+    let mainuser = UserInfo{
+            student_id: String::from("4622999"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    let couser = UserInfo{
+            student_id: String::from("4622000"),
+            family_name: String::from("テスト太郎"),
+            given_name: String::from("てすと太郎")
+        };
+    (mainuser, couser)
+}
+
+// 正常系
+#[rocket::async_test]
+#[ignore]
+async fn within_ttl() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    let (mainuser, couser) = users();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let token = match app.auth.locker_register(&mainuser, &couser, "main_auth", false).await{
+        Ok(auth) => auth.main_auth_token,
+        Err(err) => {panic!("{}", err)},
+    };
+
+    // Act
+    let result = app.auth.token_check(token, "main_auth", true).await;
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+// 異常系
+// 有効期限を過ぎたtoken
+#[rocket::async_test]
+#[ignore]
+async fn expired() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let mut app = App::new(app_option);
+    app.auth.token_ttl = TokenTtl{
+        main_auth: TimeDelta::zero(),
+        ..TokenTtl::default()
+    };
+    let (mainuser, couser) = users();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let auth = match app.auth.locker_register(&mainuser, &couser, "main_auth", false).await{
+        Ok(auth) => auth,
+        Err(err) => {panic!("{}", err)},
+    };
+
+    // Act
+    let expired = app.auth.token_check(auth.main_auth_token.clone(), "main_auth", true).await;
+    // phaseが更新されると有効期限も更新される
+    app.auth.update_phase(&auth.auth_id, String::from("co_auth")).await.unwrap();
+    let updated = app.auth.token_check(auth.co_auth_token, "co_auth", false).await;
+
+    // Assert
+    assert_eq!(expired.err(), Some(Status::Gone));
+    assert!(updated.is_ok());
+}
+
+// 異常系
+// 有効期限を過ぎた取り消し用token
+#[rocket::async_test]
+#[ignore]
+async fn cancel_expired() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let mut app = App::new(app_option);
+    app.auth.token_ttl = TokenTtl{
+        cancel: TimeDelta::zero(),
+        ..TokenTtl::default()
+    };
+    let (mainuser, couser) = users();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let auth = match app.auth.locker_register(&mainuser, &couser, "cancel", true).await{
+        Ok(auth) => auth,
+        Err(err) => {panic!("{}", err)},
+    };
+
+    // Act
+    let result = app.auth.token_check(auth.main_auth_token, "cancel", true).await;
+
+    // Assert
+    assert_eq!(result.err(), Some(Status::Gone));
+}
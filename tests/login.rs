@@ -10,6 +10,7 @@ use rocket::local::asynchronous::Client;
 use rocket::{tokio::task, http::{Status, ContentType}};
 use dotenv::dotenv;
 use tus_yuurikai_system::adapters::{controller::locker, httpmodels::LoginFormRequest};
+use tus_yuurikai_system::domain::admin::AdminRole;
 use tus_yuurikai_system::infrastructure::router::{App, AppOption};
 
 // 正常系
@@ -33,7 +34,7 @@ pub async fn normal() {
     let username = request.username.clone();
     let repository = app.admin.admin_repository.clone();
     match task::spawn_blocking(move || {
-        repository.insert(username, password_hash)
+        repository.insert(username, password_hash, AdminRole::Superadmin)
     }).await {
         Ok(Ok(_)) => {},
         Ok(Err(err)) => panic!("{}", err),
@@ -63,7 +64,7 @@ pub async fn normal() {
     setup_db(&app).await;
 }
 
-// 異常系=存在しないusernameである
+// 異常系=存在しないusernameである、passwordが異なる場合と同じ応答を返す
 #[rocket::async_test]
 pub async fn username_does_not_exist() {
     // Arrange
@@ -86,7 +87,7 @@ pub async fn username_does_not_exist() {
     let password_hash = compute_password_hash(request.password.clone()).unwrap();
     let repository = app.admin.admin_repository.clone();
     match task::spawn_blocking(move || {
-        repository.insert(correct_username, password_hash)
+        repository.insert(correct_username, password_hash, AdminRole::Superadmin)
     }).await {
         Ok(Ok(_)) => {},
         Ok(Err(err)) => panic!("{}", err),
@@ -109,7 +110,7 @@ pub async fn username_does_not_exist() {
     }
 
     // Assert
-    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.status(), Status::BadRequest);
     assert_eq!(response.cookies().get("token"), None);
 
     setup_db(&app).await;
@@ -137,7 +138,7 @@ pub async fn password_is_wrong() {
     let username = request.username.clone();
     let repository = app.admin.admin_repository.clone();
     match task::spawn_blocking(move || {
-        repository.insert(username, password_hash)
+        repository.insert(username, password_hash, AdminRole::Superadmin)
     }).await {
         Ok(Ok(_)) => {},
         Ok(Err(err)) => panic!("{}", err),
@@ -0,0 +1,193 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::{router::rocket, setup::setup_db, admin::{admin_cookie, delete_admin}};
+use rocket::local::asynchronous::Client;
+use rocket::http::{Status, ContentType};
+use tus_yuurikai_system::adapters::{controller::locker, httpmodels::{LoginAttemptListResponse, LoginFormRequest}};
+use tus_yuurikai_system::adapters::repository::login_attempt::LoginFailures;
+use tus_yuurikai_system::domain::admin::{AdminRole, LoginResult};
+use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use tus_yuurikai_system::usecase::{admin::AdminUsecase, login_attempt::LoginThrottlePolicy};
+use tus_yuurikai_system::utils::verify_password::{compute_password_hash, dummy_password_hash};
+use argon2::{Params, PasswordHash};
+use chrono::{NaiveDate, TimeDelta};
+
+fn login_request(username: &str, password: &str) -> LoginFormRequest {
+    LoginFormRequest{
+        username: String::from(username),
+        password: String::from(password),
+    }
+}
+
+// 正常系＝上限を超えて失敗するごとにロックアウトの時間が2倍になり、最大時間を超えない
+#[test]
+fn lockout_backoff() {
+
+    // Arrange
+    let policy = LoginThrottlePolicy::default();
+    let last_failed_at = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    let failures = |count: i64| LoginFailures{
+        count,
+        last_failed_at: Some(last_failed_at),
+    };
+
+    // Act
+    let below = policy.locked_until(failures(4), 5);
+    let first = policy.locked_until(failures(5), 5);
+    let second = policy.locked_until(failures(6), 5);
+    let capped = policy.locked_until(failures(20), 5);
+
+    // Assert
+    assert_eq!(below, None);
+    assert_eq!(first, Some(last_failed_at + TimeDelta::minutes(1)));
+    assert_eq!(second, Some(last_failed_at + TimeDelta::minutes(2)));
+    assert_eq!(capped, Some(last_failed_at + TimeDelta::hours(1)));
+}
+
+// 正常系＝存在しないユーザーネームで照合するハッシュ値は管理者のハッシュ値と同じパラメータで計算される
+#[test]
+fn dummy_hash_params() {
+
+    // Arrange
+    let password_hash = compute_password_hash(String::from("Locker-Admin2025")).unwrap();
+
+    // Act
+    let dummy = Params::try_from(&PasswordHash::new(dummy_password_hash()).unwrap()).unwrap();
+    let stored = Params::try_from(&PasswordHash::new(&password_hash).unwrap()).unwrap();
+
+    // Assert
+    let default = Params::default();
+    assert_eq!((dummy.m_cost(), dummy.t_cost(), dummy.p_cost()), (default.m_cost(), default.t_cost(), default.p_cost()));
+    assert_eq!((dummy.m_cost(), dummy.t_cost(), dummy.p_cost()), (stored.m_cost(), stored.t_cost(), stored.p_cost()));
+}
+
+// 異常系＝連続して失敗したusernameは正しいpasswordでもロックアウトされ、失敗したログインは管理者が確認できる
+#[rocket::async_test]
+#[ignore]
+async fn lockout() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    app.admin.register("test_lockout", "Locker-Admin2025", &AdminRole::LockerStaff).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+
+    // Act
+    let mut failed = Vec::new();
+    for _ in 0..app.login_attempt.policy.username_max_failures {
+        let response = client.post(uri!("/api", locker::login))
+            .header(ContentType::JSON)
+            .json(&login_request("test_lockout", "Wrong-Password1"))
+            .dispatch().await;
+        failed.push(response.status());
+    }
+    let locked = client.post(uri!("/api", locker::login))
+        .header(ContentType::JSON)
+        .json(&login_request("test_lockout", "Locker-Admin2025"))
+        .dispatch().await;
+    let unknown = client.post(uri!("/api", locker::login))
+        .header(ContentType::JSON)
+        .json(&login_request("test_unknown", "Wrong-Password1"))
+        .dispatch().await;
+    let log = client.get("/api/admin/login-attempts?username=test_lockout")
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let locked_log = client.get("/api/admin/login-attempts?result=locked")
+        .cookie(cookie.clone())
+        .dispatch().await;
+
+    delete_admin(&client, "test_lockout").await;
+
+    // Assert
+    assert!(failed.iter().all(|status| *status == Status::BadRequest));
+    assert_eq!(locked.status(), Status::TooManyRequests);
+    assert_eq!(locked.cookies().get("token"), None);
+    // 存在しないusernameはpasswordが異なる場合と同じ応答を返す
+    assert_eq!(unknown.status(), Status::BadRequest);
+
+    assert_eq!(log.status(), Status::Ok);
+    let log = log.into_json::<LoginAttemptListResponse>().await.unwrap();
+    assert_eq!(log.data.len(), 6);
+    assert_eq!(log.data[0].result, LoginResult::Locked);
+    assert!(log.data[1..].iter().all(|attempt| attempt.result == LoginResult::InvalidCredentials));
+
+    let locked_log = locked_log.into_json::<LoginAttemptListResponse>().await.unwrap();
+    assert_eq!(locked_log.data.len(), 1);
+    assert_eq!(locked_log.data[0].username, "test_lockout");
+
+    setup_db(&app).await;
+}
+
+// 異常系＝同時に送られた試行も失敗回数に数え、上限を超えた試行はロックアウトされる
+#[rocket::async_test]
+#[ignore]
+async fn concurrent_lockout() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    app.admin.register("test_lockout", "Locker-Admin2025", &AdminRole::LockerStaff).await.unwrap();
+    let max_failures = app.login_attempt.policy.username_max_failures;
+    let request = login_request("test_lockout", "Wrong-Password1");
+
+    // Act
+    let responses = rocket::futures::future::join_all((0..max_failures * 2).map(|_| {
+        client.post(uri!("/api", locker::login))
+            .header(ContentType::JSON)
+            .json(&request)
+            .dispatch()
+    })).await;
+
+    delete_admin(&client, "test_lockout").await;
+
+    // Assert
+    let failed = responses.iter().filter(|response| response.status() == Status::BadRequest).count();
+    let locked = responses.iter().filter(|response| response.status() == Status::TooManyRequests).count();
+    assert_eq!(failed as i64, max_failures);
+    assert_eq!(locked as i64, max_failures);
+
+    setup_db(&app).await;
+}
+
+// 異常系＝不正な検索条件とjwtのないリクエストは拒否される
+#[rocket::async_test]
+#[ignore]
+async fn search_invalid() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+
+    // Act
+    let invalid_username = client.get("/api/admin/login-attempts?username=%25")
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let invalid_ip_address = client.get("/api/admin/login-attempts?ip_address=abc")
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let invalid_result = client.get("/api/admin/login-attempts?result=unknown")
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let unauthorized = client.get("/api/admin/login-attempts")
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(invalid_username.status(), Status::BadRequest);
+    assert_eq!(invalid_ip_address.status(), Status::BadRequest);
+    assert_eq!(invalid_result.status(), Status::BadRequest);
+    assert_eq!(unauthorized.status(), Status::Unauthorized);
+}
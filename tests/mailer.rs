@@ -0,0 +1,71 @@
+extern crate tus_yuurikai_system;
+
+use std::{env, fs};
+use tus_yuurikai_system::infrastructure::mailer::{FileMailer, MailContent, Mailer, MemoryMailer, SentMail};
+
+// 正常系＝MemoryMailerに送信したメールが送信順に保存される
+#[rocket::async_test]
+async fn memory_mailer() {
+
+    // Arrange
+    let mailer = MemoryMailer::new();
+
+    // Act
+    let first = mailer.send(String::from("4622999@ed.tus.ac.jp"), MailContent{
+        subject: String::from("件名1"),
+        text: String::from("本文1"),
+        html: None,
+    }).await;
+    let second = mailer.send(String::from("4622000@ed.tus.ac.jp"), MailContent{
+        subject: String::from("件名2"),
+        text: String::from("本文2"),
+        html: Some(String::from("<p>本文2</p>")),
+    }).await;
+
+    // Assert
+    assert!(first.is_ok());
+    assert!(second.is_ok());
+    assert_eq!(mailer.sent(), vec![
+        SentMail{
+            to: String::from("4622999@ed.tus.ac.jp"),
+            subject: String::from("件名1"),
+            content: String::from("本文1"),
+            html: None,
+        },
+        SentMail{
+            to: String::from("4622000@ed.tus.ac.jp"),
+            subject: String::from("件名2"),
+            content: String::from("本文2"),
+            html: Some(String::from("<p>本文2</p>")),
+        },
+    ]);
+}
+
+// 正常系＝FileMailerでメールが.emlファイルとして書き出される
+#[rocket::async_test]
+async fn file_mailer() {
+
+    // Arrange
+    let dir = env::temp_dir().join(format!("tus_yuurikai_mail_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let mailer = FileMailer::new(dir.clone());
+
+    // Act
+    let result = mailer.send(String::from("4622999@ed.tus.ac.jp"), MailContent{
+        subject: String::from("subject"),
+        text: String::from("test"),
+        html: Some(String::from("<p>test</p>")),
+    }).await;
+
+    // Assert
+    assert!(result.is_ok());
+    let files: Vec<_> = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].extension().and_then(|ext| ext.to_str()), Some("eml"));
+    let eml = fs::read_to_string(&files[0]).unwrap();
+    assert!(eml.contains("To: User <4622999@ed.tus.ac.jp>"));
+    assert!(eml.contains("Subject: subject"));
+    assert!(eml.contains("multipart/alternative"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,106 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::{router::rocket, setup::setup_db, admin::admin_cookie};
+use rocket::local::asynchronous::Client;
+use rocket::http::{Status, ContentType};
+use tus_yuurikai_system::adapters::{controller::locker, httpmodels::{LockerAssignmentRequest, MailOutboxListResponse}};
+use tus_yuurikai_system::domain::{mail::MailStatus, student::UserInfo, student_pair::PairInfo};
+use tus_yuurikai_system::infrastructure::{router::{App, AppOption}, mailer::MemoryMailer};
+use tus_yuurikai_system::usecase::mail_outbox::MailOutboxUsecase;
+
+// 正常系＝送信したメールを学籍番号、メールアドレスで検索できる
+#[rocket::async_test]
+#[ignore]
+async fn search_mail_log() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    let mailer = MemoryMailer::new();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let created = client.post(uri!("/api/admin/locker", locker::assignment_create))
+        .header(ContentType::JSON)
+        .json(&LockerAssignmentRequest{
+            data: PairInfo{
+                main_user: UserInfo{
+                    student_id: String::from("4622999"),
+                    family_name: String::from("テスト太郎"),
+                    given_name: String::from("てすと太郎")
+                },
+                co_user: UserInfo{
+                    student_id: String::from("4622000"),
+                    family_name: String::from("テスト太郎"),
+                    given_name: String::from("てすと太郎")
+                },
+            },
+            locker_id: String::from("2001"),
+        })
+        .cookie(cookie.clone())
+        .dispatch().await;
+    app.mail_outbox.deliver_due(&mailer, 10).await.unwrap();
+
+    // Act
+    let by_student_id = client.get("/api/admin/mail-log?student_id=4622999")
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let by_email = client.get("/api/admin/mail-log?email=4622000%40ed")
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let all = client.get("/api/admin/mail-log")
+        .cookie(cookie.clone())
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(created.status(), Status::Created);
+
+    assert_eq!(by_student_id.status(), Status::Ok);
+    let by_student_id = by_student_id.into_json::<MailOutboxListResponse>().await.unwrap();
+    assert_eq!(by_student_id.data.len(), 1);
+    assert_eq!(by_student_id.data[0].to_address, "4622999@ed.tus.ac.jp");
+    assert_eq!(by_student_id.data[0].template.as_deref(), Some("locker_assignment_create"));
+    assert_eq!(by_student_id.data[0].status, MailStatus::Sent);
+    assert!(by_student_id.data[0].sent_at.is_some());
+
+    assert_eq!(by_email.status(), Status::Ok);
+    let by_email = by_email.into_json::<MailOutboxListResponse>().await.unwrap();
+    assert_eq!(by_email.data.len(), 1);
+    assert_eq!(by_email.data[0].student_id.as_deref(), Some("4622000"));
+
+    assert_eq!(all.into_json::<MailOutboxListResponse>().await.unwrap().data.len(), 2);
+
+    setup_db(&app).await;
+}
+
+// 異常系＝不正な検索条件とjwtのないリクエストは拒否される
+#[rocket::async_test]
+#[ignore]
+async fn search_mail_log_invalid() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+
+    // Act
+    let invalid_student_id = client.get("/api/admin/mail-log?student_id=abc")
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let invalid_email = client.get("/api/admin/mail-log?email=%25")
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let unauthorized = client.get("/api/admin/mail-log?student_id=4622999")
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(invalid_student_id.status(), Status::BadRequest);
+    assert_eq!(invalid_email.status(), Status::BadRequest);
+    assert_eq!(unauthorized.status(), Status::Unauthorized);
+}
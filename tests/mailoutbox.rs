@@ -0,0 +1,305 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::{router::rocket, setup::setup_db, admin::admin_cookie};
+use rocket::local::asynchronous::Client;
+use rocket::http::Status;
+use async_trait::async_trait;
+use tus_yuurikai_system::adapters::{controller, httpmodels::{MailOutboxListResponse, MailOutboxRetryResponse}};
+use tus_yuurikai_system::domain::mail::{MailAudit, MailStatus};
+use tus_yuurikai_system::infrastructure::{router::{App, AppOption}, mailer::{MailContent, MailError, Mailer, MemoryMailer}};
+use tus_yuurikai_system::usecase::mail_outbox::{MailDeliveryReport, MailOutboxUsecase, MailOutboxUsecaseImpl, MailRetryPolicy};
+use chrono::{NaiveDate, TimeDelta};
+
+// 常に送信に失敗するMailer
+struct FailingMailer;
+
+#[async_trait]
+impl Mailer for FailingMailer {
+    async fn send(&self, _user_address: String, _mail: MailContent) -> Result<(), MailError> {
+        Err(MailError::TransportError(String::from("connection refused")))
+    }
+}
+
+// 送信中に管理者がメールを再送し、leaseが切れた状態にするMailer
+struct RetryingMailer {
+    outbox: MailOutboxUsecaseImpl,
+}
+
+#[async_trait]
+impl Mailer for RetryingMailer {
+    async fn send(&self, _user_address: String, _mail: MailContent) -> Result<(), MailError> {
+        for mail in self.outbox.get_by_status(vec![MailStatus::Pending]).await.unwrap() {
+            self.outbox.retry(&mail.mail_id).await.unwrap();
+        }
+        Ok(())
+    }
+}
+
+fn mail_audit(student_id: &str) -> MailAudit {
+    MailAudit{
+        template: String::from("locker_register_complete"),
+        student_id: Some(String::from(student_id)),
+        ..Default::default()
+    }
+}
+
+fn mail_content() -> MailContent {
+    MailContent{
+        subject: String::from("件名"),
+        text: String::from("本文"),
+        html: None,
+    }
+}
+
+// 正常系＝失敗するごとに再送までの時間が2倍になり、上限に達すると再送しない
+#[test]
+fn retry_backoff() {
+
+    // Arrange
+    let policy = MailRetryPolicy{
+        max_attempts: 4,
+        base_delay: TimeDelta::minutes(1),
+        lease: TimeDelta::minutes(5),
+    };
+    let now = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+
+    // Act
+    let first = policy.next_attempt_at(1, now);
+    let second = policy.next_attempt_at(2, now);
+    let third = policy.next_attempt_at(3, now);
+    let last = policy.next_attempt_at(4, now);
+
+    // Assert
+    assert_eq!(first, Some(now + TimeDelta::minutes(1)));
+    assert_eq!(second, Some(now + TimeDelta::minutes(2)));
+    assert_eq!(third, Some(now + TimeDelta::minutes(4)));
+    assert_eq!(last, None);
+}
+
+// 正常系＝送信待ちのメールが送信され、再度送信されない
+#[rocket::async_test]
+#[ignore]
+async fn deliver_pending() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    let mailer = MemoryMailer::new();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    // Act
+    let mail = app.mail_outbox.enqueue(String::from("4622999@ed.tus.ac.jp"), mail_content(), mail_audit("4622999")).await.unwrap();
+    let report = app.mail_outbox.deliver_due(&mailer, 10).await.unwrap();
+    let report_again = app.mail_outbox.deliver_due(&mailer, 10).await.unwrap();
+
+    // Assert
+    assert_eq!(mail.status, MailStatus::Pending);
+    assert_eq!(report.sent, 1);
+    assert_eq!(report_again.sent, 0);
+    let sent = mailer.sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].to, "4622999@ed.tus.ac.jp");
+    assert_eq!(sent[0].subject, "件名");
+
+    let mails = app.mail_outbox.get_by_status(vec![MailStatus::Sent]).await.unwrap();
+    assert_eq!(mails.len(), 1);
+    assert_eq!(mails[0].attempts, 1);
+    assert!(mails[0].sent_at.is_some());
+    // 送信済みのメールの本文は保存しない
+    assert_eq!(mails[0].body_text, "");
+    assert!(mails[0].body_html.is_none());
+
+    setup_db(&app).await;
+}
+
+// 異常系＝送信中にleaseが切れたメールは結果を保存せず、送信待ちのまま残る
+#[rocket::async_test]
+#[ignore]
+async fn deliver_lease_expired() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+    let mailer = RetryingMailer{ outbox: app.mail_outbox.clone() };
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    app.mail_outbox.enqueue(String::from("4622999@ed.tus.ac.jp"), mail_content(), mail_audit("4622999")).await.unwrap();
+
+    // Act
+    let report = app.mail_outbox.deliver_due(&mailer, 10).await.unwrap();
+    let pending = app.mail_outbox.get_by_status(vec![MailStatus::Pending]).await.unwrap();
+
+    // Assert
+    assert_eq!(report.sent, 0);
+    assert_eq!(report.expired, 1);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].attempts, 0);
+    assert_eq!(pending[0].body_text, "本文");
+
+    setup_db(&app).await;
+}
+
+// 正常系＝保存期間を過ぎた送信に失敗したメールは本文のみ削除されて送信履歴は残り、送信待ちのメールは削除されない
+#[rocket::async_test]
+#[ignore]
+async fn purge() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let mut app = App::new(app_option);
+    app.mail_outbox.retry_policy.max_attempts = 1;
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    app.mail_outbox.enqueue(String::from("4622999@ed.tus.ac.jp"), mail_content(), mail_audit("4622999")).await.unwrap();
+    app.mail_outbox.deliver_due(&FailingMailer, 10).await.unwrap();
+    app.mail_outbox.enqueue(String::from("4622000@ed.tus.ac.jp"), mail_content(), mail_audit("4622000")).await.unwrap();
+
+    // Act
+    let kept = app.mail_outbox.purge(TimeDelta::days(30)).await.unwrap();
+    let cleared = app.mail_outbox.purge(TimeDelta::zero()).await.unwrap();
+    let failed = app.mail_outbox.get_by_status(vec![MailStatus::Failed]).await.unwrap();
+    let pending = app.mail_outbox.get_by_status(vec![MailStatus::Pending]).await.unwrap();
+    let retry = app.mail_outbox.retry(&failed[0].mail_id).await;
+
+    // Assert
+    assert_eq!(kept, 0);
+    assert_eq!(cleared, 1);
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].to_address, "4622999@ed.tus.ac.jp");
+    assert_eq!(failed[0].student_id.as_deref(), Some("4622999"));
+    assert_eq!(failed[0].body_text, "");
+    assert!(failed[0].body_html.is_none());
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].body_text, "本文");
+    // 本文を削除したメールは再送できない
+    assert_eq!(retry, Err(Status::NotFound));
+
+    setup_db(&app).await;
+}
+
+// 異常系＝送信に失敗したメールは再送待ちになり、上限に達するとfailedになる
+#[rocket::async_test]
+#[ignore]
+async fn deliver_failed() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let mut app = App::new(app_option);
+    // 再送時刻まで待たずに再送する
+    app.mail_outbox.retry_policy.max_attempts = 2;
+    app.mail_outbox.retry_policy.base_delay = TimeDelta::zero();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    app.mail_outbox.enqueue(String::from("4622999@ed.tus.ac.jp"), mail_content(), mail_audit("4622999")).await.unwrap();
+
+    // Act
+    let first = app.mail_outbox.deliver_due(&FailingMailer, 10).await.unwrap();
+    let pending = app.mail_outbox.get_by_status(vec![MailStatus::Pending]).await.unwrap();
+    let second = app.mail_outbox.deliver_due(&FailingMailer, 10).await.unwrap();
+    let third = app.mail_outbox.deliver_due(&FailingMailer, 10).await.unwrap();
+    let failed = app.mail_outbox.get_by_status(vec![MailStatus::Failed]).await.unwrap();
+
+    // Assert
+    assert_eq!(first.retrying, 1);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].attempts, 1);
+    assert_eq!(pending[0].last_error.as_deref(), Some("TransportError: connection refused"));
+
+    assert_eq!(second.failed, 1);
+    assert_eq!(third, MailDeliveryReport::default());
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].attempts, 2);
+
+    setup_db(&app).await;
+}
+
+// 正常系＝管理者は送信に失敗したメールを確認し、再送できる
+#[rocket::async_test]
+#[ignore]
+async fn admin_list_and_retry() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let mut app = App::new(app_option);
+    app.mail_outbox.retry_policy.max_attempts = 1;
+    let mailer = MemoryMailer::new();
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let mail = app.mail_outbox.enqueue(String::from("4622999@ed.tus.ac.jp"), mail_content(), mail_audit("4622999")).await.unwrap();
+    app.mail_outbox.enqueue(String::from("4622000@ed.tus.ac.jp"), mail_content(), mail_audit("4622000")).await.unwrap();
+    let failed = app.mail_outbox.deliver_due(&FailingMailer, 10).await.unwrap();
+
+    // Act
+    let list = client.get("/api/admin/mail-outbox?status=failed")
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let retry = client.post(uri!("/api/admin", controller::mail_outbox_retry(mail_id = mail.mail_id.to_string())))
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let retry_failed = client.post(uri!("/api/admin", controller::mail_outbox_retry_failed))
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let delivered = app.mail_outbox.deliver_due(&mailer, 10).await.unwrap();
+    let retry_sent = client.post(uri!("/api/admin", controller::mail_outbox_retry(mail_id = mail.mail_id.to_string())))
+        .cookie(cookie.clone())
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(failed.failed, 2);
+
+    assert_eq!(list.status(), Status::Ok);
+    let list = list.into_json::<MailOutboxListResponse>().await.unwrap();
+    assert_eq!(list.data.len(), 2);
+    assert!(list.data.iter().all(|item| item.status == MailStatus::Failed && item.attempts == 1));
+
+    assert_eq!(retry.status(), Status::Ok);
+    assert_eq!(retry_failed.status(), Status::Ok);
+    assert_eq!(retry_failed.into_json::<MailOutboxRetryResponse>().await.unwrap().retried, 1);
+
+    assert_eq!(delivered.sent, 2);
+    // 送信済みのメールは再送できない
+    assert_eq!(retry_sent.status(), Status::NotFound);
+
+    setup_db(&app).await;
+}
+
+// 異常系＝不正なパラメータとjwtのないリクエストは拒否される
+#[rocket::async_test]
+#[ignore]
+async fn admin_invalid_request() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+
+    // Act
+    let invalid_status = client.get("/api/admin/mail-outbox?status=unknown")
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let invalid_id = client.post(uri!("/api/admin", controller::mail_outbox_retry(mail_id = "not-uuid")))
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let unauthorized = client.get("/api/admin/mail-outbox")
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(invalid_status.status(), Status::BadRequest);
+    assert_eq!(invalid_id.status(), Status::BadRequest);
+    assert_eq!(unauthorized.status(), Status::Unauthorized);
+}
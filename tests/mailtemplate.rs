@@ -0,0 +1,252 @@
+#[macro_use]
+extern crate rocket;
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use std::path::PathBuf;
+use utils::{router::rocket, setup::setup_db, admin::admin_cookie};
+use rocket::local::asynchronous::Client;
+use rocket::http::{Status, ContentType};
+use serde_json::json;
+use tus_yuurikai_system::adapters::{controller::{self, locker}, httpmodels::{LockerAssignmentRequest, MailTemplateRequest, MailTemplateResponse}};
+use tus_yuurikai_system::domain::{student::UserInfo, student_pair::PairInfo};
+use tus_yuurikai_system::infrastructure::{router::{App, AppOption}, mailer::MemoryMailer, mail_template::MailTemplates};
+use tus_yuurikai_system::usecase::mail_outbox::MailOutboxUsecase;
+
+fn templates(locale: &str) -> MailTemplates {
+    MailTemplates::new(PathBuf::from("templates/mail"), locale.to_string()).unwrap()
+}
+
+// 正常系＝テンプレートファイルから件名、テキスト、HTMLが描画される
+#[test]
+fn render_template_file() {
+
+    // Arrange
+    let templates = templates("ja");
+
+    // Act
+    let mail = templates.render("locker_register_complete", json!({
+        "locker_id": "2001",
+        "app_url": "https://example.com",
+        "signature": "署名",
+    }), None).unwrap();
+
+    // Assert
+    assert_eq!(mail.subject, "【ロッカー登録システム】 ロッカー登録完了のお知らせ");
+    assert!(mail.text.contains("ロッカー番号: 2001"));
+    assert!(mail.text.ends_with("署名\n"));
+    assert!(mail.html.unwrap().contains("ロッカー番号: 2001"));
+}
+
+// 正常系＝HTMLでは変数がエスケープされ、テキストではそのまま出力される
+#[test]
+fn render_escape_html() {
+
+    // Arrange
+    let templates = templates("ja");
+
+    // Act
+    let mail = templates.render("locker_main_auth", json!({
+        "family_name": "<b>山田</b>",
+        "given_name": "太郎",
+        "token": "abc",
+        "app_url": "https://example.com",
+        "signature": "署名",
+    }), None).unwrap();
+
+    // Assert
+    assert!(mail.text.starts_with("<b>山田</b>太郎 様"));
+    assert!(mail.text.contains("https://example.com/locker/user-register?method=1&token=abc"));
+    assert!(mail.html.unwrap().contains("&lt;b&gt;山田&lt;&#x2F;b&gt;太郎 様"));
+}
+
+// 正常系＝設定された言語のテンプレートがない場合は日本語のテンプレートを使用する
+#[test]
+fn render_fallback_locale() {
+
+    // Arrange
+    let templates = templates("en");
+
+    // Act
+    let mail = templates.render("locker_cancel_complete", json!({
+        "family_name": "山田",
+        "given_name": "太郎",
+        "locker_id": "2001",
+        "app_url": "https://example.com",
+        "signature": "署名",
+    }), None);
+
+    // Assert
+    assert_eq!(mail.unwrap().subject, "【ロッカー登録システム】 ロッカー登録取り消し完了のお知らせ");
+    assert!(templates.names().iter().any(|name| name == "locker_cancel_complete"));
+    assert_eq!(templates.source("locker_cancel_complete").unwrap().locale, "ja");
+}
+
+// 異常系＝構文エラーのテンプレートは保存できない
+#[test]
+fn validate_syntax_error() {
+
+    // Act
+    let valid = MailTemplates::validate("locker_register_complete", "件名", "{{ locker_id }}", Some("<p>{{ locker_id }}</p>"));
+    let invalid = MailTemplates::validate("locker_register_complete", "件名", "{{ locker_id", None);
+
+    // Assert
+    assert!(valid.is_ok());
+    assert!(invalid.is_err());
+}
+
+// 異常系＝使用できない変数を含むテンプレートは保存できない
+#[test]
+fn validate_unknown_variable() {
+
+    // Act
+    let valid = MailTemplates::validate("locker_main_auth", "{{ family_name }}", "{{ app_url }}?token={{ token }}", None);
+    let invalid = MailTemplates::validate("locker_main_auth", "{{ family_name }}", "{{ app_url }}?token={{ tokn }}", None);
+    let other = MailTemplates::validate("locker_register_complete", "件名", "{{ token }}", None);
+
+    // Assert
+    assert!(valid.is_ok());
+    assert!(invalid.is_err());
+    assert!(other.is_err());
+}
+
+// 正常系＝テンプレートファイルはそれぞれのサンプル値で描画できる
+#[test]
+fn validate_template_files() {
+
+    // Arrange
+    let templates = templates("ja");
+
+    // Act
+    let invalid: Vec<String> = templates.names().into_iter()
+        .filter(|name| {
+            let source = templates.source(name).unwrap();
+            MailTemplates::validate(name, &source.subject, &source.body_text, source.body_html.as_deref()).is_err()
+        })
+        .collect();
+
+    // Assert
+    assert!(invalid.is_empty(), "{:?}", invalid);
+}
+
+// 正常系＝編集したテンプレートが送信に使用され、初期化するとテンプレートファイルに戻る
+#[rocket::async_test]
+#[ignore]
+async fn override_and_reset() {
+
+    // Arrange
+    let mailer = MemoryMailer::new();
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let request = MailTemplateRequest{
+        subject: String::from("ロッカー{{ locker_id }}を登録しました"),
+        body_text: String::from("{{ family_name }}様 {{ locker_id }}"),
+        body_html: None,
+    };
+
+    // Act
+    let put = client.put(uri!("/api/admin", controller::mail_template_put(name = "locker_assignment_create")))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let get = client.get(uri!("/api/admin", controller::mail_template_get(name = "locker_assignment_create")))
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let created = client.post(uri!("/api/admin/locker", locker::assignment_create))
+        .header(ContentType::JSON)
+        .json(&LockerAssignmentRequest{
+            data: PairInfo{
+                main_user: UserInfo{
+                    student_id: String::from("4622999"),
+                    family_name: String::from("テスト太郎"),
+                    given_name: String::from("てすと太郎")
+                },
+                co_user: UserInfo{
+                    student_id: String::from("4622000"),
+                    family_name: String::from("テスト太郎"),
+                    given_name: String::from("てすと太郎")
+                },
+            },
+            locker_id: String::from("2001"),
+        })
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let reset = client.delete(uri!("/api/admin", controller::mail_template_delete(name = "locker_assignment_create")))
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let reset_again = client.delete(uri!("/api/admin", controller::mail_template_delete(name = "locker_assignment_create")))
+        .cookie(cookie.clone())
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(put.status(), Status::Ok);
+    assert_eq!(get.status(), Status::Ok);
+    let template = get.into_json::<MailTemplateResponse>().await.unwrap();
+    assert!(template.overridden);
+    assert_eq!(template.subject, "ロッカー{{ locker_id }}を登録しました");
+
+    assert_eq!(created.status(), Status::Created);
+    let report = app.mail_outbox.deliver_due(&mailer, 10).await.unwrap();
+    assert_eq!(report.sent, 2);
+    let sent = mailer.sent();
+    assert_eq!(sent[0].subject, "ロッカー2001を登録しました");
+    assert_eq!(sent[0].content, "テスト太郎様 2001");
+    assert_eq!(sent[0].html, None);
+
+    assert_eq!(reset.status(), Status::Ok);
+    assert_eq!(reset_again.status(), Status::NotFound);
+
+    setup_db(&app).await;
+}
+
+// 異常系＝存在しないテンプレートと構文エラーのテンプレートは保存できない
+#[rocket::async_test]
+#[ignore]
+async fn override_invalid() {
+
+    // Arrange
+    let client = Client::tracked(rocket()).await.unwrap();
+    let cookie = admin_cookie(&client).await;
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let request = MailTemplateRequest{
+        subject: String::from("件名"),
+        body_text: String::from("{% if locker_id %}"),
+        body_html: None,
+    };
+
+    // Act
+    let not_found = client.put(uri!("/api/admin", controller::mail_template_put(name = "unknown_template")))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let invalid = client.put(uri!("/api/admin", controller::mail_template_put(name = "locker_assignment_create")))
+        .header(ContentType::JSON)
+        .json(&request)
+        .cookie(cookie.clone())
+        .dispatch().await;
+    let unauthorized = client.put(uri!("/api/admin", controller::mail_template_put(name = "locker_assignment_create")))
+        .header(ContentType::JSON)
+        .json(&request)
+        .dispatch().await;
+
+    // Assert
+    assert_eq!(not_found.status(), Status::NotFound);
+    assert_eq!(invalid.status(), Status::BadRequest);
+    assert_eq!(unauthorized.status(), Status::Unauthorized);
+
+    setup_db(&app).await;
+}
@@ -0,0 +1,69 @@
+extern crate tus_yuurikai_system;
+
+use tus_yuurikai_system::utils::student_identity::{StudentIdentity, StudentIdFlow, DEFAULT_LOCKER_PATTERN, DEFAULT_CIRCLE_PATTERN};
+
+// 正常系＝既定の設定ではロッカー登録と団体登録で異なる学籍番号の形式を使用する
+#[test]
+fn default_patterns() {
+
+    // Arrange
+    let identity = StudentIdentity::default();
+
+    // Act
+    let locker = ["4622999", "1512345", "8112345", "4722999", "2622999"].map(|id| identity.is_valid(StudentIdFlow::Locker, id));
+    let circle = ["4622999", "2622999", "9A12345", "5622999", "462299"].map(|id| identity.is_valid(StudentIdFlow::Circle, id));
+
+    // Assert
+    assert_eq!(locker, [true, true, true, false, false]);
+    assert_eq!(circle, [true, true, true, false, false]);
+    assert_eq!(identity.email("4622999"), "4622999@ed.tus.ac.jp");
+}
+
+// 正常系＝メールアドレスのドメインと学籍番号の形式を変更できる
+#[test]
+fn custom_settings() {
+
+    // Arrange
+    let identity = StudentIdentity::new("example.ac.jp", r"^[A-Z]\d{6}$", DEFAULT_CIRCLE_PATTERN, Vec::new()).unwrap();
+
+    // Act
+    let valid = identity.is_valid(StudentIdFlow::Locker, "A123456");
+    let invalid = identity.is_valid(StudentIdFlow::Locker, "4622999");
+
+    // Assert
+    assert!(valid);
+    assert!(!invalid);
+    assert_eq!(identity.email("A123456"), "A123456@example.ac.jp");
+}
+
+// 正常系＝学籍番号の先頭から学部、学科を判定し、最も長く一致したものを使用する
+#[test]
+fn decode_affiliation() {
+
+    // Arrange
+    let affiliations = StudentIdentity::parse_affiliations("4,創域理工学部,\n46, 創域理工学部 , 電気電子情報工学科\n").unwrap();
+    let identity = StudentIdentity::new("ed.tus.ac.jp", DEFAULT_LOCKER_PATTERN, DEFAULT_CIRCLE_PATTERN, affiliations).unwrap();
+
+    // Act
+    let department = identity.affiliation("4622999").unwrap();
+    let faculty = identity.affiliation("4122999").unwrap();
+    let unknown = identity.affiliation("1512345");
+
+    // Assert
+    assert_eq!(department.faculty, "創域理工学部");
+    assert_eq!(department.department, "電気電子情報工学科");
+    assert_eq!(faculty.prefix, "4");
+    assert_eq!(faculty.department, "");
+    assert!(unknown.is_none());
+}
+
+// 異常系＝不正な学籍番号の形式は設定できない
+#[test]
+fn invalid_pattern() {
+
+    // Act
+    let result = StudentIdentity::new("ed.tus.ac.jp", r"^(15\d{5}$", DEFAULT_CIRCLE_PATTERN, Vec::new());
+
+    // Assert
+    assert!(result.is_err());
+}
@@ -0,0 +1,78 @@
+extern crate tus_yuurikai_system;
+
+mod utils;
+
+use utils::setup::setup_db;
+use rocket::tokio::task;
+use tus_yuurikai_system::infrastructure::router::{App, AppOption};
+use tus_yuurikai_system::adapters::repository::RepositoryError;
+
+
+// 正常系
+#[rocket::async_test]
+#[ignore]
+async fn commit() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let unit_of_work = app.auth.unit_of_work.clone();
+    let student_repository = app.auth.student_repository.clone();
+    let student_pair_repository = app.auth.student_pair_repository.clone();
+
+    // Act
+    let result = task::spawn_blocking(move || {
+        unit_of_work.run(|tx| {
+            student_repository.insert_tx(tx, String::from("4622001"), String::from("山田"), String::from("太郎"))?;
+            student_repository.insert_tx(tx, String::from("4622002"), String::from("山田"), String::from("花子"))?;
+            student_pair_repository.insert_tx(tx, String::from("4622001"), String::from("4622002"), 2025)
+        })
+    }).await.unwrap();
+
+    // Assert
+    assert!(result.is_ok());
+
+    let student_repository = app.student.student_repository.clone();
+    let students = task::spawn_blocking(move || {
+        student_repository.get_all()
+    }).await.unwrap().unwrap();
+    assert_eq!(students.len(), 2);
+}
+
+// 異常系
+// 途中で失敗した場合、それまでの操作がロールバックされる
+#[rocket::async_test]
+#[ignore]
+async fn rollback_on_error() {
+
+    // Arrange
+    let app_option = AppOption::new();
+    let app = App::new(app_option);
+
+    // dbの初期化
+    setup_db(&app).await;
+
+    let unit_of_work = app.auth.unit_of_work.clone();
+    let student_repository = app.auth.student_repository.clone();
+
+    // Act
+    let result: Result<(), RepositoryError> = task::spawn_blocking(move || {
+        unit_of_work.run(|tx| {
+            student_repository.insert_tx(tx, String::from("4622001"), String::from("山田"), String::from("太郎"))?;
+            Err(RepositoryError::DieselError(diesel::result::Error::RollbackTransaction))
+        })
+    }).await.unwrap();
+
+    // Assert
+    assert!(result.is_err());
+
+    let student_repository = app.student.student_repository.clone();
+    let students = task::spawn_blocking(move || {
+        student_repository.get_all()
+    }).await.unwrap().unwrap();
+    assert!(students.is_empty());
+}
@@ -3,17 +3,15 @@ extern crate tus_yuurikai_system;
 
 mod utils;
 
-use std::env;
-use utils::{router::rocket, setup::setup_db};
+use utils::{router::rocket, setup::setup_db, admin::session_cookie};
 use rocket::local::asynchronous::Client;
-use rocket::{tokio::task, http::{Status, Cookie}};
+use rocket::{tokio::task, http::Status};
 use dotenv::dotenv;
 use tus_yuurikai_system::adapters::httpmodels::{UserSearchResponse, UserSearchResult};
-use tus_yuurikai_system::domain::{assignment::AssignmentInfo, student_pair::PairInfo, student::UserInfo};
+use tus_yuurikai_system::domain::{admin::AdminRole, assignment::AssignmentInfo, student_pair::PairInfo, student::UserInfo};
 use tus_yuurikai_system::usecase::{assignment_record::AssignmentRecordUsecase, student_pair::StudentPairUsecase, student::StudentUsecase};
 use tus_yuurikai_system::infrastructure::router::{App, AppOption};
-use tus_yuurikai_system::utils::jwt::encode_jwt;
-use chrono::{Datelike, Local, Duration};
+use chrono::{Datelike, Local};
 
 // 正常系
 #[rocket::async_test]
@@ -62,7 +60,7 @@ async fn normal() {
         Err(err) => {panic!("{}", err);},
     };
 
-    match app.assignment_record.register(&student_pair, assignment).await {
+    match app.assignment_record.register(&student_pair, assignment, &Local::now().year()).await {
         Ok(_) => {},
         Err(err) => {panic!("{}", err);},
     }
@@ -76,19 +74,14 @@ async fn normal() {
     let user_name = username.clone();
     let admin_repository = app.admin.admin_repository.clone();
     match task::spawn_blocking(move || {
-        admin_repository.insert(user_name, password_hash)
+        admin_repository.insert(user_name, password_hash, AdminRole::Superadmin)
     }).await {
         Ok(Ok(_)) => {},
         Ok(Err(err)) => panic!("{}", err),
         Err(err) => panic!("{}", err),
     }
 
-    let key = env::var("TOKEN_KEY").expect("token key must be set");
-    let token = encode_jwt(&username, Duration::hours(1), &key);
-    let cookie = Cookie::build(("token", token))
-        .path("/")
-        .secure(true)
-        .http_only(true);
+    let cookie = session_cookie(&client, &username, AdminRole::Superadmin).await;
 
     let year = Local::now().year();
 
@@ -104,6 +97,8 @@ async fn normal() {
             floor: 2,
             main_user: mainuser.clone(),
             co_user: couser.clone(),
+            main_affiliation: None,
+            co_affiliation: None,
             year: Local::now().year(),
         }]
     };
@@ -171,7 +166,7 @@ async fn given_name_is_not_requested() {
         Err(err) => {panic!("{}", err);},
     };
 
-    match app.assignment_record.register(&student_pair, assignment).await {
+    match app.assignment_record.register(&student_pair, assignment, &Local::now().year()).await {
         Ok(_) => {},
         Err(err) => {panic!("{}", err);},
     }
@@ -185,19 +180,14 @@ async fn given_name_is_not_requested() {
     let user_name = username.clone();
     let admin_repository = app.admin.admin_repository.clone();
     match task::spawn_blocking(move || {
-        admin_repository.insert(user_name, password_hash)
+        admin_repository.insert(user_name, password_hash, AdminRole::Superadmin)
     }).await {
         Ok(Ok(_)) => {},
         Ok(Err(err)) => panic!("{}", err),
         Err(err) => panic!("{}", err),
     }
 
-    let key = env::var("TOKEN_KEY").expect("token key must be set");
-    let token = encode_jwt(&username, Duration::hours(1), &key);
-    let cookie = Cookie::build(("token", token))
-        .path("/")
-        .secure(true)
-        .http_only(true);
+    let cookie = session_cookie(&client, &username, AdminRole::Superadmin).await;
 
     let year = Local::now().year();
 
@@ -214,6 +204,8 @@ async fn given_name_is_not_requested() {
             floor: 2,
             main_user: mainuser.clone(),
             co_user: couser.clone(),
+            main_affiliation: None,
+            co_affiliation: None,
             year: Local::now().year(),
         }]
     };
@@ -281,7 +273,7 @@ async fn family_name_is_not_requested() {
         Err(err) => {panic!("{}", err);},
     };
 
-    match app.assignment_record.register(&student_pair, assignment).await {
+    match app.assignment_record.register(&student_pair, assignment, &Local::now().year()).await {
         Ok(_) => {},
         Err(err) => {panic!("{}", err);},
     }
@@ -295,19 +287,14 @@ async fn family_name_is_not_requested() {
     let user_name = username.clone();
     let admin_repository = app.admin.admin_repository.clone();
     match task::spawn_blocking(move || {
-        admin_repository.insert(user_name, password_hash)
+        admin_repository.insert(user_name, password_hash, AdminRole::Superadmin)
     }).await {
         Ok(Ok(_)) => {},
         Ok(Err(err)) => panic!("{}", err),
         Err(err) => panic!("{}", err),
     }
 
-    let key = env::var("TOKEN_KEY").expect("token key must be set");
-    let token = encode_jwt(&username, Duration::hours(1), &key);
-    let cookie = Cookie::build(("token", token))
-        .path("/")
-        .secure(true)
-        .http_only(true);
+    let cookie = session_cookie(&client, &username, AdminRole::Superadmin).await;
 
     let year = Local::now().year();
 
@@ -323,6 +310,8 @@ async fn family_name_is_not_requested() {
             floor: 2,
             main_user: mainuser.clone(),
             co_user: couser.clone(),
+            main_affiliation: None,
+            co_affiliation: None,
             year: Local::now().year(),
         }]
     };
@@ -390,7 +379,7 @@ async fn name_is_not_requested() {
         Err(err) => {panic!("{}", err);},
     };
 
-    match app.assignment_record.register(&student_pair, assignment).await {
+    match app.assignment_record.register(&student_pair, assignment, &Local::now().year()).await {
         Ok(_) => {},
         Err(err) => {panic!("{}", err);},
     }
@@ -404,7 +393,7 @@ async fn name_is_not_requested() {
     let user_name = username.clone();
     let admin_repository = app.admin.admin_repository.clone();
     match task::spawn_blocking(move || {
-        admin_repository.insert(user_name, password_hash)
+        admin_repository.insert(user_name, password_hash, AdminRole::Superadmin)
     }).await {
         Ok(Ok(_)) => {},
         Ok(Err(err)) => panic!("{}", err),
@@ -412,12 +401,7 @@ async fn name_is_not_requested() {
     }
 
 
-    let key = env::var("TOKEN_KEY").expect("token key must be set");
-    let token = encode_jwt(&username, Duration::hours(1), &key);
-    let cookie = Cookie::build(("token", token))
-        .path("/")
-        .secure(true)
-        .http_only(true);
+    let cookie = session_cookie(&client, &username, AdminRole::Superadmin).await;
 
     let year = Local::now().year();
 
@@ -433,6 +417,8 @@ async fn name_is_not_requested() {
             floor: 2,
             main_user: mainuser.clone(),
             co_user: couser.clone(),
+            main_affiliation: None,
+            co_affiliation: None,
             year: Local::now().year(),
         }]
     };
@@ -500,7 +486,7 @@ async fn jwt_does_not_exist() {
         Err(err) => {panic!("{}", err);},
     };
 
-    match app.assignment_record.register(&student_pair, assignment).await {
+    match app.assignment_record.register(&student_pair, assignment, &Local::now().year()).await {
         Ok(_) => {},
         Err(err) => {panic!("{}", err);},
     }
@@ -513,7 +499,7 @@ async fn jwt_does_not_exist() {
     let response = client.get(format!("/api/admin/locker/user-search/{}?familyname=%E3%83%86%E3%82%B9%E3%83%88&givenname=%E5%A4%AA%E9%83%8E", year)).dispatch().await;
 
     // Assert
-    assert_eq!(response.status(), Status::BadRequest);
+    assert_eq!(response.status(), Status::Unauthorized);
 
     setup_db(&app).await;
 }
\ No newline at end of file
@@ -0,0 +1,89 @@
+// テストごとに使用する関数が異なるため、未使用の警告を抑制する
+#![allow(dead_code)]
+
+use std::env;
+use tus_yuurikai_system::domain::admin::AdminRole;
+use tus_yuurikai_system::infrastructure::router::App;
+use tus_yuurikai_system::usecase::admin_session::AdminSessionUsecase;
+use tus_yuurikai_system::utils::jwt::encode_jwt;
+use rocket::{tokio::task, local::asynchronous::Client, http::Cookie};
+use dotenv::dotenv;
+use chrono::Duration;
+use uuid::Uuid;
+
+/// テストで使用するsuperadmin
+pub const TEST_ADMIN: &str = "test_admin";
+
+/// ### register_admin
+/// テスト用の管理者をadminテーブルに登録する、既に存在する場合は権限を更新して有効にする
+///
+/// パスワードにはハッシュ値でない値を保存するため、ログインには使用できない
+pub async fn register_admin(client: &Client, username: &str, role: AdminRole) {
+    let app = client.rocket().state::<App>().expect("app state must be managed");
+    let username = username.to_string();
+    let repository = app.admin.admin_repository.clone();
+    match task::spawn_blocking(move || {
+        match repository.insert(username.clone(), String::from("!"), role) {
+            Ok(admin) => Ok(admin),
+            Err(_) => repository.update(username, None, Some(role), Some(false)),
+        }
+    }).await {
+        Ok(Ok(_)) => {},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err),
+    }
+}
+
+// jwtを保存したCookieを作成する
+fn build_cookie(username: &str, role: AdminRole, session_id: Uuid, exp: Duration) -> Cookie<'static> {
+    dotenv().ok();
+    let key = env::var("TOKEN_KEY").expect("token key must be set");
+    let token = encode_jwt(username, role, session_id, exp, &key);
+    Cookie::build(("token", token))
+        .path("/")
+        .secure(true)
+        .http_only(true)
+        .build()
+}
+
+/// ### token_cookie
+/// 指定した管理者のjwtを保存したCookieを作成する、管理者とセッションは登録しないため、jtiには存在しないセッションのidを使用する
+pub fn token_cookie(username: &str, role: AdminRole, exp: Duration) -> Cookie<'static> {
+    build_cookie(username, role, Uuid::nil(), exp)
+}
+
+/// ### session_cookie
+/// 登録済みの管理者のセッションを作成し、jwtを保存したCookieを作成する
+pub async fn session_cookie(client: &Client, username: &str, role: AdminRole) -> Cookie<'static> {
+    let app = client.rocket().state::<App>().expect("app state must be managed");
+    let session = app.admin_session.start(username, None).await.expect("failed to start admin session");
+    build_cookie(username, role, session.session_id, app.admin_session.policy.idle)
+}
+
+/// ### staff_cookie
+/// 指定した権限の管理者を登録し、jwtを保存したCookieを作成する
+pub async fn staff_cookie(client: &Client, username: &str, role: AdminRole) -> Cookie<'static> {
+    register_admin(client, username, role).await;
+    session_cookie(client, username, role).await
+}
+
+/// ### admin_cookie
+/// superadminのテスト用管理者を登録し、jwtを保存したCookieを作成する
+pub async fn admin_cookie(client: &Client) -> Cookie<'static> {
+    staff_cookie(client, TEST_ADMIN, AdminRole::Superadmin).await
+}
+
+/// ### delete_admin
+/// テストで登録した管理者を削除する
+pub async fn delete_admin(client: &Client, username: &str) {
+    let app = client.rocket().state::<App>().expect("app state must be managed");
+    let username = username.to_string();
+    let repository = app.admin.admin_repository.clone();
+    match task::spawn_blocking(move || {
+        repository.delete_by_name(username)
+    }).await {
+        Ok(Ok(_)) => {},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err),
+    }
+}
@@ -1,3 +1,4 @@
 pub mod router;
 pub mod setup;
-pub mod password_hash;
\ No newline at end of file
+pub mod password_hash;
+pub mod admin;
@@ -1,16 +1,18 @@
 extern crate tus_yuurikai_system;
 
-use tus_yuurikai_system::{infrastructure::router::{App, AppOption}, adapters::controller::ApiDoc};
-use tus_yuurikai_system::adapters::controller::{*, locker::*};
+use tus_yuurikai_system::{infrastructure::{router::{App, AppOption, rocket_config}, mailer::MailTransport}, adapters::controller::ApiDoc};
+use tus_yuurikai_system::adapters::controller::{*, locker::*, circle::*};
 
-use rocket::{routes, Rocket, Build};
+use rocket::{routes, catchers, Rocket, Build};
 use utoipa_swagger_ui::SwaggerUi;
 use utoipa::OpenApi;
 
+// メールは送信待ちに保存されるため、テストではApp::mail_outboxから送信する
 pub fn rocket() -> Rocket<Build> {
-    let app_option = AppOption::new();
+    let mut app_option = AppOption::new();
+    app_option.mail_transport = MailTransport::Memory;
     let app = App::new(app_option);
-    rocket::build()
+    rocket::custom(rocket_config())
         .manage(app)
         .mount(
             "/api",
@@ -20,16 +22,49 @@ pub fn rocket() -> Rocket<Build> {
                 login,
             ]
         )
+        .mount(
+            "/api/admin",
+            routes![
+                auth_sweep_status,
+                mail_template_list,
+                mail_template_get,
+                mail_template_put,
+                mail_template_delete,
+                mail_outbox_list,
+                mail_outbox_retry,
+                mail_outbox_retry_failed,
+                mail_log_search,
+                admin_list,
+                admin_create,
+                admin_update,
+                admin_password_reset,
+                admin_password_change,
+                admin_session_list,
+                admin_session_revoke,
+                admin_session_revoke_all,
+                login_attempt_list,
+            ]
+        )
         .mount(
             "/api/admin/locker",
             routes![
                 user_search,
                 reset,
+                rollover,
+                period_setting_post,
+                locker_list,
+                locker_create,
+                locker_update,
+                locker_delete,
+                locker_import,
+                assignment_create,
+                assignment_update,
             ]
         )
         .mount(
             "/api/admin/circle",
             routes![
+                circle_list,
             ]
         )
         .mount(
@@ -38,17 +73,29 @@ pub fn rocket() -> Rocket<Build> {
                 token_generator,
                 main_auth,
                 co_auth,
+                auth_check,
+                locker_resend,
                 locker_register,
+                cancel_token_generator,
+                cancel,
                 availability,
+                period_setting_get,
             ],
         )
         .mount(
             "/api/circle",
             routes![
+                update_entry,
+                update_token_generator,
+                register_token_generator,
+                circle_main_auth,
+                circle_co_auth,
+                circle_resend,
             ],
         )
         .mount(
             "/",
             SwaggerUi::new("/swagger-ui/<_..>").url("/api-docs/openapi.json", ApiDoc::openapi()),
         )
+        .register("/", catchers![token_expired, unauthorized, forbidden])
 }
\ No newline at end of file
@@ -1,5 +1,8 @@
 use tus_yuurikai_system::infrastructure::router::App;
+use tus_yuurikai_system::domain::locker::LockerStatus;
+use tus_yuurikai_system::usecase::locker::LockerUsecase;
 use rocket::tokio::task;
+use chrono::{Datelike, Local};
 
 pub async fn setup_db(app: &App) {
     let assignment_record_repository = app.assignment_record.assignment_record_repository.clone();
@@ -56,11 +59,53 @@ pub async fn setup_db(app: &App) {
         Err(err) => panic!("{}", err),
     }
 
+    let time_repository = app.time.time_repository.clone();
+    match task::spawn_blocking(move || {
+        time_repository.delete_all()
+    }).await {
+        Ok(Ok(_)) => {},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err),
+    }
+
+    let mail_template_repository = app.mail_template.mail_template_repository.clone();
+    match task::spawn_blocking(move || {
+        mail_template_repository.delete_all()
+    }).await {
+        Ok(Ok(_)) => {},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err),
+    }
+
+    let mail_outbox_repository = app.mail_outbox.mail_outbox_repository.clone();
+    match task::spawn_blocking(move || {
+        mail_outbox_repository.delete_all()
+    }).await {
+        Ok(Ok(_)) => {},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err),
+    }
+
+    let login_attempt_repository = app.login_attempt.login_attempt_repository.clone();
+    match task::spawn_blocking(move || {
+        login_attempt_repository.delete_all()
+    }).await {
+        Ok(Ok(_)) => {},
+        Ok(Err(err)) => panic!("{}", err),
+        Err(err) => panic!("{}", err),
+    }
+
     let locker_repository = app.locker.locker_repository.clone();
     match task::spawn_blocking(move || {
-        locker_repository.update_all_status(String::from("vacant"))
+        locker_repository.update_all_status(LockerStatus::Vacant)
     }).await {
         Ok(Ok(_)) => {},
         _ => panic!("failed to update locker status"),
     }
-}
\ No newline at end of file
+}
+
+// 今年度のロッカーの空き状態を取得する
+pub async fn locker_status(app: &App, locker_id: &str) -> LockerStatus {
+    let lockers = app.locker.get_by_floor(&None, &Local::now().year()).await.unwrap();
+    lockers.into_iter().find(|locker| locker.locker_id == locker_id).unwrap().status
+}